@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS posts_unpublish_at_idx;
+DROP INDEX IF EXISTS posts_publish_at_idx;
+ALTER TABLE posts
+    DROP COLUMN unpublish_at,
+    DROP COLUMN publish_at;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE post_slug_redirects;
+ALTER TABLE posts DROP CONSTRAINT posts_slug_key;
+ALTER TABLE posts DROP COLUMN slug;
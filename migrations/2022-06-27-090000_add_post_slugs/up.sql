@@ -0,0 +1,14 @@
+-- Your SQL goes here
+ALTER TABLE posts ADD COLUMN slug VARCHAR(255);
+UPDATE posts SET slug = 'post-' || id;
+ALTER TABLE posts ALTER COLUMN slug SET NOT NULL;
+ALTER TABLE posts ADD CONSTRAINT posts_slug_key UNIQUE (slug);
+
+CREATE TABLE post_slug_redirects (
+    slug VARCHAR(255) NOT NULL PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT post_slug_redirects_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE post_tags;
+DROP TABLE post_categories;
+DROP TABLE tags;
+DROP TABLE categories;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE posts
+    DROP COLUMN rendered_body,
+    DROP COLUMN body_format;
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+ALTER TABLE posts
+    ADD COLUMN body_format VARCHAR(255) NOT NULL DEFAULT 'plain',
+    ADD COLUMN rendered_body TEXT NOT NULL DEFAULT '';
+
+-- Existing posts are plain text, render them the same way `body_renderer::render_plain` does
+UPDATE posts SET rendered_body = '<p>' || replace(
+    replace(replace(replace(replace(replace(post_body,
+        '&', '&amp;'),
+        '<', '&lt;'),
+        '>', '&gt;'),
+        '"', '&quot;'),
+        '''', '&#39;'),
+    E'\n', '<br>') || '</p>';
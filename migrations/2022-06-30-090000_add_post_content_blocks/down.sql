@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE posts
+    DROP COLUMN content_text,
+    DROP COLUMN content_html,
+    DROP COLUMN content_blocks;
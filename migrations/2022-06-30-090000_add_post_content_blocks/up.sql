@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE posts
+    ADD COLUMN content_blocks JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN content_html TEXT NOT NULL DEFAULT '',
+    ADD COLUMN content_text TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,34 @@
+-- Your SQL goes here
+CREATE TABLE media (
+    id SERIAL NOT NULL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    storage_key VARCHAR(512) NOT NULL UNIQUE,
+    url TEXT NOT NULL,
+    file_name VARCHAR(255) NOT NULL,
+    mime_type VARCHAR(255) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    width INTEGER,
+    height INTEGER,
+    checksum VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT media_user_id_fkey FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX media_user_id_idx ON media (user_id);
+
+CREATE TABLE post_media (
+    post_id INTEGER NOT NULL,
+    media_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, media_id),
+    CONSTRAINT post_media_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT post_media_media_id_fkey FOREIGN KEY (media_id)
+        REFERENCES media(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX post_media_media_id_idx ON post_media (media_id);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE media_variants;
+
+ALTER TABLE media
+    DROP COLUMN processing_status,
+    DROP COLUMN blurhash;
@@ -0,0 +1,24 @@
+-- Your SQL goes here
+ALTER TABLE media
+    ADD COLUMN processing_status VARCHAR(32) NOT NULL DEFAULT 'pending',
+    ADD COLUMN blurhash VARCHAR(128);
+
+-- files uploaded before image processing existed are left as they are
+UPDATE media SET processing_status = 'skipped';
+
+CREATE TABLE media_variants (
+    id SERIAL NOT NULL PRIMARY KEY,
+    media_id INTEGER NOT NULL,
+    storage_key VARCHAR(512) NOT NULL UNIQUE,
+    url TEXT NOT NULL,
+    mime_type VARCHAR(255) NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT media_variants_media_id_fkey FOREIGN KEY (media_id)
+        REFERENCES media(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX media_variants_media_id_idx ON media_variants (media_id);
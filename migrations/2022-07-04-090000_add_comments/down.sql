@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE comments;
+
+ALTER TABLE posts DROP COLUMN comments_enabled;
@@ -0,0 +1,32 @@
+-- Your SQL goes here
+ALTER TABLE posts ADD COLUMN comments_enabled BOOLEAN NOT NULL DEFAULT TRUE;
+
+CREATE TABLE comments (
+    id SERIAL NOT NULL PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    -- direct parent, NULL for top level comments
+    parent_id INTEGER,
+    -- top level comment of the thread, NULL for top level comments
+    root_id INTEGER,
+    body TEXT NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT comments_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT comments_user_id_fkey FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+    CONSTRAINT comments_parent_id_fkey FOREIGN KEY (parent_id)
+        REFERENCES comments(id)
+        ON DELETE CASCADE,
+    CONSTRAINT comments_root_id_fkey FOREIGN KEY (root_id)
+        REFERENCES comments(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX comments_post_id_idx ON comments (post_id, created_at);
+CREATE INDEX comments_root_id_idx ON comments (root_id);
+CREATE INDEX comments_status_idx ON comments (status);
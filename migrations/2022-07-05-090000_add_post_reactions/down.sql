@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE post_reactions;
+
+ALTER TABLE posts DROP COLUMN reaction_counts;
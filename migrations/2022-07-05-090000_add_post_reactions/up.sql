@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- number of reactions per emoji, kept in sync with `post_reactions`
+ALTER TABLE posts ADD COLUMN reaction_counts JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+CREATE TABLE post_reactions (
+    post_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    emoji VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, user_id),
+    CONSTRAINT post_reactions_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT post_reactions_user_id_fkey FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX post_reactions_user_id_idx ON post_reactions (user_id);
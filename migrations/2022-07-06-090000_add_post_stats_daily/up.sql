@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- unique visitors per post and day, rolled up from redis
+CREATE TABLE post_stats_daily (
+    post_id INTEGER NOT NULL,
+    day DATE NOT NULL,
+    views INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, day),
+    CONSTRAINT post_stats_daily_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX post_stats_daily_day_idx ON post_stats_daily (day);
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+CREATE TABLE post_collaborators (
+    post_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    -- one of `co-author`, `editor` or `viewer`
+    role VARCHAR(16) NOT NULL,
+    invited_by INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, user_id),
+    CONSTRAINT post_collaborators_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT post_collaborators_user_id_fkey FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+    CONSTRAINT post_collaborators_invited_by_fkey FOREIGN KEY (invited_by)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX post_collaborators_user_id_idx ON post_collaborators (user_id);
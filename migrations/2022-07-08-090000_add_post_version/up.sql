@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- bumped on every change to a post, used as its ETag
+ALTER TABLE posts ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- autosaves live in redis, this table is only used when redis is not available
+CREATE TABLE post_autosaves (
+    post_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    -- bumped on every save, to tell saves from different tabs apart
+    revision BIGINT NOT NULL,
+    -- version of the post the autosave was started from
+    post_version INTEGER NOT NULL,
+    subject TEXT,
+    body TEXT,
+    body_format VARCHAR(16),
+    saved_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, user_id),
+    CONSTRAINT post_autosaves_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT post_autosaves_user_id_fkey FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
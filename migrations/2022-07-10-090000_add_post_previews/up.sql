@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- preview links handed out for drafts, the signed token refers to a row here so that it can be revoked
+CREATE TABLE post_previews (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    -- version of the post the link shows
+    post_version INTEGER NOT NULL,
+    created_by INTEGER NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT post_previews_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT post_previews_created_by_fkey FOREIGN KEY (created_by)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX post_previews_post_id_idx ON post_previews (post_id);
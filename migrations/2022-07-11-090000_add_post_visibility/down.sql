@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE posts DROP COLUMN access_password_hash;
+ALTER TABLE posts DROP COLUMN visibility;
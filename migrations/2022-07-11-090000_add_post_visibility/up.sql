@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- one of `public`, `unlisted`, `members-only` or `password-protected`
+ALTER TABLE posts ADD COLUMN visibility VARCHAR(32) NOT NULL DEFAULT 'public';
+-- argon2 hash, only set for `password-protected` posts
+ALTER TABLE posts ADD COLUMN access_password_hash VARCHAR;
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+-- translations of a post, each one is reviewed and published on its own
+CREATE TABLE post_translations (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    -- lowercase language tag, e.g. `de` or `pt-br`
+    locale VARCHAR(16) NOT NULL,
+    post_subject VARCHAR NOT NULL,
+    post_body TEXT NOT NULL,
+    body_format VARCHAR(16) NOT NULL DEFAULT 'plain',
+    rendered_body TEXT NOT NULL DEFAULT '',
+    slug VARCHAR NOT NULL,
+    published_status VARCHAR(255) NOT NULL,
+    created_by INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT post_translations_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT post_translations_created_by_fkey FOREIGN KEY (created_by)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+    CONSTRAINT post_translations_post_id_locale_key UNIQUE (post_id, locale),
+    CONSTRAINT post_translations_slug_key UNIQUE (slug)
+);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX posts_content_type_id_idx;
+ALTER TABLE posts DROP CONSTRAINT posts_content_type_id_fkey;
+ALTER TABLE posts DROP COLUMN fields;
+ALTER TABLE posts DROP COLUMN content_type_id;
+DROP TABLE content_types;
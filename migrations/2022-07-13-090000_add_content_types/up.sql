@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- shapes of content besides blog posts, e.g. events or job openings
+CREATE TABLE content_types (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    slug VARCHAR NOT NULL UNIQUE,
+    -- {"fields": [{"name": "starts_at", "type": "date", "required": true}, ...]}
+    field_schema JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- entries of a content type are posts, so that they go through the same publishing workflow
+ALTER TABLE posts ADD COLUMN content_type_id INTEGER;
+ALTER TABLE posts ADD COLUMN fields JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE posts ADD CONSTRAINT posts_content_type_id_fkey FOREIGN KEY (content_type_id)
+    REFERENCES content_types(id);
+CREATE INDEX posts_content_type_id_idx ON posts (content_type_id);
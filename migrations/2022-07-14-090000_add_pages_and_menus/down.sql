@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE menu_items;
+DROP TABLE menus;
+DROP TABLE pages;
@@ -0,0 +1,66 @@
+-- Your SQL goes here
+-- static pages such as About or Contact, arranged in a tree
+CREATE TABLE pages (
+    id SERIAL PRIMARY KEY,
+    title VARCHAR NOT NULL,
+    body TEXT NOT NULL DEFAULT '',
+    body_format VARCHAR(16) NOT NULL DEFAULT 'plain',
+    rendered_body TEXT NOT NULL DEFAULT '',
+    slug VARCHAR NOT NULL UNIQUE,
+    parent_id INTEGER,
+    -- order among the pages with the same parent, starting at 0
+    position INTEGER NOT NULL DEFAULT 0,
+    published BOOLEAN NOT NULL DEFAULT FALSE,
+    created_by INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT pages_parent_id_fkey FOREIGN KEY (parent_id)
+        REFERENCES pages(id)
+        ON DELETE SET NULL,
+    CONSTRAINT pages_created_by_fkey FOREIGN KEY (created_by)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX pages_parent_id_position_idx ON pages (parent_id, position);
+
+CREATE TABLE menus (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    slug VARCHAR NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- each item points to exactly one of a page, a post, a category or an external url
+CREATE TABLE menu_items (
+    id SERIAL PRIMARY KEY,
+    menu_id INTEGER NOT NULL,
+    parent_id INTEGER,
+    position INTEGER NOT NULL DEFAULT 0,
+    label VARCHAR NOT NULL,
+    page_id INTEGER,
+    post_id INTEGER,
+    category_id INTEGER,
+    url VARCHAR,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT menu_items_menu_id_fkey FOREIGN KEY (menu_id)
+        REFERENCES menus(id)
+        ON DELETE CASCADE,
+    CONSTRAINT menu_items_parent_id_fkey FOREIGN KEY (parent_id)
+        REFERENCES menu_items(id)
+        ON DELETE CASCADE,
+    CONSTRAINT menu_items_page_id_fkey FOREIGN KEY (page_id)
+        REFERENCES pages(id)
+        ON DELETE CASCADE,
+    CONSTRAINT menu_items_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT menu_items_category_id_fkey FOREIGN KEY (category_id)
+        REFERENCES categories(id)
+        ON DELETE CASCADE,
+    CONSTRAINT menu_items_single_target CHECK (num_nonnulls(page_id, post_id, category_id, url) = 1)
+);
+
+CREATE INDEX menu_items_menu_id_idx ON menu_items (menu_id);
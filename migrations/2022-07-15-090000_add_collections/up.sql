@@ -0,0 +1,33 @@
+-- Your SQL goes here
+-- ordered lists of posts, either a series such as a multi-part tutorial or a curated collection
+CREATE TABLE collections (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    slug VARCHAR NOT NULL UNIQUE,
+    kind VARCHAR(16) NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    -- posts of pinned collections come first on the home listing
+    pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    user_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT collections_user_id_fkey FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE collection_posts (
+    collection_id INTEGER NOT NULL,
+    post_id INTEGER NOT NULL,
+    -- order within the collection, starting at 0
+    position INTEGER NOT NULL,
+    PRIMARY KEY (collection_id, post_id),
+    CONSTRAINT collection_posts_collection_id_fkey FOREIGN KEY (collection_id)
+        REFERENCES collections(id)
+        ON DELETE CASCADE,
+    CONSTRAINT collection_posts_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES posts(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX collection_posts_post_id_idx ON collection_posts (post_id);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX posts_updated_at_idx;
+
+DROP EXTENSION IF EXISTS pg_trgm;
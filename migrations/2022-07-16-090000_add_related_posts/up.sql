@@ -0,0 +1,5 @@
+-- Your SQL goes here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- related posts are cached until the most recently updated post changes
+CREATE INDEX posts_updated_at_idx ON posts (updated_at);
@@ -0,0 +1,124 @@
+use crate::auth::actor::RedisPool;
+use actix::{Actor, Handler, Message, SyncContext};
+use r2d2_redis::redis::{self, Commands, RedisError};
+use r2d2_redis::RedisConnectionManager;
+
+/// Keys outlive their day a little, so that the last rollup of a day still finds them.
+const VIEW_KEYS_TTL_SECS: usize = 3 * 24 * 60 * 60;
+
+/// Counts unique visitors per post and day with redis HyperLogLogs. Views
+/// are not counted at all when redis is not available.
+#[derive(Clone)]
+pub struct ViewCounter {
+    redis_pool: Option<RedisPool>,
+}
+
+impl ViewCounter {
+    pub fn new(redis_server_url: String, redis_server_get_connection_timeout: u64) -> Self {
+        let redis_pool = RedisConnectionManager::new(redis_server_url)
+            .map_err(|err| err.to_string())
+            .and_then(|manager| {
+                r2d2::Pool::builder()
+                    .connection_timeout(std::time::Duration::from_secs(
+                        redis_server_get_connection_timeout,
+                    ))
+                    .build(manager)
+                    .map_err(|err| err.to_string())
+            });
+        match redis_pool {
+            Ok(redis_pool) => ViewCounter {
+                redis_pool: Some(redis_pool),
+            },
+            Err(err) => {
+                log::error!(
+                    "Failed connecting to redis, views will not be counted: {}",
+                    err
+                );
+                ViewCounter { redis_pool: None }
+            }
+        }
+    }
+
+    fn views_key(day: chrono::NaiveDate, post_id: i32) -> String {
+        format!("post_views:{}:{}", day, post_id)
+    }
+
+    /// Set of the posts that got views on `day`
+    fn viewed_posts_key(day: chrono::NaiveDate) -> String {
+        format!("post_views:{}:posts", day)
+    }
+}
+
+impl Actor for ViewCounter {
+    type Context = SyncContext<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), RedisError>")]
+pub struct RecordView {
+    pub post_id: i32,
+    /// Anything that identifies the visitor for the day
+    pub visitor_id: String,
+    pub day: chrono::NaiveDate,
+}
+
+impl Handler<RecordView> for ViewCounter {
+    type Result = Result<(), RedisError>;
+
+    fn handle(&mut self, msg: RecordView, _: &mut Self::Context) -> Self::Result {
+        let mut conn = match &self.redis_pool {
+            None => return Ok(()),
+            Some(redis_pool) => match redis_pool.get() {
+                Ok(conn) => conn,
+                Err(err) => {
+                    log::error!("Failed getting redis connection: {}", err);
+                    return Ok(());
+                }
+            },
+        };
+        let views_key = Self::views_key(msg.day, msg.post_id);
+        let viewed_posts_key = Self::viewed_posts_key(msg.day);
+        redis::pipe()
+            .atomic()
+            .pfadd(&views_key, msg.visitor_id)
+            .ignore()
+            .expire(&views_key, VIEW_KEYS_TTL_SECS)
+            .ignore()
+            .sadd(&viewed_posts_key, msg.post_id)
+            .ignore()
+            .expire(&viewed_posts_key, VIEW_KEYS_TTL_SECS)
+            .ignore()
+            .query(&mut *conn)
+    }
+}
+
+/// Unique visitors of `day` so far, as `(post_id, views)`.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<(i32, i64)>, RedisError>")]
+pub struct GetDailyViews {
+    pub day: chrono::NaiveDate,
+}
+
+impl Handler<GetDailyViews> for ViewCounter {
+    type Result = Result<Vec<(i32, i64)>, RedisError>;
+
+    fn handle(&mut self, msg: GetDailyViews, _: &mut Self::Context) -> Self::Result {
+        let mut conn = match &self.redis_pool {
+            None => return Ok(vec![]),
+            Some(redis_pool) => match redis_pool.get() {
+                Ok(conn) => conn,
+                Err(err) => {
+                    log::error!("Failed getting redis connection: {}", err);
+                    return Ok(vec![]);
+                }
+            },
+        };
+        let post_ids: Vec<i32> = conn.smembers(Self::viewed_posts_key(msg.day))?;
+        let mut views_by_post = Vec::with_capacity(post_ids.len());
+        for post_id in post_ids {
+            let views: i64 = conn.pfcount(Self::views_key(msg.day, post_id))?;
+            views_by_post.push((post_id, views));
+        }
+        Ok(views_by_post)
+    }
+}
@@ -0,0 +1,38 @@
+/// Lowercase fragments of user agents that are not people reading posts.
+const BOT_USER_AGENT_FRAGMENTS: &[&str] = &[
+    "bot",
+    "crawler",
+    "spider",
+    "slurp",
+    "scraper",
+    "headless",
+    "preview",
+    "facebookexternalhit",
+    "embedly",
+    "curl/",
+    "wget/",
+    "python-requests",
+    "python-urllib",
+    "go-http-client",
+    "java/",
+    "okhttp",
+    "axios/",
+    "node-fetch",
+    "lighthouse",
+    "pingdom",
+    "uptime",
+];
+
+/// Best effort check, requests without a user agent are treated as bots too.
+pub fn is_bot(user_agent: Option<&str>) -> bool {
+    match user_agent {
+        None => true,
+        Some(user_agent) => {
+            let user_agent = user_agent.to_lowercase();
+            user_agent.trim().is_empty()
+                || BOT_USER_AGENT_FRAGMENTS
+                    .iter()
+                    .any(|fragment| user_agent.contains(fragment))
+        }
+    }
+}
@@ -97,6 +97,23 @@ impl AuthManager {
         }
     }
 
+    /// Tokens with their own expiry are never cached, each call makes a new one.
+    pub fn create_expiring_token<T: Serialize>(
+        &self,
+        data: T,
+        expiration_duration: u32,
+    ) -> Option<String> {
+        use AuthManager::*;
+        match self {
+            SimpleAuthManager(jwt_auth_mgr) => {
+                jwt_auth_mgr.create_token_expiring_in::<T>(data, expiration_duration)
+            }
+            RedisAuthManager(jwt_auth_mgr, _) => {
+                jwt_auth_mgr.create_token_expiring_in::<T>(data, expiration_duration)
+            }
+        }
+    }
+
     fn decode_token<'a, T: DeserializeOwned>(
         &self,
         token: &'a str,
@@ -137,6 +154,22 @@ impl<T: Serialize + DeserializeOwned + ToRedisArgs + std::fmt::Debug + Copy> Han
     }
 }
 
+#[derive(Message)]
+#[rtype(result = "Option<String>")]
+pub struct CreateExpiringToken<T: Serialize + std::fmt::Debug> {
+    pub data: T,
+    /// Seconds the token stays valid for
+    pub expiration_duration: u32,
+}
+
+impl<T: Serialize + std::fmt::Debug> Handler<CreateExpiringToken<T>> for AuthManager {
+    type Result = Option<String>;
+
+    fn handle(&mut self, msg: CreateExpiringToken<T>, _: &mut Self::Context) -> Self::Result {
+        self.create_expiring_token(msg.data, msg.expiration_duration)
+    }
+}
+
 #[derive(Message)]
 #[rtype(result = "jwt::errors::Result<T>")]
 pub struct ExtractClaim<T: 'static + DeserializeOwned + std::fmt::Debug> {
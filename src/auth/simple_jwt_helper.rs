@@ -28,8 +28,17 @@ impl SimpleJWT {
     }
 
     pub fn create_token<T: Serialize>(&self, data: T) -> Option<String> {
+        self.create_token_expiring_in(data, self.expiration_duration)
+    }
+
+    /// Like `create_token`, valid for `expiration_duration` seconds instead of the default.
+    pub fn create_token_expiring_in<T: Serialize>(
+        &self,
+        data: T,
+        expiration_duration: u32,
+    ) -> Option<String> {
         let expiration = Utc::now()
-            .checked_add_signed(chrono::Duration::seconds(self.expiration_duration as i64))
+            .checked_add_signed(chrono::Duration::seconds(expiration_duration as i64))
             .expect("Valid Timestamp")
             .timestamp();
         let claims = Claims {
@@ -52,7 +61,7 @@ impl SimpleJWT {
     ) -> jwt::errors::Result<TokenData<T>> {
         let validation = Validation::new(self.algorithm);
         decode::<T>(
-            &token,
+            token,
             &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
             &validation,
         )
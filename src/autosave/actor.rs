@@ -0,0 +1,181 @@
+use crate::auth::actor::RedisPool;
+use crate::db::models::autosaves::PostAutosave;
+use actix::{Actor, Handler, Message, SyncContext};
+use derive_more::Display;
+use r2d2_redis::redis::{self, Commands, RedisError};
+use r2d2_redis::RedisConnectionManager;
+
+/// Autosaves nobody came back to are dropped after a week.
+const AUTOSAVE_TTL_SECS: usize = 7 * 24 * 60 * 60;
+
+/// Saves only when the stored revision is still the one the client started from.
+/// Returns `{1, ''}` once saved, `{0, current}` on a conflict.
+const SAVE_IF_REVISION_SCRIPT: &str = r"
+local current = redis.call('GET', KEYS[1])
+local revision = 0
+if current then
+    revision = cjson.decode(current)['revision']
+end
+if revision ~= tonumber(ARGV[1]) then
+    return {0, current or ''}
+end
+redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+return {1, ''}
+";
+
+#[derive(Debug, Display)]
+pub enum AutosaveStoreError {
+    #[display(fmt = "{}", _0)]
+    Pool(r2d2::Error),
+
+    #[display(fmt = "{}", _0)]
+    Redis(RedisError),
+
+    #[display(fmt = "{}", _0)]
+    Diesel(diesel::result::Error),
+
+    #[display(fmt = "{}", _0)]
+    Serde(serde_json::Error),
+}
+
+impl From<RedisError> for AutosaveStoreError {
+    fn from(err: RedisError) -> Self {
+        AutosaveStoreError::Redis(err)
+    }
+}
+
+impl From<diesel::result::Error> for AutosaveStoreError {
+    fn from(err: diesel::result::Error) -> Self {
+        AutosaveStoreError::Diesel(err)
+    }
+}
+
+impl From<serde_json::Error> for AutosaveStoreError {
+    fn from(err: serde_json::Error) -> Self {
+        AutosaveStoreError::Serde(err)
+    }
+}
+
+pub enum SaveOutcome {
+    Saved(PostAutosave),
+    /// Another tab saved in between, holds what it saved if anything is left
+    Conflict(Option<PostAutosave>),
+}
+
+/// Keeps autosaves in redis. The `DbActor` handles the same messages, and is used instead
+/// when redis is not available.
+#[derive(Clone)]
+pub struct AutosaveStore {
+    redis_pool: RedisPool,
+}
+
+impl AutosaveStore {
+    /// `None` when redis cannot be reached.
+    pub fn new(redis_server_url: String, redis_server_get_connection_timeout: u64) -> Option<Self> {
+        let redis_pool = RedisConnectionManager::new(redis_server_url)
+            .map_err(|err| err.to_string())
+            .and_then(|manager| {
+                r2d2::Pool::builder()
+                    .connection_timeout(std::time::Duration::from_secs(
+                        redis_server_get_connection_timeout,
+                    ))
+                    .build(manager)
+                    .map_err(|err| err.to_string())
+            });
+        match redis_pool {
+            Ok(redis_pool) => Some(AutosaveStore {
+                redis_pool: redis_pool,
+            }),
+            Err(err) => {
+                log::error!(
+                    "Failed connecting to redis, autosaves will be kept in postgres: {}",
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    fn autosave_key(post_id: i32, user_id: i32) -> String {
+        format!("post_autosave:{}:{}", post_id, user_id)
+    }
+
+    fn get_conn(
+        &self,
+    ) -> Result<r2d2::PooledConnection<RedisConnectionManager>, AutosaveStoreError> {
+        self.redis_pool.get().map_err(AutosaveStoreError::Pool)
+    }
+}
+
+impl Actor for AutosaveStore {
+    type Context = SyncContext<Self>;
+}
+
+/// Stores `autosave` if the current revision is still `base_revision`, 0 when there is none.
+#[derive(Message, Clone)]
+#[rtype(result = "Result<SaveOutcome, AutosaveStoreError>")]
+pub struct SaveAutosave {
+    pub autosave: PostAutosave,
+    pub base_revision: i64,
+}
+
+impl Handler<SaveAutosave> for AutosaveStore {
+    type Result = Result<SaveOutcome, AutosaveStoreError>;
+
+    fn handle(&mut self, msg: SaveAutosave, _: &mut Self::Context) -> Self::Result {
+        let mut conn = self.get_conn()?;
+        let (saved, current): (i32, String) = redis::Script::new(SAVE_IF_REVISION_SCRIPT)
+            .key(Self::autosave_key(
+                msg.autosave.post_id,
+                msg.autosave.user_id,
+            ))
+            .arg(msg.base_revision)
+            .arg(serde_json::to_string(&msg.autosave)?)
+            .arg(AUTOSAVE_TTL_SECS)
+            .invoke(&mut *conn)?;
+        if saved == 1 {
+            return Ok(SaveOutcome::Saved(msg.autosave));
+        }
+        match current.as_str() {
+            "" => Ok(SaveOutcome::Conflict(None)),
+            current => Ok(SaveOutcome::Conflict(Some(serde_json::from_str(current)?))),
+        }
+    }
+}
+
+#[derive(Message, Clone)]
+#[rtype(result = "Result<Option<PostAutosave>, AutosaveStoreError>")]
+pub struct GetAutosave {
+    pub post_id: i32,
+    pub user_id: i32,
+}
+
+impl Handler<GetAutosave> for AutosaveStore {
+    type Result = Result<Option<PostAutosave>, AutosaveStoreError>;
+
+    fn handle(&mut self, msg: GetAutosave, _: &mut Self::Context) -> Self::Result {
+        let mut conn = self.get_conn()?;
+        let autosave: Option<String> = conn.get(Self::autosave_key(msg.post_id, msg.user_id))?;
+        match autosave {
+            None => Ok(None),
+            Some(autosave) => Ok(Some(serde_json::from_str(&autosave)?)),
+        }
+    }
+}
+
+#[derive(Message, Clone)]
+#[rtype(result = "Result<(), AutosaveStoreError>")]
+pub struct DiscardAutosave {
+    pub post_id: i32,
+    pub user_id: i32,
+}
+
+impl Handler<DiscardAutosave> for AutosaveStore {
+    type Result = Result<(), AutosaveStoreError>;
+
+    fn handle(&mut self, msg: DiscardAutosave, _: &mut Self::Context) -> Self::Result {
+        let mut conn = self.get_conn()?;
+        let _: () = conn.del(Self::autosave_key(msg.post_id, msg.user_id))?;
+        Ok(())
+    }
+}
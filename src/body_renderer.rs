@@ -0,0 +1,122 @@
+use crate::db::models::posts::BodyFormat;
+use ammonia::Builder;
+use pulldown_cmark::{html, Options, Parser};
+
+/// Renders a post body to html that is safe to embed into a page as is.
+pub fn render_body<'a>(body: &'a str, body_format: &BodyFormat) -> String {
+    match body_format {
+        BodyFormat::Plain => render_plain(body),
+        BodyFormat::Markdown => sanitize(&render_markdown(body)),
+        BodyFormat::Html => sanitize(body),
+    }
+}
+
+pub fn escape_html<'a>(text: &'a str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the text and keeps its line breaks.
+pub fn render_plain<'a>(body: &'a str) -> String {
+    format!("<p>{}</p>", escape_html(body).replace('\n', "<br>"))
+}
+
+/// Markdown to (unsanitized) html with the GitHub flavoured extensions.
+fn render_markdown<'a>(body: &'a str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+
+    let mut rendered = String::with_capacity(body.len() * 3 / 2);
+    html::push_html(&mut rendered, Parser::new_ext(body, options));
+    rendered
+}
+
+/// Strips everything that is not on the allowlist, e.g. `<script>`, `on*` handlers and
+/// `javascript:` urls.
+fn sanitize<'a>(html: &'a str) -> String {
+    Builder::default()
+        // task list checkboxes
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        // syntax highlighting hints, e.g. `language-rust`
+        .add_tag_attributes("code", &["class"])
+        .clean(html)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_script_tags() {
+        for format in &[BodyFormat::Html, BodyFormat::Markdown] {
+            let rendered = render_body("<p>hi</p><script>alert(1)</script>", format);
+            assert!(!rendered.contains("<script"), "{}", rendered);
+            assert!(!rendered.contains("alert(1)"), "{}", rendered);
+            assert!(rendered.contains("hi"));
+        }
+    }
+
+    #[test]
+    fn strips_javascript_links() {
+        let rendered = render_body(
+            r#"<a href="javascript:alert(1)">click</a>"#,
+            &BodyFormat::Html,
+        );
+        assert!(!rendered.contains("javascript:"), "{}", rendered);
+        assert!(rendered.contains("click"));
+
+        let rendered = render_body("[click](javascript:alert(1))", &BodyFormat::Markdown);
+        assert!(!rendered.contains("javascript:"), "{}", rendered);
+    }
+
+    #[test]
+    fn keeps_safe_links() {
+        let rendered = render_body("[docs](https://example.com)", &BodyFormat::Markdown);
+        assert!(
+            rendered.contains(r#"href="https://example.com""#),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn strips_event_handlers() {
+        let rendered = render_body(
+            r#"<img src="x.png" onerror="alert(1)"><p onclick="alert(2)">hi</p>"#,
+            &BodyFormat::Html,
+        );
+        assert!(!rendered.contains("onerror"), "{}", rendered);
+        assert!(!rendered.contains("onclick"), "{}", rendered);
+        assert!(!rendered.contains("alert"), "{}", rendered);
+    }
+
+    #[test]
+    fn keeps_task_list_checkboxes() {
+        let rendered = render_body("- [x] done", &BodyFormat::Markdown);
+        assert!(rendered.contains("<input"), "{}", rendered);
+        assert!(rendered.contains("checked"), "{}", rendered);
+    }
+
+    #[test]
+    fn escapes_plain_bodies() {
+        assert_eq!(
+            render_body("<b>a</b> & \"b\"\nc", &BodyFormat::Plain),
+            "<p>&lt;b&gt;a&lt;/b&gt; &amp; &quot;b&quot;<br>c</p>"
+        );
+    }
+}
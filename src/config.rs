@@ -1,6 +1,8 @@
+use crate::image_processing::actor::VariantFormat;
 use dotenv::dotenv;
 
 use serde::Deserialize;
+use std::net::IpAddr;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
@@ -12,6 +14,44 @@ pub struct Config {
     pub redis_server_url: String,
     pub redis_server_get_connection_timeout: u64,
     pub cors_allow_all: bool,
+    pub post_scheduler_interval: u64,
+    pub trash_purge_interval: u64,
+    pub trash_retention_days: i32,
+    pub storage: StorageConfig,
+    pub media_max_size_bytes: usize,
+    pub media_allowed_types: Vec<String>,
+    pub image_variant_widths: Vec<u32>,
+    pub image_variant_formats: Vec<VariantFormat>,
+    pub image_variant_quality: u8,
+    pub comment_trusted_after_approved: i64,
+    pub reaction_emojis: Vec<String>,
+    pub view_rollup_interval: u64,
+    /// Reverse proxies whose `Forwarded`/`X-Forwarded-For` headers are believed
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Locale posts are written in, translations are into the other `supported_locales`
+    pub default_locale: String,
+    pub supported_locales: Vec<String>,
+    /// Tried in order after the locales a reader asked for
+    pub locale_fallbacks: Vec<String>,
+    /// Public address of the site, feeds link to posts below it
+    pub site_url: String,
+    pub site_title: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub enum StorageConfig {
+    LocalFs {
+        root: String,
+        base_url: String,
+    },
+    S3 {
+        bucket: String,
+        region: String,
+        endpoint: String,
+        access_key: String,
+        secret_key: String,
+        public_url: Option<String>,
+    },
 }
 
 fn env_var_not_set_msg(env_var: &str) -> String {
@@ -30,7 +70,7 @@ fn env_var_parsing_error_msg(env_var: &str) -> String {
 
 fn read_from_env<T: std::fmt::Display + std::str::FromStr + std::fmt::Debug>(env_var: &str) -> T {
     match std::env::var(env_var)
-        .expect(env_var_not_set_msg(env_var).as_str())
+        .unwrap_or_else(|_| panic!("{}", env_var_not_set_msg(env_var)))
         .parse()
     {
         Ok(parse_value) => parse_value,
@@ -41,6 +81,50 @@ fn read_from_env<T: std::fmt::Display + std::str::FromStr + std::fmt::Debug>(env
     }
 }
 
+fn read_optional_from_env<T: std::fmt::Display + std::str::FromStr + std::fmt::Debug>(
+    env_var: &str,
+) -> Option<T> {
+    std::env::var(env_var).ok().map(|_| read_from_env(env_var))
+}
+
+/// Reads a comma separated list, e.g. `320,640,1024`
+fn read_list_from_env<T: std::str::FromStr>(env_var: &str) -> Vec<T> {
+    read_from_env::<String>(env_var)
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| match item.parse() {
+            Ok(parse_value) => parse_value,
+            Err(_) => {
+                log::error!("{}", env_var_parsing_error_msg(env_var));
+                panic!()
+            }
+        })
+        .collect()
+}
+
+fn read_storage_config() -> StorageConfig {
+    let storage_backend: String = read_from_env("STORAGE_BACKEND");
+    match storage_backend.as_str() {
+        "local" => StorageConfig::LocalFs {
+            root: read_from_env("LOCAL_STORAGE_PATH"),
+            base_url: read_from_env("LOCAL_STORAGE_BASE_URL"),
+        },
+        "s3" => StorageConfig::S3 {
+            bucket: read_from_env("S3_BUCKET"),
+            region: read_from_env("S3_REGION"),
+            endpoint: read_from_env("S3_ENDPOINT"),
+            access_key: read_from_env("S3_ACCESS_KEY"),
+            secret_key: read_from_env("S3_SECRET_KEY"),
+            public_url: read_optional_from_env("S3_PUBLIC_URL"),
+        },
+        _ => {
+            log::error!("{}", env_var_parsing_error_msg("STORAGE_BACKEND"));
+            panic!()
+        }
+    }
+}
+
 impl Config {
     pub fn from_env() -> Config {
         dotenv().ok();
@@ -55,6 +139,35 @@ impl Config {
         let redis_server_get_connection_timeout: u64 =
             read_from_env("REDIS_SERVER_GET_CONNECTION_TIMEOUT");
         let cors_allow_all: bool = read_from_env::<u8>("CORS_ALLOW_ALL") == 1;
+        let post_scheduler_interval: u64 = read_from_env("POST_SCHEDULER_INTERVAL");
+        let trash_purge_interval: u64 = read_from_env("TRASH_PURGE_INTERVAL");
+        let trash_retention_days: i32 = read_from_env("TRASH_RETENTION_DAYS");
+        let storage = read_storage_config();
+        let media_max_size_bytes: usize = read_from_env("MEDIA_MAX_SIZE_BYTES");
+        let media_allowed_types: Vec<String> = read_list_from_env("MEDIA_ALLOWED_TYPES");
+        let image_variant_widths: Vec<u32> = read_list_from_env("IMAGE_VARIANT_WIDTHS");
+        let image_variant_formats: Vec<VariantFormat> = read_list_from_env("IMAGE_VARIANT_FORMATS");
+        let image_variant_quality: u8 = read_from_env("IMAGE_VARIANT_QUALITY");
+        let comment_trusted_after_approved: i64 = read_from_env("COMMENT_TRUSTED_AFTER_APPROVED");
+        let reaction_emojis: Vec<String> = read_list_from_env("REACTION_EMOJIS");
+        let view_rollup_interval: u64 = read_from_env("VIEW_ROLLUP_INTERVAL");
+        let trusted_proxies: Vec<IpAddr> = read_list_from_env("TRUSTED_PROXIES");
+        let default_locale: String = read_from_env::<String>("DEFAULT_LOCALE").to_lowercase();
+        let mut supported_locales: Vec<String> = read_list_from_env::<String>("SUPPORTED_LOCALES")
+            .iter()
+            .map(|locale| locale.to_lowercase())
+            .collect();
+        if !supported_locales.contains(&default_locale) {
+            supported_locales.push(default_locale.clone());
+        }
+        let locale_fallbacks: Vec<String> = read_list_from_env::<String>("LOCALE_FALLBACKS")
+            .iter()
+            .map(|locale| locale.to_lowercase())
+            .collect();
+        let site_url: String = read_from_env::<String>("SITE_URL")
+            .trim_end_matches('/')
+            .to_string();
+        let site_title: String = read_from_env("SITE_TITLE");
 
         Config {
             host: host,
@@ -65,6 +178,24 @@ impl Config {
             redis_server_url: redis_server_url,
             redis_server_get_connection_timeout: redis_server_get_connection_timeout,
             cors_allow_all: cors_allow_all,
+            post_scheduler_interval: post_scheduler_interval,
+            trash_purge_interval: trash_purge_interval,
+            trash_retention_days: trash_retention_days,
+            storage: storage,
+            media_max_size_bytes: media_max_size_bytes,
+            media_allowed_types: media_allowed_types,
+            image_variant_widths: image_variant_widths,
+            image_variant_formats: image_variant_formats,
+            image_variant_quality: image_variant_quality,
+            comment_trusted_after_approved: comment_trusted_after_approved,
+            reaction_emojis: reaction_emojis,
+            view_rollup_interval: view_rollup_interval,
+            trusted_proxies: trusted_proxies,
+            default_locale: default_locale,
+            supported_locales: supported_locales,
+            locale_fallbacks: locale_fallbacks,
+            site_url: site_url,
+            site_title: site_title,
         }
     }
 }
@@ -0,0 +1,458 @@
+use crate::body_renderer::escape_html as escape;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+const MAX_BLOCKS: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    /// Generated by the server when left empty
+    #[serde(default)]
+    pub id: String,
+    #[serde(flatten)]
+    pub content: BlockContent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlockContent {
+    Paragraph {
+        text: String,
+    },
+    Heading {
+        level: u8,
+        text: String,
+    },
+    Image {
+        url: String,
+        alt: Option<String>,
+        caption: Option<String>,
+    },
+    Quote {
+        text: String,
+        cite: Option<String>,
+    },
+    Code {
+        language: Option<String>,
+        code: String,
+    },
+    Embed {
+        url: String,
+    },
+    Callout {
+        style: CalloutStyle,
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+pub enum CalloutStyle {
+    #[display(fmt = "info")]
+    Info,
+
+    #[display(fmt = "tip")]
+    Tip,
+
+    #[display(fmt = "warning")]
+    Warning,
+
+    #[display(fmt = "danger")]
+    Danger,
+}
+
+/// A change to the blocks of a post.
+#[derive(Debug)]
+pub enum BlockEdit {
+    ReplaceAll(Vec<Block>),
+    /// Inserts at the start when `after_block_id` is `None`
+    Insert {
+        block: Block,
+        after_block_id: Option<String>,
+    },
+    Update {
+        block_id: String,
+        block: Block,
+    },
+    Delete {
+        block_id: String,
+    },
+    /// Moves to the start when `after_block_id` is `None`
+    Move {
+        block_id: String,
+        after_block_id: Option<String>,
+    },
+}
+
+#[derive(Debug, Display)]
+pub enum BlockEditError {
+    #[display(fmt = "{}", _0)]
+    DieselError(diesel::result::Error),
+
+    #[display(fmt = "Block `{}` Not Found!", _0)]
+    BlockNotFound(String),
+
+    #[display(fmt = "Invalid block: {}", _0)]
+    InvalidBlock(String),
+}
+
+impl From<diesel::result::Error> for BlockEditError {
+    fn from(err: diesel::result::Error) -> Self {
+        BlockEditError::DieselError(err)
+    }
+}
+
+fn new_block_id() -> String {
+    uuid::Uuid::new_v4().to_simple().to_string()
+}
+
+fn position_of<'a>(blocks: &'a [Block], block_id: &'a str) -> Result<usize, BlockEditError> {
+    blocks
+        .iter()
+        .position(|block| block.id == block_id)
+        .ok_or_else(|| BlockEditError::BlockNotFound(block_id.to_string()))
+}
+
+fn insert_position<'a>(
+    blocks: &'a [Block],
+    after_block_id: &'a Option<String>,
+) -> Result<usize, BlockEditError> {
+    match after_block_id {
+        None => Ok(0),
+        Some(after_block_id) => Ok(position_of(blocks, after_block_id)? + 1),
+    }
+}
+
+/// Applies `edit` to `blocks`, the result is validated before it is returned.
+pub fn apply_edit(mut blocks: Vec<Block>, edit: BlockEdit) -> Result<Vec<Block>, BlockEditError> {
+    match edit {
+        BlockEdit::ReplaceAll(new_blocks) => {
+            blocks = new_blocks;
+        }
+        BlockEdit::Insert {
+            mut block,
+            after_block_id,
+        } => {
+            let idx = insert_position(&blocks, &after_block_id)?;
+            block.id = new_block_id();
+            blocks.insert(idx, block);
+        }
+        BlockEdit::Update {
+            block_id,
+            mut block,
+        } => {
+            let idx = position_of(&blocks, &block_id)?;
+            block.id = block_id;
+            blocks[idx] = block;
+        }
+        BlockEdit::Delete { block_id } => {
+            let idx = position_of(&blocks, &block_id)?;
+            blocks.remove(idx);
+        }
+        BlockEdit::Move {
+            block_id,
+            after_block_id,
+        } => {
+            let block = blocks.remove(position_of(&blocks, &block_id)?);
+            let idx = insert_position(&blocks, &after_block_id)?;
+            blocks.insert(idx, block);
+        }
+    }
+
+    for block in blocks.iter_mut() {
+        if block.id.is_empty() {
+            block.id = new_block_id();
+        }
+    }
+    validate(&blocks)?;
+    Ok(blocks)
+}
+
+fn is_http_url<'a>(url: &'a str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+pub fn validate<'a>(blocks: &'a [Block]) -> Result<(), BlockEditError> {
+    let invalid = |msg: String| Err(BlockEditError::InvalidBlock(msg));
+
+    if blocks.len() > MAX_BLOCKS {
+        return invalid(format!("a post can have at most {} blocks", MAX_BLOCKS));
+    }
+    for (idx, block) in blocks.iter().enumerate() {
+        if blocks[..idx].iter().any(|other| other.id == block.id) {
+            return invalid(format!("duplicate block id `{}`", block.id));
+        }
+        match &block.content {
+            BlockContent::Heading { level, .. } if !(1..=6).contains(level) => {
+                return invalid(format!(
+                    "heading `{}` level must be within 1 and 6",
+                    block.id
+                ))
+            }
+            BlockContent::Paragraph { text }
+            | BlockContent::Heading { text, .. }
+            | BlockContent::Quote { text, .. }
+            | BlockContent::Callout { text, .. }
+                if text.trim().is_empty() =>
+            {
+                return invalid(format!("block `{}` has no text", block.id))
+            }
+            BlockContent::Image { url, .. } | BlockContent::Embed { url } if !is_http_url(url) => {
+                return invalid(format!("block `{}` must have a http(s) url", block.id))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Renders (validated) blocks to html, all text is escaped.
+pub fn render_html<'a>(blocks: &'a [Block]) -> String {
+    let mut html = String::new();
+    for block in blocks {
+        let rendered = match &block.content {
+            BlockContent::Paragraph { text } => format!("<p>{}</p>", escape(text)),
+            BlockContent::Heading { level, text } => {
+                format!("<h{level}>{}</h{level}>", escape(text), level = level)
+            }
+            BlockContent::Image { url, alt, caption } => format!(
+                "<figure><img src=\"{}\" alt=\"{}\">{}</figure>",
+                escape(url),
+                escape(alt.as_deref().unwrap_or("")),
+                caption
+                    .as_ref()
+                    .map(|caption| format!("<figcaption>{}</figcaption>", escape(caption)))
+                    .unwrap_or_default(),
+            ),
+            BlockContent::Quote { text, cite } => format!(
+                "<blockquote><p>{}</p>{}</blockquote>",
+                escape(text),
+                cite.as_ref()
+                    .map(|cite| format!("<cite>{}</cite>", escape(cite)))
+                    .unwrap_or_default(),
+            ),
+            BlockContent::Code { language, code } => match language {
+                Some(language) => format!(
+                    "<pre><code class=\"language-{}\">{}</code></pre>",
+                    escape(language),
+                    escape(code)
+                ),
+                None => format!("<pre><code>{}</code></pre>", escape(code)),
+            },
+            // never embed third party markup, clients can upgrade the link to a player
+            BlockContent::Embed { url } => format!(
+                "<figure class=\"embed\"><a href=\"{url}\" rel=\"noopener noreferrer\">{url}</a></figure>",
+                url = escape(url)
+            ),
+            BlockContent::Callout { style, text } => format!(
+                "<aside class=\"callout callout-{}\"><p>{}</p></aside>",
+                style,
+                escape(text)
+            ),
+        };
+        html.push_str(&format!(
+            "<section data-block-id=\"{}\">",
+            escape(&block.id)
+        ));
+        html.push_str(&rendered);
+        html.push_str("</section>");
+    }
+    html
+}
+
+/// Renders blocks to plain text, e.g. for search or feeds.
+pub fn render_text<'a>(blocks: &'a [Block]) -> String {
+    blocks
+        .iter()
+        .filter_map(|block| match &block.content {
+            BlockContent::Paragraph { text }
+            | BlockContent::Heading { text, .. }
+            | BlockContent::Callout { text, .. } => Some(text.clone()),
+            BlockContent::Quote { text, cite } => Some(match cite {
+                Some(cite) => format!("\"{}\" - {}", text, cite),
+                None => format!("\"{}\"", text),
+            }),
+            BlockContent::Code { code, .. } => Some(code.clone()),
+            BlockContent::Image { alt, caption, .. } => caption.clone().or_else(|| alt.clone()),
+            BlockContent::Embed { url } => Some(url.clone()),
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraph(id: &str, text: &str) -> Block {
+        Block {
+            id: id.to_string(),
+            content: BlockContent::Paragraph {
+                text: text.to_string(),
+            },
+        }
+    }
+
+    fn heading(level: u8) -> Block {
+        Block {
+            id: "h".to_string(),
+            content: BlockContent::Heading {
+                level: level,
+                text: "Title".to_string(),
+            },
+        }
+    }
+
+    fn ids(blocks: &[Block]) -> Vec<&str> {
+        blocks.iter().map(|block| block.id.as_str()).collect()
+    }
+
+    fn sample() -> Vec<Block> {
+        vec![
+            paragraph("a", "one"),
+            paragraph("b", "two"),
+            paragraph("c", "three"),
+        ]
+    }
+
+    #[test]
+    fn moves_after_a_block_or_to_the_start() {
+        let blocks = apply_edit(
+            sample(),
+            BlockEdit::Move {
+                block_id: "a".to_string(),
+                after_block_id: Some("c".to_string()),
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&blocks), vec!["b", "c", "a"]);
+
+        let blocks = apply_edit(
+            sample(),
+            BlockEdit::Move {
+                block_id: "c".to_string(),
+                after_block_id: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&blocks), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn move_after_a_missing_block_fails() {
+        let res = apply_edit(
+            sample(),
+            BlockEdit::Move {
+                block_id: "a".to_string(),
+                after_block_id: Some("missing".to_string()),
+            },
+        );
+        assert!(matches!(res, Err(BlockEditError::BlockNotFound(id)) if id == "missing"));
+    }
+
+    #[test]
+    fn insert_after_a_missing_block_fails() {
+        let res = apply_edit(
+            sample(),
+            BlockEdit::Insert {
+                block: paragraph("", "new"),
+                after_block_id: Some("missing".to_string()),
+            },
+        );
+        assert!(matches!(res, Err(BlockEditError::BlockNotFound(id)) if id == "missing"));
+    }
+
+    #[test]
+    fn inserted_blocks_get_a_new_id() {
+        let blocks = apply_edit(
+            sample(),
+            BlockEdit::Insert {
+                block: paragraph("a", "new"),
+                after_block_id: Some("b".to_string()),
+            },
+        )
+        .unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(&ids(&blocks)[..2], &["a", "b"]);
+        assert!(!["a", "b", "c", ""].contains(&blocks[2].id.as_str()));
+    }
+
+    #[test]
+    fn rejects_duplicate_ids() {
+        let res = apply_edit(
+            vec![],
+            BlockEdit::ReplaceAll(vec![paragraph("a", "one"), paragraph("a", "two")]),
+        );
+        assert!(matches!(res, Err(BlockEditError::InvalidBlock(_))));
+    }
+
+    #[test]
+    fn heading_levels_are_within_1_and_6() {
+        assert!(validate(&[heading(1)]).is_ok());
+        assert!(validate(&[heading(6)]).is_ok());
+        assert!(matches!(
+            validate(&[heading(0)]),
+            Err(BlockEditError::InvalidBlock(_))
+        ));
+        assert!(matches!(
+            validate(&[heading(7)]),
+            Err(BlockEditError::InvalidBlock(_))
+        ));
+    }
+
+    #[test]
+    fn urls_must_be_http() {
+        let image = |url: &str| Block {
+            id: "i".to_string(),
+            content: BlockContent::Image {
+                url: url.to_string(),
+                alt: None,
+                caption: None,
+            },
+        };
+        let embed = |url: &str| Block {
+            id: "e".to_string(),
+            content: BlockContent::Embed {
+                url: url.to_string(),
+            },
+        };
+        assert!(validate(&[image("https://example.com/a.png")]).is_ok());
+        assert!(validate(&[embed("http://example.com/video")]).is_ok());
+        for url in &[
+            "javascript:alert(1)",
+            "data:text/html,hi",
+            "ftp://example.com",
+            "",
+        ] {
+            assert!(validate(&[image(url)]).is_err(), "{}", url);
+            assert!(validate(&[embed(url)]).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn render_html_escapes_text() {
+        let blocks = vec![
+            paragraph("a\"><script>", "<script>alert(1)</script> & more"),
+            Block {
+                id: "q".to_string(),
+                content: BlockContent::Quote {
+                    text: "<b>quoted</b>".to_string(),
+                    cite: Some("\"someone\"".to_string()),
+                },
+            },
+            Block {
+                id: "e".to_string(),
+                content: BlockContent::Embed {
+                    url: "https://example.com/?a=1&b=\"2\"".to_string(),
+                },
+            },
+        ];
+        let html = render_html(&blocks);
+        assert!(!html.contains("<script>"), "{}", html);
+        assert!(!html.contains("<b>"), "{}", html);
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt; &amp; more"));
+        assert!(html.contains("data-block-id=\"a&quot;&gt;&lt;script&gt;\""));
+        assert!(html.contains("<cite>&quot;someone&quot;</cite>"));
+        assert!(html.contains("href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\""));
+    }
+}
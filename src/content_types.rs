@@ -0,0 +1,204 @@
+use crate::{body_renderer::render_body, db::models::posts::BodyFormat};
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+const MAX_FIELDS: usize = 100;
+const MAX_FIELD_NAME_LENGTH: usize = 64;
+
+/// Fields of a content type, stored as its `field_schema`, e.g.
+/// `{"fields": [{"name": "starts_at", "type": "date", "required": true}]}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSchema {
+    pub fields: Vec<FieldDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDefinition {
+    /// Key of the field in the `fields` of an entry
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub required: bool,
+    /// Allowed values of an `enum` field
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+    /// Longest allowed `string`, in characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    #[display(fmt = "string")]
+    String,
+
+    /// Html, sanitized on write
+    #[display(fmt = "rich_text")]
+    RichText,
+
+    #[display(fmt = "number")]
+    Number,
+
+    /// `YYYY-MM-DD`
+    #[display(fmt = "date")]
+    Date,
+
+    /// Id of a post or entry
+    #[display(fmt = "reference")]
+    Reference,
+
+    /// Id of an uploaded media
+    #[display(fmt = "media")]
+    Media,
+
+    /// One of the `values` of the field
+    #[display(fmt = "enum")]
+    Enum,
+}
+
+/// Fields of an entry that passed validation.
+#[derive(Debug)]
+pub struct ValidEntry {
+    /// With rich text sanitized
+    pub fields: Value,
+    /// Posts referenced by `reference` fields, they must exist
+    pub post_ids: Vec<i32>,
+    /// Media referenced by `media` fields, they must exist
+    pub media_ids: Vec<i32>,
+}
+
+fn is_valid_name<'a>(name: &'a str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_FIELD_NAME_LENGTH
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+impl FieldSchema {
+    /// Parses and checks a schema sent by an admin.
+    pub fn parse(schema: Value) -> Result<Self, String> {
+        let schema: FieldSchema =
+            serde_json::from_value(schema).map_err(|err| format!("invalid schema: {}", err))?;
+        if schema.fields.len() > MAX_FIELDS {
+            return Err(format!("at most {} fields are allowed", MAX_FIELDS));
+        }
+        let mut names = HashSet::new();
+        for field in schema.fields.iter() {
+            if !is_valid_name(&field.name) {
+                return Err(format!(
+                    "`{}` must be lowercase letters, digits and underscores, starting with a letter",
+                    field.name
+                ));
+            }
+            if !names.insert(field.name.as_str()) {
+                return Err(format!("`{}` is defined more than once", field.name));
+            }
+            if field.field_type == FieldType::Enum && field.values.is_empty() {
+                return Err(format!("enum `{}` needs `values`", field.name));
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Checks the `fields` of an entry, reporting what is wrong with each invalid field.
+    pub fn validate_entry(&self, fields: &Value) -> Result<ValidEntry, BTreeMap<String, String>> {
+        let mut invalid = BTreeMap::new();
+        let empty = Map::new();
+        let fields = match fields {
+            Value::Object(fields) => fields,
+            Value::Null => &empty,
+            _ => {
+                invalid.insert("fields".to_string(), "must be an object".to_string());
+                return Err(invalid);
+            }
+        };
+        for name in fields.keys() {
+            if !self.fields.iter().any(|field| &field.name == name) {
+                invalid.insert(
+                    name.clone(),
+                    "is not a field of this content type".to_string(),
+                );
+            }
+        }
+
+        let mut valid = ValidEntry {
+            fields: Value::Object(Map::new()),
+            post_ids: vec![],
+            media_ids: vec![],
+        };
+        let mut valid_fields = Map::new();
+        for field in self.fields.iter() {
+            match fields.get(&field.name) {
+                None | Some(Value::Null) => {
+                    if field.required {
+                        invalid.insert(field.name.clone(), "is required".to_string());
+                    }
+                }
+                Some(value) => match field.check(value) {
+                    Ok(value) => {
+                        match field.field_type {
+                            FieldType::Reference => {
+                                valid.post_ids.extend(value.as_i64().map(|id| id as i32))
+                            }
+                            FieldType::Media => {
+                                valid.media_ids.extend(value.as_i64().map(|id| id as i32))
+                            }
+                            _ => {}
+                        }
+                        valid_fields.insert(field.name.clone(), value);
+                    }
+                    Err(err) => {
+                        invalid.insert(field.name.clone(), err);
+                    }
+                },
+            }
+        }
+        if !invalid.is_empty() {
+            return Err(invalid);
+        }
+        valid.fields = Value::Object(valid_fields);
+        Ok(valid)
+    }
+}
+
+impl FieldDefinition {
+    fn check<'a>(&self, value: &'a Value) -> Result<Value, String> {
+        match (self.field_type, value) {
+            (FieldType::String, Value::String(text)) => match self.max_length {
+                Some(max_length) if text.chars().count() > max_length => {
+                    Err(format!("must be at most {} characters", max_length))
+                }
+                _ => Ok(value.clone()),
+            },
+            (FieldType::RichText, Value::String(html)) => {
+                Ok(Value::String(render_body(html, &BodyFormat::Html)))
+            }
+            (FieldType::Number, Value::Number(_)) => Ok(value.clone()),
+            (FieldType::Date, Value::String(date)) => {
+                chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map(|_| value.clone())
+                    .map_err(|_| "must be a date like `2022-07-13`".to_string())
+            }
+            (FieldType::Reference, Value::Number(id)) | (FieldType::Media, Value::Number(id))
+                if id
+                    .as_i64()
+                    .is_some_and(|id| id > 0 && id <= i32::MAX as i64) =>
+            {
+                Ok(value.clone())
+            }
+            (FieldType::Reference, _) => Err("must be the id of a post".to_string()),
+            (FieldType::Media, _) => Err("must be the id of a media".to_string()),
+            (FieldType::Enum, Value::String(choice)) if self.values.contains(choice) => {
+                Ok(value.clone())
+            }
+            (FieldType::Enum, _) => Err(format!("must be one of {}", self.values.join(", "))),
+            (field_type, _) => Err(format!("must be a {}", field_type)),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::db::schema::post_autosaves;
+use serde::{Deserialize, Serialize};
+
+/// Unsaved edits of a user to a post. Kept as json in redis, the table is only used when
+/// redis is not available.
+#[derive(
+    Debug,
+    Identifiable,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    AsChangeset,
+    Associations,
+    Clone,
+)]
+#[table_name = "post_autosaves"]
+#[primary_key(post_id, user_id)]
+#[changeset_options(treat_none_as_null = "true")]
+#[belongs_to(parent = Post)]
+pub struct PostAutosave {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub revision: i64,
+    pub post_version: i32,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+    pub body_format: Option<String>,
+    pub saved_at: chrono::NaiveDateTime,
+}
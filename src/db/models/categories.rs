@@ -0,0 +1,32 @@
+use crate::db::schema::{categories, post_categories};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Clone)]
+#[table_name = "categories"]
+pub struct Category {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub parent_id: Option<i32>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "categories"]
+pub struct NewCategory<'a> {
+    pub name: &'a str,
+    pub slug: &'a str,
+    pub parent_id: Option<i32>,
+}
+
+#[derive(Debug, Identifiable, Queryable, Insertable, Associations)]
+#[table_name = "post_categories"]
+#[primary_key(post_id, category_id)]
+#[belongs_to(parent = Post)]
+#[belongs_to(parent = Category)]
+pub struct PostCategory {
+    pub post_id: i32,
+    pub category_id: i32,
+}
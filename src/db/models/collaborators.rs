@@ -0,0 +1,63 @@
+use crate::db::schema::post_collaborators;
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+#[derive(Display, Debug, Clone, Copy, PartialEq)]
+pub enum CollaboratorRole {
+    /// Edits the post and is listed among its authors
+    #[display(fmt = "co-author")]
+    CoAuthor,
+
+    /// Edits the post without being credited
+    #[display(fmt = "editor")]
+    Editor,
+
+    /// Reads the post while it is a draft
+    #[display(fmt = "viewer")]
+    Viewer,
+}
+
+impl CollaboratorRole {
+    pub fn can_edit(&self) -> bool {
+        match self {
+            CollaboratorRole::CoAuthor | CollaboratorRole::Editor => true,
+            CollaboratorRole::Viewer => false,
+        }
+    }
+}
+
+impl std::str::FromStr for CollaboratorRole {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "co-author" => Ok(CollaboratorRole::CoAuthor),
+            "editor" => Ok(CollaboratorRole::Editor),
+            "viewer" => Ok(CollaboratorRole::Viewer),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations, Clone)]
+#[table_name = "post_collaborators"]
+#[primary_key(post_id, user_id)]
+#[belongs_to(parent = Post)]
+pub struct PostCollaborator {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub role: String,
+    pub invited_by: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "post_collaborators"]
+pub struct NewPostCollaborator<'a> {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub role: &'a str,
+    pub invited_by: i32,
+}
@@ -0,0 +1,62 @@
+use crate::db::schema::{collection_posts, collections};
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+#[derive(Display, Debug, Clone, Copy, PartialEq)]
+pub enum CollectionKind {
+    /// Parts meant to be read in order, a post is part of at most one series
+    #[display(fmt = "series")]
+    Series,
+
+    /// Curated list of posts
+    #[display(fmt = "collection")]
+    Collection,
+}
+
+impl std::str::FromStr for CollectionKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "series" => Ok(CollectionKind::Series),
+            "collection" => Ok(CollectionKind::Collection),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Clone)]
+#[table_name = "collections"]
+pub struct Collection {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub kind: String,
+    pub description: String,
+    pub pinned: bool,
+    pub user_id: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "collections"]
+pub struct NewCollection<'a> {
+    pub name: &'a str,
+    pub slug: &'a str,
+    pub kind: &'a str,
+    pub description: &'a str,
+    pub user_id: i32,
+}
+
+#[derive(Debug, Identifiable, Queryable, Insertable, Associations)]
+#[table_name = "collection_posts"]
+#[primary_key(collection_id, post_id)]
+#[belongs_to(parent = Post)]
+#[belongs_to(parent = Collection)]
+pub struct CollectionPost {
+    pub collection_id: i32,
+    pub post_id: i32,
+    /// Order within the collection, starting at 0
+    pub position: i32,
+}
@@ -0,0 +1,44 @@
+use crate::db::schema::comments;
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+#[derive(Display, Debug, Clone, Copy, PartialEq)]
+pub enum CommentStatus {
+    /// Waiting in the moderation queue
+    #[display(fmt = "pending")]
+    Pending,
+
+    #[display(fmt = "approved")]
+    Approved,
+
+    #[display(fmt = "hidden")]
+    Hidden,
+}
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations, Clone)]
+#[table_name = "comments"]
+#[belongs_to(parent = Post)]
+#[belongs_to(parent = User)]
+pub struct Comment {
+    pub id: i32,
+    pub post_id: i32,
+    pub user_id: i32,
+    pub parent_id: Option<i32>,
+    pub root_id: Option<i32>,
+    pub body: String,
+    pub status: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "comments"]
+pub struct NewComment<'a> {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub parent_id: Option<i32>,
+    pub root_id: Option<i32>,
+    pub body: &'a str,
+    pub status: &'a str,
+}
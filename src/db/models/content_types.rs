@@ -0,0 +1,22 @@
+use crate::db::schema::content_types;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Clone)]
+#[table_name = "content_types"]
+pub struct ContentType {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    /// A `content_types::FieldSchema`
+    pub field_schema: serde_json::Value,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "content_types"]
+pub struct NewContentType<'a> {
+    pub name: &'a str,
+    pub slug: &'a str,
+    pub field_schema: &'a serde_json::Value,
+}
@@ -0,0 +1,100 @@
+use crate::db::schema::{media, media_variants, post_media};
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+#[derive(Display, Debug, Clone, Copy, PartialEq)]
+pub enum MediaProcessingStatus {
+    /// Waiting for the image processors
+    #[display(fmt = "pending")]
+    Pending,
+
+    #[display(fmt = "processed")]
+    Processed,
+
+    #[display(fmt = "failed")]
+    Failed,
+
+    /// Not an image (or a format that is not processed), stored as uploaded
+    #[display(fmt = "skipped")]
+    Skipped,
+}
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations, Clone)]
+#[table_name = "media"]
+#[belongs_to(parent = User)]
+pub struct Media {
+    pub id: i32,
+    pub user_id: i32,
+    pub storage_key: String,
+    pub url: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub checksum: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub processing_status: String,
+    pub blurhash: Option<String>,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "media"]
+pub struct NewMedia<'a> {
+    pub user_id: i32,
+    pub storage_key: &'a str,
+    pub url: &'a str,
+    pub file_name: &'a str,
+    pub mime_type: &'a str,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub checksum: &'a str,
+    pub processing_status: String,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "post_media"]
+pub struct NewPostMedia {
+    pub post_id: i32,
+    pub media_id: i32,
+}
+
+#[derive(Debug, Identifiable, Queryable, Associations)]
+#[table_name = "post_media"]
+#[primary_key(post_id, media_id)]
+#[belongs_to(parent = Post)]
+#[belongs_to(parent = Media)]
+pub struct PostMedia {
+    pub post_id: i32,
+    pub media_id: i32,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations, Clone)]
+#[table_name = "media_variants"]
+#[belongs_to(parent = Media)]
+pub struct MediaVariant {
+    pub id: i32,
+    pub media_id: i32,
+    pub storage_key: String,
+    pub url: String,
+    pub mime_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub size_bytes: i64,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "media_variants"]
+pub struct NewMediaVariant {
+    pub media_id: i32,
+    pub storage_key: String,
+    pub url: String,
+    pub mime_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub size_bytes: i64,
+}
@@ -0,0 +1,99 @@
+use crate::db::schema::{menu_items, menus};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Clone)]
+#[table_name = "menus"]
+pub struct Menu {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "menus"]
+pub struct NewMenu<'a> {
+    pub name: &'a str,
+    pub slug: &'a str,
+}
+
+/// Exactly one of `page_id`, `post_id`, `category_id` and `url` is set, see `MenuTarget`.
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations, Clone)]
+#[table_name = "menu_items"]
+#[belongs_to(parent = Menu)]
+pub struct MenuItem {
+    pub id: i32,
+    pub menu_id: i32,
+    pub parent_id: Option<i32>,
+    /// Order among the items with the same parent, starting at 0
+    pub position: i32,
+    pub label: String,
+    pub page_id: Option<i32>,
+    pub post_id: Option<i32>,
+    pub category_id: Option<i32>,
+    pub url: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "menu_items"]
+pub struct NewMenuItem<'a> {
+    pub menu_id: i32,
+    pub parent_id: Option<i32>,
+    pub position: i32,
+    pub label: &'a str,
+    pub page_id: Option<i32>,
+    pub post_id: Option<i32>,
+    pub category_id: Option<i32>,
+    pub url: Option<&'a str>,
+}
+
+/// What a menu item links to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuTarget {
+    Page(i32),
+    Post(i32),
+    Category(i32),
+    Url(String),
+}
+
+impl MenuTarget {
+    /// `None` unless exactly one target is given.
+    pub fn from_columns(
+        page_id: Option<i32>,
+        post_id: Option<i32>,
+        category_id: Option<i32>,
+        url: Option<String>,
+    ) -> Option<Self> {
+        match (page_id, post_id, category_id, url) {
+            (Some(page_id), None, None, None) => Some(MenuTarget::Page(page_id)),
+            (None, Some(post_id), None, None) => Some(MenuTarget::Post(post_id)),
+            (None, None, Some(category_id), None) => Some(MenuTarget::Category(category_id)),
+            (None, None, None, Some(url)) => Some(MenuTarget::Url(url)),
+            _ => None,
+        }
+    }
+
+    /// Values of the `page_id`, `post_id`, `category_id` and `url` columns.
+    pub fn columns(&self) -> (Option<i32>, Option<i32>, Option<i32>, Option<&str>) {
+        match self {
+            MenuTarget::Page(page_id) => (Some(*page_id), None, None, None),
+            MenuTarget::Post(post_id) => (None, Some(*post_id), None, None),
+            MenuTarget::Category(category_id) => (None, None, Some(*category_id), None),
+            MenuTarget::Url(url) => (None, None, None, Some(url)),
+        }
+    }
+}
+
+impl MenuItem {
+    pub fn target(&self) -> Option<MenuTarget> {
+        MenuTarget::from_columns(
+            self.page_id,
+            self.post_id,
+            self.category_id,
+            self.url.clone(),
+        )
+    }
+}
@@ -0,0 +1,46 @@
+use crate::db::schema::pages;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Clone)]
+#[table_name = "pages"]
+pub struct Page {
+    pub id: i32,
+    pub title: String,
+    pub body: String,
+    pub body_format: String,
+    pub rendered_body: String,
+    pub slug: String,
+    pub parent_id: Option<i32>,
+    /// Order among the pages with the same parent, starting at 0
+    pub position: i32,
+    pub published: bool,
+    pub created_by: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "pages"]
+pub struct NewPage<'a> {
+    pub title: &'a str,
+    pub body: &'a str,
+    pub body_format: &'a str,
+    pub rendered_body: &'a str,
+    pub slug: &'a str,
+    pub parent_id: Option<i32>,
+    pub position: i32,
+    pub published: bool,
+    pub created_by: i32,
+}
+
+/// Columns changed by an update of a page, `None` fields are left as they are.
+#[derive(AsChangeset, Debug, Default)]
+#[table_name = "pages"]
+pub struct PageChangeset {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub body_format: Option<String>,
+    pub rendered_body: Option<String>,
+    pub slug: Option<String>,
+    pub published: Option<bool>,
+}
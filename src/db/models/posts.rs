@@ -1,4 +1,4 @@
-use crate::{db::schema::posts, db::models::users::User};
+use crate::{db::schema::post_slug_redirects, db::schema::posts};
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +12,69 @@ pub enum PublishStatus {
 
     #[display(fmt = "Request to admin for publish")]
     RequestToAdminForPublish,
+
+    #[display(fmt = "Scheduled")]
+    Scheduled,
+
+    #[display(fmt = "Archived")]
+    Archived,
+}
+
+/// Who can read a published post.
+#[derive(Display, Debug, Clone, Copy, PartialEq)]
+pub enum Visibility {
+    #[display(fmt = "public")]
+    Public,
+
+    /// Left out of listings, readable by anyone with the link
+    #[display(fmt = "unlisted")]
+    Unlisted,
+
+    #[display(fmt = "members-only")]
+    MembersOnly,
+
+    /// Readable with an access token, handed out for the post password
+    #[display(fmt = "password-protected")]
+    PasswordProtected,
+}
+
+impl std::str::FromStr for Visibility {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(Visibility::Public),
+            "unlisted" => Ok(Visibility::Unlisted),
+            "members-only" => Ok(Visibility::MembersOnly),
+            "password-protected" => Ok(Visibility::PasswordProtected),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Display, Debug, Clone, Copy, PartialEq)]
+pub enum BodyFormat {
+    #[display(fmt = "plain")]
+    Plain,
+
+    #[display(fmt = "markdown")]
+    Markdown,
+
+    #[display(fmt = "html")]
+    Html,
+}
+
+impl std::str::FromStr for BodyFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(BodyFormat::Plain),
+            "markdown" => Ok(BodyFormat::Markdown),
+            "html" => Ok(BodyFormat::Html),
+            _ => Err(()),
+        }
+    }
 }
 
 #[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations)]
@@ -25,6 +88,25 @@ pub struct Post {
     pub user_id: i32,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    pub unpublish_at: Option<chrono::NaiveDateTime>,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+    pub slug: String,
+    pub body_format: String,
+    pub rendered_body: String,
+    pub content_blocks: serde_json::Value,
+    pub content_html: String,
+    pub content_text: String,
+    pub comments_enabled: bool,
+    pub reaction_counts: serde_json::Value,
+    pub version: i32,
+    pub visibility: String,
+    #[serde(skip_serializing)]
+    pub access_password_hash: Option<String>,
+    /// Set for entries of a content type, `None` for blog posts
+    pub content_type_id: Option<i32>,
+    /// Values of the content type fields, validated against its schema
+    pub fields: serde_json::Value,
 }
 
 
@@ -35,4 +117,40 @@ pub struct NewPost<'a> {
     pub post_body: &'a str,
     pub user_id: i32,
     pub published_status: &'a str,
+    pub slug: &'a str,
+    pub body_format: &'a str,
+    pub rendered_body: &'a str,
+}
+
+/// A post that is an entry of a content type.
+#[derive(Insertable, Debug)]
+#[table_name = "posts"]
+pub struct NewEntry<'a> {
+    pub post_subject: &'a str,
+    pub post_body: &'a str,
+    pub user_id: i32,
+    pub published_status: &'a str,
+    pub slug: &'a str,
+    pub content_type_id: i32,
+    pub fields: &'a serde_json::Value,
+}
+
+/// Columns changed by a partial update of a post, `None` fields are left as they are.
+#[derive(AsChangeset, Debug, Default)]
+#[table_name = "posts"]
+pub struct PostChangeset {
+    pub post_subject: Option<String>,
+    pub post_body: Option<String>,
+    pub body_format: Option<String>,
+    pub rendered_body: Option<String>,
+    pub slug: Option<String>,
+    pub comments_enabled: Option<bool>,
+    pub fields: Option<serde_json::Value>,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "post_slug_redirects"]
+pub struct NewPostSlugRedirect<'a> {
+    pub slug: &'a str,
+    pub post_id: i32,
 }
@@ -0,0 +1,24 @@
+use crate::db::schema::post_previews;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations, Clone)]
+#[table_name = "post_previews"]
+#[belongs_to(parent = Post)]
+pub struct PostPreview {
+    pub id: i32,
+    pub post_id: i32,
+    pub post_version: i32,
+    pub created_by: i32,
+    pub expires_at: chrono::NaiveDateTime,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "post_previews"]
+pub struct NewPostPreview {
+    pub post_id: i32,
+    pub post_version: i32,
+    pub created_by: i32,
+    pub expires_at: chrono::NaiveDateTime,
+}
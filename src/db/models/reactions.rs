@@ -0,0 +1,23 @@
+use crate::db::schema::post_reactions;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations)]
+#[table_name = "post_reactions"]
+#[primary_key(post_id, user_id)]
+#[belongs_to(parent = Post)]
+#[belongs_to(parent = User)]
+pub struct PostReaction {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub emoji: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "post_reactions"]
+pub struct NewPostReaction<'a> {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub emoji: &'a str,
+}
@@ -0,0 +1,22 @@
+use crate::db::schema::post_stats_daily;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations)]
+#[table_name = "post_stats_daily"]
+#[primary_key(post_id, day)]
+#[belongs_to(parent = Post)]
+pub struct PostStatsDaily {
+    pub post_id: i32,
+    pub day: chrono::NaiveDate,
+    pub views: i32,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "post_stats_daily"]
+pub struct NewPostStatsDaily {
+    pub post_id: i32,
+    pub day: chrono::NaiveDate,
+    pub views: i32,
+}
@@ -0,0 +1,29 @@
+use crate::db::schema::{post_tags, tags};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Clone)]
+#[table_name = "tags"]
+pub struct Tag {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "tags"]
+pub struct NewTag<'a> {
+    pub name: &'a str,
+    pub slug: &'a str,
+}
+
+#[derive(Debug, Identifiable, Queryable, Insertable, Associations)]
+#[table_name = "post_tags"]
+#[primary_key(post_id, tag_id)]
+#[belongs_to(parent = Post)]
+#[belongs_to(parent = Tag)]
+pub struct PostTag {
+    pub post_id: i32,
+    pub tag_id: i32,
+}
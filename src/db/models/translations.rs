@@ -0,0 +1,45 @@
+use crate::db::schema::post_translations;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize, Queryable, Associations, Clone)]
+#[table_name = "post_translations"]
+#[belongs_to(parent = Post)]
+pub struct PostTranslation {
+    pub id: i32,
+    pub post_id: i32,
+    pub locale: String,
+    pub post_subject: String,
+    pub post_body: String,
+    pub body_format: String,
+    pub rendered_body: String,
+    pub slug: String,
+    pub published_status: String,
+    pub created_by: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "post_translations"]
+pub struct NewPostTranslation<'a> {
+    pub post_id: i32,
+    pub locale: &'a str,
+    pub post_subject: &'a str,
+    pub post_body: &'a str,
+    pub body_format: &'a str,
+    pub rendered_body: &'a str,
+    pub slug: &'a str,
+    pub published_status: &'a str,
+    pub created_by: i32,
+}
+
+/// Columns changed by an update of a translation, `None` fields are left as they are.
+#[derive(AsChangeset, Debug, Default)]
+#[table_name = "post_translations"]
+pub struct PostTranslationChangeset {
+    pub post_subject: Option<String>,
+    pub post_body: Option<String>,
+    pub body_format: Option<String>,
+    pub rendered_body: Option<String>,
+    pub slug: Option<String>,
+}
@@ -1,3 +1,237 @@
+table! {
+    categories (id) {
+        id -> Int4,
+        name -> Varchar,
+        slug -> Varchar,
+        parent_id -> Nullable<Int4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    collection_posts (collection_id, post_id) {
+        collection_id -> Int4,
+        post_id -> Int4,
+        position -> Int4,
+    }
+}
+
+table! {
+    collections (id) {
+        id -> Int4,
+        name -> Varchar,
+        slug -> Varchar,
+        kind -> Varchar,
+        description -> Text,
+        pinned -> Bool,
+        user_id -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    comments (id) {
+        id -> Int4,
+        post_id -> Int4,
+        user_id -> Int4,
+        parent_id -> Nullable<Int4>,
+        root_id -> Nullable<Int4>,
+        body -> Text,
+        status -> Varchar,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    content_types (id) {
+        id -> Int4,
+        name -> Varchar,
+        slug -> Varchar,
+        field_schema -> Jsonb,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    media (id) {
+        id -> Int4,
+        user_id -> Int4,
+        storage_key -> Varchar,
+        url -> Text,
+        file_name -> Varchar,
+        mime_type -> Varchar,
+        size_bytes -> Int8,
+        width -> Nullable<Int4>,
+        height -> Nullable<Int4>,
+        checksum -> Varchar,
+        created_at -> Timestamp,
+        processing_status -> Varchar,
+        blurhash -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    media_variants (id) {
+        id -> Int4,
+        media_id -> Int4,
+        storage_key -> Varchar,
+        url -> Text,
+        mime_type -> Varchar,
+        width -> Int4,
+        height -> Int4,
+        size_bytes -> Int8,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    menu_items (id) {
+        id -> Int4,
+        menu_id -> Int4,
+        parent_id -> Nullable<Int4>,
+        position -> Int4,
+        label -> Varchar,
+        page_id -> Nullable<Int4>,
+        post_id -> Nullable<Int4>,
+        category_id -> Nullable<Int4>,
+        url -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    menus (id) {
+        id -> Int4,
+        name -> Varchar,
+        slug -> Varchar,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    pages (id) {
+        id -> Int4,
+        title -> Varchar,
+        body -> Text,
+        body_format -> Varchar,
+        rendered_body -> Text,
+        slug -> Varchar,
+        parent_id -> Nullable<Int4>,
+        position -> Int4,
+        published -> Bool,
+        created_by -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    post_autosaves (post_id, user_id) {
+        post_id -> Int4,
+        user_id -> Int4,
+        revision -> Int8,
+        post_version -> Int4,
+        subject -> Nullable<Text>,
+        body -> Nullable<Text>,
+        body_format -> Nullable<Varchar>,
+        saved_at -> Timestamp,
+    }
+}
+
+table! {
+    post_categories (post_id, category_id) {
+        post_id -> Int4,
+        category_id -> Int4,
+    }
+}
+
+table! {
+    post_collaborators (post_id, user_id) {
+        post_id -> Int4,
+        user_id -> Int4,
+        role -> Varchar,
+        invited_by -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    post_media (post_id, media_id) {
+        post_id -> Int4,
+        media_id -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    post_previews (id) {
+        id -> Int4,
+        post_id -> Int4,
+        post_version -> Int4,
+        created_by -> Int4,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    post_reactions (post_id, user_id) {
+        post_id -> Int4,
+        user_id -> Int4,
+        emoji -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    post_slug_redirects (slug) {
+        slug -> Varchar,
+        post_id -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    post_stats_daily (post_id, day) {
+        post_id -> Int4,
+        day -> Date,
+        views -> Int4,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    post_tags (post_id, tag_id) {
+        post_id -> Int4,
+        tag_id -> Int4,
+    }
+}
+
+table! {
+    post_translations (id) {
+        id -> Int4,
+        post_id -> Int4,
+        locale -> Varchar,
+        post_subject -> Varchar,
+        post_body -> Text,
+        body_format -> Varchar,
+        rendered_body -> Text,
+        slug -> Varchar,
+        published_status -> Varchar,
+        created_by -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     posts (id) {
         id -> Int4,
@@ -7,6 +241,31 @@ table! {
         user_id -> Int4,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        publish_at -> Nullable<Timestamp>,
+        unpublish_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
+        slug -> Varchar,
+        body_format -> Varchar,
+        rendered_body -> Text,
+        content_blocks -> Jsonb,
+        content_html -> Text,
+        content_text -> Text,
+        comments_enabled -> Bool,
+        reaction_counts -> Jsonb,
+        version -> Int4,
+        visibility -> Varchar,
+        access_password_hash -> Nullable<Varchar>,
+        content_type_id -> Nullable<Int4>,
+        fields -> Jsonb,
+    }
+}
+
+table! {
+    tags (id) {
+        id -> Int4,
+        name -> Varchar,
+        slug -> Varchar,
+        created_at -> Timestamp,
     }
 }
 
@@ -21,9 +280,60 @@ table! {
     }
 }
 
+joinable!(collection_posts -> collections (collection_id));
+joinable!(collection_posts -> posts (post_id));
+joinable!(collections -> users (user_id));
+joinable!(comments -> posts (post_id));
+joinable!(comments -> users (user_id));
+joinable!(media -> users (user_id));
+joinable!(media_variants -> media (media_id));
+joinable!(menu_items -> categories (category_id));
+joinable!(menu_items -> menus (menu_id));
+joinable!(menu_items -> pages (page_id));
+joinable!(menu_items -> posts (post_id));
+joinable!(pages -> users (created_by));
+joinable!(post_autosaves -> posts (post_id));
+joinable!(post_autosaves -> users (user_id));
+joinable!(post_categories -> categories (category_id));
+joinable!(post_categories -> posts (post_id));
+joinable!(post_collaborators -> posts (post_id));
+joinable!(post_media -> media (media_id));
+joinable!(post_media -> posts (post_id));
+joinable!(post_previews -> posts (post_id));
+joinable!(post_previews -> users (created_by));
+joinable!(post_reactions -> posts (post_id));
+joinable!(post_reactions -> users (user_id));
+joinable!(post_slug_redirects -> posts (post_id));
+joinable!(post_stats_daily -> posts (post_id));
+joinable!(post_tags -> posts (post_id));
+joinable!(post_tags -> tags (tag_id));
+joinable!(post_translations -> posts (post_id));
+joinable!(post_translations -> users (created_by));
+joinable!(posts -> content_types (content_type_id));
 joinable!(posts -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    categories,
+    collection_posts,
+    collections,
+    comments,
+    content_types,
+    media,
+    media_variants,
+    menu_items,
+    menus,
+    pages,
+    post_autosaves,
+    post_categories,
+    post_collaborators,
+    post_media,
+    post_previews,
+    post_reactions,
+    post_slug_redirects,
+    post_stats_daily,
+    post_tags,
+    post_translations,
     posts,
+    tags,
     users,
 );
@@ -0,0 +1,76 @@
+use crate::{db::actor::DbActor, db::models::categories::Category};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+
+/// Ids of `root_id` and every category below it in the tree.
+pub fn descendant_ids(categories: &[Category], root_id: i32) -> Vec<i32> {
+    let mut ids = vec![root_id];
+    let mut idx = 0;
+    while idx < ids.len() {
+        let parent = ids[idx];
+        ids.extend(
+            categories
+                .iter()
+                .filter(|category| {
+                    category.parent_id == Some(parent) && !ids.contains(&category.id)
+                })
+                .map(|category| category.id)
+                .collect::<Vec<i32>>(),
+        );
+        idx += 1;
+    }
+    ids
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Category>, diesel::result::Error>")]
+pub struct GetCategories;
+
+impl Handler<GetCategories> for DbActor {
+    type Result = Result<Vec<Category>, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetCategories, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::categories::dsl::*;
+        categories.order(name.asc()).load::<Category>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Category, diesel::result::Error>")]
+pub struct GetCategoryById {
+    pub category_id: i32,
+}
+
+impl Handler<GetCategoryById> for DbActor {
+    type Result = Result<Category, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetCategoryById, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::categories::dsl::*;
+        categories
+            .filter(id.eq(msg.category_id))
+            .get_result::<Category>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Category>, diesel::result::Error>")]
+pub struct GetPostCategories {
+    pub post_id: i32,
+}
+
+impl Handler<GetPostCategories> for DbActor {
+    type Result = Result<Vec<Category>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostCategories, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{categories, post_categories};
+        categories::table
+            .inner_join(post_categories::table)
+            .filter(post_categories::post_id.eq(msg.post_id))
+            .order(categories::name.asc())
+            .select(categories::all_columns)
+            .load::<Category>(&conn)
+    }
+}
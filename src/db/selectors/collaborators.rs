@@ -0,0 +1,88 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::collaborators::{CollaboratorRole, PostCollaborator},
+    db::schema::{post_collaborators, posts, users},
+};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+/// The collaborators of a post along with their usernames, oldest first.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<(PostCollaborator, String)>, diesel::result::Error>")]
+pub struct GetPostCollaborators {
+    pub post_id: i32,
+}
+
+impl Handler<GetPostCollaborators> for DbActor {
+    type Result = Result<Vec<(PostCollaborator, String)>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostCollaborators, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        post_collaborators::table
+            .inner_join(users::table.on(post_collaborators::user_id.eq(users::id)))
+            .filter(post_collaborators::post_id.eq(msg.post_id))
+            .order(post_collaborators::created_at.asc())
+            .select((post_collaborators::all_columns, users::username))
+            .load::<(PostCollaborator, String)>(&conn)
+    }
+}
+
+/// The role of a user on a post, `None` when they dont collaborate on it.
+#[derive(Message)]
+#[rtype(result = "Result<Option<String>, diesel::result::Error>")]
+pub struct GetCollaboratorRole {
+    pub post_id: i32,
+    pub user_id: i32,
+}
+
+impl Handler<GetCollaboratorRole> for DbActor {
+    type Result = Result<Option<String>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetCollaboratorRole, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_collaborators::dsl::*;
+        post_collaborators
+            .filter(post_id.eq(msg.post_id).and(user_id.eq(msg.user_id)))
+            .select(role)
+            .get_result::<String>(&conn)
+            .optional()
+    }
+}
+
+/// Usernames of the authors of the given posts by post id, the owner first and then
+/// the co-authors in the order they joined.
+#[derive(Message)]
+#[rtype(result = "Result<HashMap<i32, Vec<String>>, diesel::result::Error>")]
+pub struct GetPostAuthors {
+    pub post_ids: Vec<i32>,
+}
+
+impl Handler<GetPostAuthors> for DbActor {
+    type Result = Result<HashMap<i32, Vec<String>>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostAuthors, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        let owners = posts::table
+            .inner_join(users::table)
+            .filter(posts::id.eq_any(&msg.post_ids))
+            .select((posts::id, users::username))
+            .load::<(i32, String)>(&conn)?;
+        let co_authors = post_collaborators::table
+            .inner_join(users::table.on(post_collaborators::user_id.eq(users::id)))
+            .filter(
+                post_collaborators::post_id
+                    .eq_any(&msg.post_ids)
+                    .and(post_collaborators::role.eq(format!("{}", CollaboratorRole::CoAuthor))),
+            )
+            .order(post_collaborators::created_at.asc())
+            .select((post_collaborators::post_id, users::username))
+            .load::<(i32, String)>(&conn)?;
+
+        let mut authors = HashMap::<i32, Vec<String>>::new();
+        for (post_id, username) in owners.into_iter().chain(co_authors) {
+            authors.entry(post_id).or_default().push(username);
+        }
+        Ok(authors)
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::collections::{Collection, CollectionKind},
+    db::models::posts::PublishStatus,
+};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Collection>, diesel::result::Error>")]
+pub struct GetCollections;
+
+impl Handler<GetCollections> for DbActor {
+    type Result = Result<Vec<Collection>, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetCollections, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::collections::dsl::*;
+        collections.order(name.asc()).load::<Collection>(&conn)
+    }
+}
+
+/// A collection along with the ids of its posts, in order.
+#[derive(Message)]
+#[rtype(result = "Result<(Collection, Vec<i32>), diesel::result::Error>")]
+pub enum GetCollection {
+    ById(i32),
+    BySlug(String),
+}
+
+impl Handler<GetCollection> for DbActor {
+    type Result = Result<(Collection, Vec<i32>), diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetCollection, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{collection_posts, collections};
+        let collection = match msg {
+            GetCollection::ById(collection_id) => collections::table
+                .filter(collections::id.eq(collection_id))
+                .get_result::<Collection>(&conn)?,
+            GetCollection::BySlug(collection_slug) => collections::table
+                .filter(collections::slug.eq(collection_slug))
+                .get_result::<Collection>(&conn)?,
+        };
+        let post_ids = collection_posts::table
+            .filter(collection_posts::collection_id.eq(collection.id))
+            .order(collection_posts::position.asc())
+            .select(collection_posts::post_id)
+            .load::<i32>(&conn)?;
+        Ok((collection, post_ids))
+    }
+}
+
+/// The series a post is part of, if any.
+#[derive(Message)]
+#[rtype(result = "Result<Option<Collection>, diesel::result::Error>")]
+pub struct GetPostSeries {
+    pub post_id: i32,
+}
+
+impl Handler<GetPostSeries> for DbActor {
+    type Result = Result<Option<Collection>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostSeries, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{collection_posts, collections};
+        collections::table
+            .inner_join(collection_posts::table)
+            .filter(collection_posts::post_id.eq(msg.post_id))
+            .filter(collections::kind.eq(format!("{}", CollectionKind::Series)))
+            .select(collections::all_columns)
+            .first::<Collection>(&conn)
+            .optional()
+    }
+}
+
+#[derive(Queryable, Debug, Clone)]
+pub struct SeriesPost {
+    pub id: i32,
+    pub subject: String,
+    pub slug: String,
+}
+
+/// Where a post stands in its series, among the published parts.
+#[derive(Debug)]
+pub struct SeriesNavigation {
+    pub series: Collection,
+    /// Starting at 1
+    pub position: usize,
+    pub total: usize,
+    pub previous: Option<SeriesPost>,
+    pub next: Option<SeriesPost>,
+}
+
+/// Navigation within their series for each of `post_ids` that is part of one. Drafts and
+/// trashed parts are skipped, except for the post itself.
+#[derive(Message)]
+#[rtype(result = "Result<HashMap<i32, SeriesNavigation>, diesel::result::Error>")]
+pub struct GetSeriesNavigation {
+    pub post_ids: Vec<i32>,
+}
+
+impl Handler<GetSeriesNavigation> for DbActor {
+    type Result = Result<HashMap<i32, SeriesNavigation>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetSeriesNavigation, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{collection_posts, collections, posts};
+
+        let all_series = collections::table
+            .inner_join(collection_posts::table)
+            .filter(collection_posts::post_id.eq_any(&msg.post_ids))
+            .filter(collections::kind.eq(format!("{}", CollectionKind::Series)))
+            .select(collections::all_columns)
+            .distinct()
+            .load::<Collection>(&conn)?;
+        let parts = collection_posts::table
+            .inner_join(posts::table)
+            .filter(
+                collection_posts::collection_id.eq_any(
+                    all_series
+                        .iter()
+                        .map(|series| series.id)
+                        .collect::<Vec<i32>>(),
+                ),
+            )
+            .filter(posts::deleted_at.is_null())
+            .order((
+                collection_posts::collection_id.asc(),
+                collection_posts::position.asc(),
+            ))
+            .select((
+                collection_posts::collection_id,
+                posts::published_status,
+                (posts::id, posts::post_subject, posts::slug),
+            ))
+            .load::<(i32, String, SeriesPost)>(&conn)?;
+
+        let published = format!("{}", PublishStatus::Published);
+        let mut navigation = HashMap::new();
+        for series in all_series {
+            for post_id in msg.post_ids.iter() {
+                let series_parts = parts
+                    .iter()
+                    .filter(|(series_id, status, part)| {
+                        *series_id == series.id && (*status == published || part.id == *post_id)
+                    })
+                    .map(|(_, _, part)| part)
+                    .collect::<Vec<&SeriesPost>>();
+                let idx = match series_parts.iter().position(|part| part.id == *post_id) {
+                    Some(idx) => idx,
+                    None => continue,
+                };
+                navigation.insert(
+                    *post_id,
+                    SeriesNavigation {
+                        series: series.clone(),
+                        position: idx + 1,
+                        total: series_parts.len(),
+                        previous: idx
+                            .checked_sub(1)
+                            .map(|previous| series_parts[previous].clone()),
+                        next: series_parts.get(idx + 1).map(|next| (*next).clone()),
+                    },
+                );
+            }
+        }
+        Ok(navigation)
+    }
+}
+
+/// Ids of the posts of pinned collections, in order, collections sorted by name.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<i32>, diesel::result::Error>")]
+pub struct GetPinnedPostIds;
+
+impl Handler<GetPinnedPostIds> for DbActor {
+    type Result = Result<Vec<i32>, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetPinnedPostIds, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{collection_posts, collections};
+        let mut post_ids = collection_posts::table
+            .inner_join(collections::table)
+            .filter(collections::pinned.eq(true))
+            .order((
+                collections::name.asc(),
+                collections::id.asc(),
+                collection_posts::position.asc(),
+            ))
+            .select(collection_posts::post_id)
+            .load::<i32>(&conn)?;
+        // a post in several pinned collections shows up once
+        let mut seen = std::collections::HashSet::new();
+        post_ids.retain(|post_id| seen.insert(*post_id));
+        Ok(post_ids)
+    }
+}
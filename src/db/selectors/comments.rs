@@ -0,0 +1,165 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::collaborators::CollaboratorRole,
+    db::models::comments::{Comment, CommentStatus},
+    db::schema::{comments, post_collaborators, posts, users},
+};
+use actix::{Handler, Message};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+pub struct CommentPage {
+    pub comments: Vec<Comment>,
+    /// Usernames of the authors of `comments`, by user id
+    pub author_names: HashMap<i32, String>,
+    pub total: i64,
+}
+
+fn author_names(
+    conn: &PgConnection,
+    comments: &[Comment],
+) -> Result<HashMap<i32, String>, diesel::result::Error> {
+    let user_ids = comments
+        .iter()
+        .map(|comment| comment.user_id)
+        .collect::<Vec<i32>>();
+    Ok(users::table
+        .filter(users::id.eq_any(user_ids))
+        .select((users::id, users::username))
+        .load::<(i32, String)>(conn)?
+        .into_iter()
+        .collect())
+}
+
+/// Approved comments of a post, plus the pending/hidden ones of the viewer.
+fn visible_comments<'a>(post_id: i32, viewer_id: Option<i32>) -> comments::BoxedQuery<'a, Pg> {
+    let approved = format!("{}", CommentStatus::Approved);
+    let query = comments::table
+        .filter(comments::post_id.eq(post_id))
+        .into_boxed();
+    match viewer_id {
+        Some(viewer_id) => query.filter(
+            comments::status
+                .eq(approved)
+                .or(comments::user_id.eq(viewer_id)),
+        ),
+        None => query.filter(comments::status.eq(approved)),
+    }
+}
+
+/// A page of top level comments along with all of their replies, `total` is
+/// the number of top level comments.
+#[derive(Message)]
+#[rtype(result = "Result<CommentPage, diesel::result::Error>")]
+pub struct GetPostComments {
+    pub post_id: i32,
+    pub viewer_id: Option<i32>,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+impl Handler<GetPostComments> for DbActor {
+    type Result = Result<CommentPage, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostComments, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        let total = visible_comments(msg.post_id, msg.viewer_id)
+            .filter(comments::parent_id.is_null())
+            .count()
+            .get_result::<i64>(&conn)?;
+        let roots = visible_comments(msg.post_id, msg.viewer_id)
+            .filter(comments::parent_id.is_null())
+            .order(comments::created_at.asc())
+            .offset((msg.page - 1) * msg.per_page)
+            .limit(msg.per_page)
+            .load::<Comment>(&conn)?;
+        let root_ids = roots.iter().map(|root| root.id).collect::<Vec<i32>>();
+        let replies = visible_comments(msg.post_id, msg.viewer_id)
+            .filter(comments::root_id.eq_any(root_ids))
+            .order(comments::created_at.asc())
+            .load::<Comment>(&conn)?;
+
+        let mut all_comments = roots;
+        all_comments.extend(replies);
+        Ok(CommentPage {
+            author_names: author_names(&conn, &all_comments)?,
+            comments: all_comments,
+            total: total,
+        })
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Comment, diesel::result::Error>")]
+pub struct GetCommentById {
+    pub comment_id: i32,
+}
+
+impl Handler<GetCommentById> for DbActor {
+    type Result = Result<Comment, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetCommentById, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::comments::dsl::*;
+        comments
+            .filter(id.eq(msg.comment_id))
+            .get_result::<Comment>(&conn)
+    }
+}
+
+/// Pending comments, oldest first. Only those on the posts `post_owner_id` owns or
+/// edits as a collaborator when set, otherwise all of them (for admins).
+#[derive(Message)]
+#[rtype(result = "Result<CommentPage, diesel::result::Error>")]
+pub struct GetModerationQueue {
+    pub post_owner_id: Option<i32>,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+impl Handler<GetModerationQueue> for DbActor {
+    type Result = Result<CommentPage, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetModerationQueue, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        let pending = format!("{}", CommentStatus::Pending);
+        let queue = |post_owner_id: Option<i32>| {
+            let query = comments::table
+                .filter(comments::status.eq(pending.clone()))
+                .into_boxed();
+            match post_owner_id {
+                Some(post_owner_id) => query.filter(
+                    comments::post_id
+                        .eq_any(
+                            posts::table
+                                .filter(posts::user_id.eq(post_owner_id))
+                                .select(posts::id),
+                        )
+                        .or(comments::post_id.eq_any(
+                            post_collaborators::table
+                                .filter(
+                                    post_collaborators::user_id.eq(post_owner_id).and(
+                                        post_collaborators::role
+                                            .ne(format!("{}", CollaboratorRole::Viewer)),
+                                    ),
+                                )
+                                .select(post_collaborators::post_id),
+                        )),
+                ),
+                None => query,
+            }
+        };
+        let total = queue(msg.post_owner_id).count().get_result::<i64>(&conn)?;
+        let pending_comments = queue(msg.post_owner_id)
+            .order(comments::created_at.asc())
+            .offset((msg.page - 1) * msg.per_page)
+            .limit(msg.per_page)
+            .load::<Comment>(&conn)?;
+        Ok(CommentPage {
+            author_names: author_names(&conn, &pending_comments)?,
+            comments: pending_comments,
+            total: total,
+        })
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{db::actor::DbActor, db::models::content_types::ContentType};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<ContentType>, diesel::result::Error>")]
+pub struct GetContentTypes;
+
+impl Handler<GetContentTypes> for DbActor {
+    type Result = Result<Vec<ContentType>, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetContentTypes, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::content_types::dsl::*;
+        content_types.order(name.asc()).load::<ContentType>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<ContentType, diesel::result::Error>")]
+pub enum GetContentType {
+    ById(i32),
+    BySlug(String),
+}
+
+impl Handler<GetContentType> for DbActor {
+    type Result = Result<ContentType, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetContentType, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::content_types::dsl::*;
+        match msg {
+            GetContentType::ById(content_type_id) => content_types
+                .filter(id.eq(content_type_id))
+                .get_result::<ContentType>(&conn),
+            GetContentType::BySlug(content_type_slug) => content_types
+                .filter(slug.eq(content_type_slug))
+                .get_result::<ContentType>(&conn),
+        }
+    }
+}
+
+/// Which of the posts and media referenced by an entry do not exist, trashed posts count
+/// as missing.
+#[derive(Message)]
+#[rtype(result = "Result<(Vec<i32>, Vec<i32>), diesel::result::Error>")]
+pub struct GetMissingReferences {
+    pub post_ids: Vec<i32>,
+    pub media_ids: Vec<i32>,
+}
+
+impl Handler<GetMissingReferences> for DbActor {
+    type Result = Result<(Vec<i32>, Vec<i32>), diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetMissingReferences, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{media, posts};
+        let found_post_ids = posts::table
+            .filter(posts::id.eq_any(&msg.post_ids))
+            .filter(posts::deleted_at.is_null())
+            .select(posts::id)
+            .load::<i32>(&conn)?;
+        let found_media_ids = media::table
+            .filter(media::id.eq_any(&msg.media_ids))
+            .select(media::id)
+            .load::<i32>(&conn)?;
+        Ok((
+            msg.post_ids
+                .into_iter()
+                .filter(|post_id| !found_post_ids.contains(post_id))
+                .collect(),
+            msg.media_ids
+                .into_iter()
+                .filter(|media_id| !found_media_ids.contains(media_id))
+                .collect(),
+        ))
+    }
+}
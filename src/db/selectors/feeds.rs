@@ -0,0 +1,88 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::posts::{PublishStatus, Visibility},
+};
+use actix::{Handler, Message};
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::Timestamp;
+
+/// Posts published right away have no `publish_at`, they went out when they were created.
+const PUBLISHED_AT: &str = "COALESCE(posts.publish_at, posts.created_at)";
+
+#[derive(Queryable)]
+pub struct FeedPost {
+    pub subject: String,
+    pub slug: String,
+    pub owner_name: String,
+    pub rendered_body: String,
+    pub content_html: String,
+    pub published_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+pub enum FeedFilter {
+    All,
+    /// Posts owned by the user
+    Author(i32),
+    Tag(i32),
+    /// Posts in any of the categories
+    Categories(Vec<i32>),
+}
+
+/// The `limit` most recently published public blog posts, newest first.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<FeedPost>, diesel::result::Error>")]
+pub struct GetFeedPosts {
+    pub filter: FeedFilter,
+    pub limit: i64,
+}
+
+impl Handler<GetFeedPosts> for DbActor {
+    type Result = Result<Vec<FeedPost>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetFeedPosts, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{post_categories, post_tags, posts, users};
+
+        let mut query = posts::table
+            .inner_join(users::table)
+            .filter(posts::published_status.eq(format!("{}", PublishStatus::Published)))
+            .filter(posts::visibility.eq(format!("{}", Visibility::Public)))
+            .filter(posts::deleted_at.is_null())
+            .filter(posts::content_type_id.is_null())
+            .select((
+                posts::post_subject,
+                posts::slug,
+                users::username,
+                posts::rendered_body,
+                posts::content_html,
+                sql::<Timestamp>(PUBLISHED_AT),
+                posts::updated_at,
+            ))
+            .order((sql::<Timestamp>(PUBLISHED_AT).desc(), posts::id.desc()))
+            .limit(msg.limit)
+            .into_boxed();
+
+        query = match msg.filter {
+            FeedFilter::All => query,
+            FeedFilter::Author(author_id) => query.filter(posts::user_id.eq(author_id)),
+            FeedFilter::Tag(tag_id) => query.filter(
+                posts::id.eq_any(
+                    post_tags::table
+                        .filter(post_tags::tag_id.eq(tag_id))
+                        .select(post_tags::post_id),
+                ),
+            ),
+            FeedFilter::Categories(category_ids) => query.filter(
+                posts::id.eq_any(
+                    post_categories::table
+                        .filter(post_categories::category_id.eq_any(category_ids))
+                        .select(post_categories::post_id),
+                ),
+            ),
+        };
+
+        query.load::<FeedPost>(&conn)
+    }
+}
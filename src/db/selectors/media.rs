@@ -0,0 +1,81 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::media::{Media, MediaVariant},
+};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+
+#[derive(Message)]
+#[rtype(result = "Result<Media, diesel::result::Error>")]
+pub struct GetMediaById {
+    pub media_id: i32,
+}
+
+impl Handler<GetMediaById> for DbActor {
+    type Result = Result<Media, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetMediaById, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::media::dsl::*;
+        media.filter(id.eq(msg.media_id)).get_result::<Media>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Media>, diesel::result::Error>")]
+pub struct GetUserMedia {
+    pub user_id: i32,
+}
+
+impl Handler<GetUserMedia> for DbActor {
+    type Result = Result<Vec<Media>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetUserMedia, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::media::dsl::*;
+        media
+            .filter(user_id.eq(msg.user_id))
+            .order(created_at.desc())
+            .load::<Media>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Media>, diesel::result::Error>")]
+pub struct GetPostMedia {
+    pub post_id: i32,
+}
+
+impl Handler<GetPostMedia> for DbActor {
+    type Result = Result<Vec<Media>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostMedia, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{media, post_media};
+        media::table
+            .inner_join(post_media::table)
+            .filter(post_media::post_id.eq(msg.post_id))
+            .order(post_media::created_at.asc())
+            .select(media::all_columns)
+            .load::<Media>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<MediaVariant>, diesel::result::Error>")]
+pub struct GetMediaVariants {
+    pub media_id: i32,
+}
+
+impl Handler<GetMediaVariants> for DbActor {
+    type Result = Result<Vec<MediaVariant>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetMediaVariants, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::media_variants::dsl::*;
+        media_variants
+            .filter(media_id.eq(msg.media_id))
+            .order((mime_type.asc(), width.asc()))
+            .load::<MediaVariant>(&conn)
+    }
+}
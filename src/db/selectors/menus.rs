@@ -0,0 +1,142 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::menus::{Menu, MenuItem},
+    db::models::pages::Page,
+    db::models::posts::PublishStatus,
+    db::selectors::pages::published_pages,
+};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+/// Ids of `root_id` and every item below it in the tree.
+pub fn descendant_item_ids(items: &[MenuItem], root_id: i32) -> Vec<i32> {
+    let mut ids = vec![root_id];
+    let mut idx = 0;
+    while idx < ids.len() {
+        let parent = ids[idx];
+        ids.extend(
+            items
+                .iter()
+                .filter(|item| item.parent_id == Some(parent) && !ids.contains(&item.id))
+                .map(|item| item.id)
+                .collect::<Vec<i32>>(),
+        );
+        idx += 1;
+    }
+    ids
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Menu>, diesel::result::Error>")]
+pub struct GetMenus;
+
+impl Handler<GetMenus> for DbActor {
+    type Result = Result<Vec<Menu>, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetMenus, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::menus::dsl::*;
+        menus.order(name.asc()).load::<Menu>(&conn)
+    }
+}
+
+/// A menu along with all its items in order among their siblings.
+#[derive(Message)]
+#[rtype(result = "Result<(Menu, Vec<MenuItem>), diesel::result::Error>")]
+pub enum GetMenu {
+    ById(i32),
+    BySlug(String),
+}
+
+impl Handler<GetMenu> for DbActor {
+    type Result = Result<(Menu, Vec<MenuItem>), diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetMenu, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{menu_items, menus};
+        let menu = match msg {
+            GetMenu::ById(menu_id) => menus::table
+                .filter(menus::id.eq(menu_id))
+                .get_result::<Menu>(&conn)?,
+            GetMenu::BySlug(menu_slug) => menus::table
+                .filter(menus::slug.eq(menu_slug))
+                .get_result::<Menu>(&conn)?,
+        };
+        let items = menu_items::table
+            .filter(menu_items::menu_id.eq(menu.id))
+            .order((menu_items::position.asc(), menu_items::id.asc()))
+            .load::<MenuItem>(&conn)?;
+        Ok((menu, items))
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<MenuItem, diesel::result::Error>")]
+pub struct GetMenuItem {
+    pub item_id: i32,
+}
+
+impl Handler<GetMenuItem> for DbActor {
+    type Result = Result<MenuItem, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetMenuItem, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::menu_items::dsl::*;
+        menu_items
+            .filter(id.eq(msg.item_id))
+            .get_result::<MenuItem>(&conn)
+    }
+}
+
+/// Slugs of the pages, posts and categories menu items point to, by id.
+#[derive(Debug, Default)]
+pub struct MenuLinks {
+    pub pages: HashMap<i32, String>,
+    pub posts: HashMap<i32, String>,
+    pub categories: HashMap<i32, String>,
+}
+
+/// Resolves the targets of menu items. Draft pages, pages below them and posts that are not
+/// published are left out, so that the public does not follow links to them.
+#[derive(Message)]
+#[rtype(result = "Result<MenuLinks, diesel::result::Error>")]
+pub struct GetMenuLinks {
+    pub page_ids: Vec<i32>,
+    pub post_ids: Vec<i32>,
+    pub category_ids: Vec<i32>,
+}
+
+impl Handler<GetMenuLinks> for DbActor {
+    type Result = Result<MenuLinks, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetMenuLinks, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{categories, pages, posts};
+        let mut links = MenuLinks::default();
+
+        if !msg.page_ids.is_empty() {
+            let all_pages = pages::table.load::<Page>(&conn)?;
+            links.pages = published_pages(all_pages)
+                .into_iter()
+                .filter(|page| msg.page_ids.contains(&page.id))
+                .map(|page| (page.id, page.slug))
+                .collect();
+        }
+        links.posts = posts::table
+            .filter(posts::id.eq_any(&msg.post_ids))
+            .filter(posts::published_status.eq(format!("{}", PublishStatus::Published)))
+            .filter(posts::deleted_at.is_null())
+            .select((posts::id, posts::slug))
+            .load::<(i32, String)>(&conn)?
+            .into_iter()
+            .collect();
+        links.categories = categories::table
+            .filter(categories::id.eq_any(&msg.category_ids))
+            .select((categories::id, categories::slug))
+            .load::<(i32, String)>(&conn)?
+            .into_iter()
+            .collect();
+        Ok(links)
+    }
+}
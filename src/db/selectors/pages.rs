@@ -0,0 +1,58 @@
+use crate::{db::actor::DbActor, db::models::pages::Page};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+
+/// Ids of `root_id` and every page below it in the tree.
+pub fn descendant_page_ids(pages: &[Page], root_id: i32) -> Vec<i32> {
+    let mut ids = vec![root_id];
+    let mut idx = 0;
+    while idx < ids.len() {
+        let parent = ids[idx];
+        ids.extend(
+            pages
+                .iter()
+                .filter(|page| page.parent_id == Some(parent) && !ids.contains(&page.id))
+                .map(|page| page.id)
+                .collect::<Vec<i32>>(),
+        );
+        idx += 1;
+    }
+    ids
+}
+
+/// Leaves out drafts and the pages below them.
+pub fn published_pages(pages: Vec<Page>) -> Vec<Page> {
+    let hidden = pages
+        .iter()
+        .filter(|page| !page.published)
+        .flat_map(|page| descendant_page_ids(&pages, page.id))
+        .collect::<Vec<i32>>();
+    pages
+        .into_iter()
+        .filter(|page| !hidden.contains(&page.id))
+        .collect()
+}
+
+/// All pages in order among their siblings, the tree is described by `parent_id`.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Page>, diesel::result::Error>")]
+pub struct GetPages {
+    /// Drafts are left out, along with the pages below them
+    pub published_only: bool,
+}
+
+impl Handler<GetPages> for DbActor {
+    type Result = Result<Vec<Page>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPages, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::pages::dsl::*;
+        let all_pages = pages
+            .order((position.asc(), id.asc()))
+            .load::<Page>(&conn)?;
+        if !msg.published_only {
+            return Ok(all_pages);
+        }
+        Ok(published_pages(all_pages))
+    }
+}
@@ -1,6 +1,11 @@
 use crate::{
     db::actor::DbActor,
-    db::models::posts::{Post, PublishStatus},
+    db::models::{
+        categories::Category,
+        posts::{Post, PublishStatus, Visibility},
+    },
+    db::schema::{posts, users},
+    db::selectors::categories::descendant_ids,
 };
 use actix::{Handler, Message};
 use diesel::prelude::*;
@@ -13,13 +18,91 @@ pub struct PostData {
     pub user_id: i32,
     pub owner_name: String,
     pub status: String,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    pub unpublish_at: Option<chrono::NaiveDateTime>,
+    pub slug: String,
+    pub body_format: String,
+    pub rendered_body: String,
+    pub content_blocks: serde_json::Value,
+    pub content_html: String,
+    pub content_text: String,
+    pub comments_enabled: bool,
+    pub reaction_counts: serde_json::Value,
+    pub version: i32,
+    pub visibility: String,
+    pub content_type_id: Option<i32>,
+    pub fields: serde_json::Value,
+}
+
+type PostDataColumns = (
+    posts::id,
+    posts::post_subject,
+    posts::post_body,
+    users::id,
+    users::username,
+    posts::published_status,
+    posts::publish_at,
+    posts::unpublish_at,
+    posts::slug,
+    posts::body_format,
+    posts::rendered_body,
+    posts::content_blocks,
+    posts::content_html,
+    posts::content_text,
+    posts::comments_enabled,
+    posts::reaction_counts,
+    posts::version,
+    posts::visibility,
+    posts::content_type_id,
+    posts::fields,
+);
+
+/// Columns to select (from `posts` joined with `users`) to load a `PostData`.
+const POST_DATA_COLUMNS: PostDataColumns = (
+    posts::id,
+    posts::post_subject,
+    posts::post_body,
+    users::id,
+    users::username,
+    posts::published_status,
+    posts::publish_at,
+    posts::unpublish_at,
+    posts::slug,
+    posts::body_format,
+    posts::rendered_body,
+    posts::content_blocks,
+    posts::content_html,
+    posts::content_text,
+    posts::comments_enabled,
+    posts::reaction_counts,
+    posts::version,
+    posts::visibility,
+    posts::content_type_id,
+    posts::fields,
+);
+
+#[derive(Default)]
+pub struct PostFilter {
+    /// Slug of a tag the posts must have
+    pub tag: Option<String>,
+    /// Slug of a category the posts must be in, sub categories included
+    pub category: Option<String>,
+    /// Entries of this content type instead of blog posts
+    pub content_type_id: Option<i32>,
+    /// Members-only posts are listed too, for signed in readers
+    pub include_members_only: bool,
 }
 
 #[derive(Message)]
 #[rtype(result = "Result<Vec<PostData>, diesel::result::Error>")]
+#[allow(clippy::enum_variant_names)]
 pub enum GetPosts {
-    GetPublishedPosts,
-    GetUnpublishedPosts(i32),
+    /// Only public ones (and members-only ones if the filter says so), unlisted and
+    /// password-protected posts are left out
+    GetPublishedPosts(PostFilter),
+    /// Posts in the given status the user owns or collaborates on
+    GetOwnedPosts(i32, PublishStatus),
+    GetTrashedPosts(i32),
 }
 
 impl Handler<GetPosts> for DbActor {
@@ -33,36 +116,84 @@ impl Handler<GetPosts> for DbActor {
         use crate::db::schema::{posts, posts::dsl::*, users, users::dsl::*};
 
         match msg {
-            GetPublishedPosts => {
+            GetPublishedPosts(filter) => {
+                use crate::db::schema::{categories, post_categories, post_tags, tags};
+
                 let status = format!("{}", PublishStatus::Published);
-                posts
-                    .filter(published_status.eq(&status))
+                let mut visibilities = vec![format!("{}", Visibility::Public)];
+                if filter.include_members_only {
+                    visibilities.push(format!("{}", Visibility::MembersOnly));
+                }
+                let mut query = posts
+                    .filter(published_status.eq(status).and(deleted_at.is_null()))
+                    .filter(visibility.eq_any(visibilities))
                     .inner_join(users.on(posts::user_id.eq(users::id)))
-                    .select((
-                        posts::id,
-                        posts::post_subject,
-                        posts::post_body,
-                        users::id,
-                        users::username,
-                        posts::published_status,
-                    ))
-                    .get_results::<PostData>(&conn)
+                    .select(POST_DATA_COLUMNS)
+                    .into_boxed();
+
+                query = match filter.content_type_id {
+                    Some(entry_type_id) => query.filter(content_type_id.eq(entry_type_id)),
+                    None => query.filter(content_type_id.is_null()),
+                };
+
+                if let Some(tag_slug) = filter.tag {
+                    query = query.filter(
+                        posts::id.eq_any(
+                            post_tags::table
+                                .inner_join(tags::table)
+                                .filter(tags::slug.eq(tag_slug))
+                                .select(post_tags::post_id),
+                        ),
+                    );
+                }
+
+                if let Some(category_slug) = filter.category {
+                    let all_categories = categories::table.load::<Category>(&conn)?;
+                    let category_ids = match all_categories
+                        .iter()
+                        .find(|category| category.slug == category_slug)
+                    {
+                        Some(category) => descendant_ids(&all_categories, category.id),
+                        None => return Ok(vec![]),
+                    };
+                    query = query.filter(
+                        posts::id.eq_any(
+                            post_categories::table
+                                .filter(post_categories::category_id.eq_any(category_ids))
+                                .select(post_categories::post_id),
+                        ),
+                    );
+                }
+
+                query.get_results::<PostData>(&conn)
             }
-            GetUnpublishedPosts(owner_id) => {
-                let status = format!("{}", PublishStatus::Unpublished);
+            GetOwnedPosts(owner_id, owned_status) => {
+                use crate::db::schema::post_collaborators;
+
+                let status = format!("{}", owned_status);
                 posts
-                    .filter(published_status.eq(&status).and(user_id.eq(owner_id)))
+                    .filter(
+                        published_status
+                            .eq(&status)
+                            .and(
+                                user_id.eq(owner_id).or(posts::id.eq_any(
+                                    post_collaborators::table
+                                        .filter(post_collaborators::user_id.eq(owner_id))
+                                        .select(post_collaborators::post_id),
+                                )),
+                            )
+                            .and(deleted_at.is_null()),
+                    )
                     .inner_join(users.on(posts::user_id.eq(users::id)))
-                    .select((
-                        posts::id,
-                        posts::post_subject,
-                        posts::post_body,
-                        users::id,
-                        users::username,
-                        posts::published_status,
-                    ))
+                    .select(POST_DATA_COLUMNS)
                     .get_results::<PostData>(&conn)
             }
+            GetTrashedPosts(owner_id) => posts
+                .filter(user_id.eq(owner_id).and(deleted_at.is_not_null()))
+                .order(deleted_at.desc())
+                .inner_join(users.on(posts::user_id.eq(users::id)))
+                .select(POST_DATA_COLUMNS)
+                .get_results::<PostData>(&conn),
         }
     }
 }
@@ -79,6 +210,93 @@ impl Handler<GetPostById> for DbActor {
     fn handle(&mut self, msg: GetPostById, _: &mut Self::Context) -> Self::Result {
         let conn = self.get_conn();
         use crate::db::schema::posts::dsl::*;
-        posts.filter(id.eq(msg.post_id)).get_result::<Post>(&conn)
+        posts
+            .filter(id.eq(msg.post_id).and(deleted_at.is_null()))
+            .get_result::<Post>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<PostData, diesel::result::Error>")]
+pub struct GetPostDataById {
+    pub post_id: i32,
+}
+
+impl Handler<GetPostDataById> for DbActor {
+    type Result = Result<PostData, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostDataById, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        posts::table
+            .filter(posts::id.eq(msg.post_id).and(posts::deleted_at.is_null()))
+            .inner_join(users::table)
+            .select(POST_DATA_COLUMNS)
+            .get_result::<PostData>(&conn)
+    }
+}
+
+/// Posts that are not trashed among `post_ids`, in no particular order.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<PostData>, diesel::result::Error>")]
+pub struct GetPostsDataByIds {
+    pub post_ids: Vec<i32>,
+}
+
+impl Handler<GetPostsDataByIds> for DbActor {
+    type Result = Result<Vec<PostData>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostsDataByIds, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        posts::table
+            .filter(posts::id.eq_any(&msg.post_ids).and(posts::deleted_at.is_null()))
+            .inner_join(users::table)
+            .select(POST_DATA_COLUMNS)
+            .get_results::<PostData>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<PostData, diesel::result::Error>")]
+pub struct GetPublishedPostBySlug {
+    pub slug: String,
+}
+
+impl Handler<GetPublishedPostBySlug> for DbActor {
+    type Result = Result<PostData, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPublishedPostBySlug, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        let status = format!("{}", PublishStatus::Published);
+        posts::table
+            .filter(
+                posts::slug
+                    .eq(msg.slug)
+                    .and(posts::published_status.eq(&status))
+                    .and(posts::deleted_at.is_null()),
+            )
+            .inner_join(users::table)
+            .select(POST_DATA_COLUMNS)
+            .get_result::<PostData>(&conn)
+    }
+}
+
+/// Resolves a slug that a post used to have to the slug it has now.
+#[derive(Message)]
+#[rtype(result = "Result<String, diesel::result::Error>")]
+pub struct GetSlugRedirect {
+    pub slug: String,
+}
+
+impl Handler<GetSlugRedirect> for DbActor {
+    type Result = Result<String, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetSlugRedirect, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_slug_redirects;
+        post_slug_redirects::table
+            .inner_join(posts::table)
+            .filter(post_slug_redirects::slug.eq(msg.slug))
+            .select(posts::slug)
+            .get_result::<String>(&conn)
     }
 }
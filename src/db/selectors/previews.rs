@@ -0,0 +1,44 @@
+use crate::{db::actor::DbActor, db::models::previews::PostPreview};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+#[derive(Message)]
+#[rtype(result = "Result<PostPreview, diesel::result::Error>")]
+pub struct GetPostPreview {
+    pub preview_id: i32,
+}
+
+impl Handler<GetPostPreview> for DbActor {
+    type Result = Result<PostPreview, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostPreview, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_previews::dsl::*;
+        post_previews
+            .filter(id.eq(msg.preview_id))
+            .get_result::<PostPreview>(&conn)
+    }
+}
+
+/// Preview links of a post that still work, newest first.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<PostPreview>, diesel::result::Error>")]
+pub struct GetActivePostPreviews {
+    pub post_id: i32,
+}
+
+impl Handler<GetActivePostPreviews> for DbActor {
+    type Result = Result<Vec<PostPreview>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetActivePostPreviews, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_previews::dsl::*;
+        post_previews
+            .filter(post_id.eq(msg.post_id))
+            .filter(revoked_at.is_null())
+            .filter(expires_at.gt(now))
+            .order(created_at.desc())
+            .load::<PostPreview>(&conn)
+    }
+}
@@ -0,0 +1,27 @@
+use crate::db::actor::DbActor;
+use actix::{Handler, Message};
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+/// The reactions of a user to the given posts, by post id.
+#[derive(Message)]
+#[rtype(result = "Result<HashMap<i32, String>, diesel::result::Error>")]
+pub struct GetUserReactions {
+    pub user_id: i32,
+    pub post_ids: Vec<i32>,
+}
+
+impl Handler<GetUserReactions> for DbActor {
+    type Result = Result<HashMap<i32, String>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetUserReactions, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_reactions::dsl::*;
+        Ok(post_reactions
+            .filter(user_id.eq(msg.user_id).and(post_id.eq_any(msg.post_ids)))
+            .select((post_id, emoji))
+            .load::<(i32, String)>(&conn)?
+            .into_iter()
+            .collect())
+    }
+}
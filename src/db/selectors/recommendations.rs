@@ -0,0 +1,111 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::posts::{PublishStatus, Visibility},
+};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Double, Integer, Text};
+
+/// Weight of the share of the post's tags a candidate has.
+const TAG_WEIGHT: f64 = 0.5;
+/// Weight of the trigram similarity of the subjects, or of the start of the texts.
+const TEXT_WEIGHT: f64 = 0.35;
+/// Weight of how recently a candidate was published.
+const RECENCY_WEIGHT: f64 = 0.15;
+/// A candidate published this many days ago gets half of the recency score.
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+/// Candidates without a shared tag need at least this text similarity.
+const MIN_TEXT_SIMILARITY: f64 = 0.1;
+
+const RELATED_POSTS_QUERY: &str = r"
+WITH source AS (
+    SELECT id, post_subject, left(content_text, 2000) AS excerpt
+    FROM posts
+    WHERE id = $1
+), source_tags AS (
+    SELECT tag_id FROM post_tags WHERE post_id = $1
+), candidates AS (
+    SELECT posts.id,
+        (SELECT count(*) FROM post_tags
+            WHERE post_tags.post_id = posts.id
+            AND post_tags.tag_id IN (SELECT tag_id FROM source_tags))::float8
+            / GREATEST((SELECT count(*) FROM source_tags), 1) AS tag_score,
+        GREATEST(
+            similarity(posts.post_subject, source.post_subject),
+            similarity(left(posts.content_text, 2000), source.excerpt)
+        )::float8 AS text_score,
+        (1.0 / (1.0 + EXTRACT(EPOCH FROM NOW() - COALESCE(posts.publish_at, posts.created_at))
+            / 86400.0 / $2))::float8 AS recency_score
+    FROM posts, source
+    WHERE posts.id <> source.id
+        AND posts.published_status = $3
+        AND posts.visibility = $4
+        AND posts.deleted_at IS NULL
+        AND posts.content_type_id IS NULL
+)
+SELECT id
+FROM candidates
+WHERE tag_score > 0 OR text_score >= $5
+ORDER BY $6 * tag_score + $7 * text_score + $8 * recency_score DESC, id DESC
+LIMIT $9
+";
+
+#[derive(QueryableByName)]
+struct RelatedPostId {
+    #[sql_type = "Integer"]
+    id: i32,
+}
+
+/// Changes whenever a post is added, updated, published, unpublished or purged, cached related
+/// posts computed under another stamp are stale.
+#[derive(Message)]
+#[rtype(result = "Result<String, diesel::result::Error>")]
+pub struct GetRelatedPostsStamp;
+
+impl Handler<GetRelatedPostsStamp> for DbActor {
+    type Result = Result<String, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetRelatedPostsStamp, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+        let last_updated_at = posts
+            .select(diesel::dsl::max(updated_at))
+            .get_result::<Option<chrono::NaiveDateTime>>(&conn)?;
+        // purging a post leaves the last update as it was
+        let num_posts = posts.count().get_result::<i64>(&conn)?;
+        Ok(format!(
+            "{}-{}",
+            last_updated_at.map_or(0, |last_updated_at| last_updated_at.timestamp_nanos()),
+            num_posts
+        ))
+    }
+}
+
+/// Ids of up to `limit` published public posts related to a post, best first. Candidates are
+/// scored by the tags they share with it, the similarity of their text and their recency.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<i32>, diesel::result::Error>")]
+pub struct GetRelatedPostIds {
+    pub post_id: i32,
+    pub limit: i64,
+}
+
+impl Handler<GetRelatedPostIds> for DbActor {
+    type Result = Result<Vec<i32>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetRelatedPostIds, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        let related = diesel::sql_query(RELATED_POSTS_QUERY)
+            .bind::<Integer, _>(msg.post_id)
+            .bind::<Double, _>(RECENCY_HALF_LIFE_DAYS)
+            .bind::<Text, _>(format!("{}", PublishStatus::Published))
+            .bind::<Text, _>(format!("{}", Visibility::Public))
+            .bind::<Double, _>(MIN_TEXT_SIMILARITY)
+            .bind::<Double, _>(TAG_WEIGHT)
+            .bind::<Double, _>(TEXT_WEIGHT)
+            .bind::<Double, _>(RECENCY_WEIGHT)
+            .bind::<BigInt, _>(msg.limit)
+            .load::<RelatedPostId>(&conn)?;
+        Ok(related.into_iter().map(|related| related.id).collect())
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{db::actor::DbActor, db::models::stats::PostStatsDaily};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Date, Integer, Nullable, Text};
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<PostStatsDaily>, diesel::result::Error>")]
+pub struct GetPostStats {
+    pub post_id: i32,
+    pub since: chrono::NaiveDate,
+}
+
+impl Handler<GetPostStats> for DbActor {
+    type Result = Result<Vec<PostStatsDaily>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostStats, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_stats_daily::dsl::*;
+        post_stats_daily
+            .filter(post_id.eq(msg.post_id).and(day.ge(msg.since)))
+            .order(day.asc())
+            .load::<PostStatsDaily>(&conn)
+    }
+}
+
+const TOP_POSTS_QUERY: &str = r"
+SELECT posts.id, posts.post_subject AS subject, posts.slug, sum(post_stats_daily.views) AS views
+FROM post_stats_daily
+INNER JOIN posts ON posts.id = post_stats_daily.post_id
+WHERE post_stats_daily.day >= $1
+    AND posts.deleted_at IS NULL
+GROUP BY posts.id, posts.post_subject, posts.slug
+ORDER BY views DESC
+LIMIT $2
+";
+
+#[derive(QueryableByName)]
+pub struct TopPost {
+    #[sql_type = "Integer"]
+    pub id: i32,
+    #[sql_type = "Text"]
+    pub subject: String,
+    #[sql_type = "Text"]
+    pub slug: String,
+    #[sql_type = "Nullable<BigInt>"]
+    pub views: Option<i64>,
+}
+
+/// Posts with the most views since `since`, trashed posts excluded.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<TopPost>, diesel::result::Error>")]
+pub struct GetTopPosts {
+    pub since: chrono::NaiveDate,
+    pub limit: i64,
+}
+
+impl Handler<GetTopPosts> for DbActor {
+    type Result = Result<Vec<TopPost>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetTopPosts, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        diesel::sql_query(TOP_POSTS_QUERY)
+            .bind::<Date, _>(msg.since)
+            .bind::<BigInt, _>(msg.limit)
+            .load::<TopPost>(&conn)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::{
+        posts::{PublishStatus, Visibility},
+        tags::Tag,
+    },
+};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Text};
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Tag>, diesel::result::Error>")]
+pub struct GetTags;
+
+impl Handler<GetTags> for DbActor {
+    type Result = Result<Vec<Tag>, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetTags, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::tags::dsl::*;
+        tags.order(name.asc()).load::<Tag>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Tag>, diesel::result::Error>")]
+pub struct GetPostTags {
+    pub post_id: i32,
+}
+
+impl Handler<GetPostTags> for DbActor {
+    type Result = Result<Vec<Tag>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostTags, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{post_tags, tags};
+        tags::table
+            .inner_join(post_tags::table)
+            .filter(post_tags::post_id.eq(msg.post_id))
+            .order(tags::name.asc())
+            .select(tags::all_columns)
+            .load::<Tag>(&conn)
+    }
+}
+
+const TAG_CLOUD_QUERY: &str = r"
+SELECT tags.id, tags.name, tags.slug, count(posts.id) AS post_count
+FROM tags
+INNER JOIN post_tags ON post_tags.tag_id = tags.id
+INNER JOIN posts ON posts.id = post_tags.post_id
+WHERE posts.published_status = $1
+    AND posts.visibility = $2
+    AND posts.deleted_at IS NULL
+GROUP BY tags.id, tags.name, tags.slug
+ORDER BY post_count DESC
+";
+
+#[derive(QueryableByName)]
+pub struct TagCount {
+    #[sql_type = "Integer"]
+    pub id: i32,
+    #[sql_type = "Text"]
+    pub name: String,
+    #[sql_type = "Text"]
+    pub slug: String,
+    #[sql_type = "BigInt"]
+    pub post_count: i64,
+}
+
+/// Number of public published posts per tag, tags without any are left out.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<TagCount>, diesel::result::Error>")]
+pub struct GetTagCloud;
+
+impl Handler<GetTagCloud> for DbActor {
+    type Result = Result<Vec<TagCount>, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetTagCloud, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        diesel::sql_query(TAG_CLOUD_QUERY)
+            .bind::<Text, _>(format!("{}", PublishStatus::Published))
+            .bind::<Text, _>(format!("{}", Visibility::Public))
+            .load::<TagCount>(&conn)
+    }
+}
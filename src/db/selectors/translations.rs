@@ -0,0 +1,114 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::{posts::PublishStatus, translations::PostTranslation},
+};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+
+#[derive(Message)]
+#[rtype(result = "Result<PostTranslation, diesel::result::Error>")]
+pub struct GetPostTranslation {
+    pub translation_id: i32,
+}
+
+impl Handler<GetPostTranslation> for DbActor {
+    type Result = Result<PostTranslation, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostTranslation, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+        post_translations
+            .filter(id.eq(msg.translation_id))
+            .get_result::<PostTranslation>(&conn)
+    }
+}
+
+/// Translations of a post ordered by locale, only the published ones if `published_only`.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<PostTranslation>, diesel::result::Error>")]
+pub struct GetPostTranslations {
+    pub post_id: i32,
+    pub published_only: bool,
+}
+
+impl Handler<GetPostTranslations> for DbActor {
+    type Result = Result<Vec<PostTranslation>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPostTranslations, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+        let mut query = post_translations
+            .filter(post_id.eq(msg.post_id))
+            .into_boxed();
+        if msg.published_only {
+            query = query.filter(published_status.eq(format!("{}", PublishStatus::Published)));
+        }
+        query.order(locale.asc()).load::<PostTranslation>(&conn)
+    }
+}
+
+/// Published translations of any of `post_ids` into any of `locales`.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<PostTranslation>, diesel::result::Error>")]
+pub struct GetPublishedTranslations {
+    pub post_ids: Vec<i32>,
+    pub locales: Vec<String>,
+}
+
+impl Handler<GetPublishedTranslations> for DbActor {
+    type Result = Result<Vec<PostTranslation>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: GetPublishedTranslations, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+        if msg.post_ids.is_empty() || msg.locales.is_empty() {
+            return Ok(vec![]);
+        }
+        post_translations
+            .filter(post_id.eq_any(msg.post_ids))
+            .filter(locale.eq_any(msg.locales))
+            .filter(published_status.eq(format!("{}", PublishStatus::Published)))
+            .load::<PostTranslation>(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<PostTranslation, diesel::result::Error>")]
+pub struct GetPublishedTranslationBySlug {
+    pub slug: String,
+}
+
+impl Handler<GetPublishedTranslationBySlug> for DbActor {
+    type Result = Result<PostTranslation, diesel::result::Error>;
+
+    fn handle(
+        &mut self,
+        msg: GetPublishedTranslationBySlug,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+        post_translations
+            .filter(slug.eq(msg.slug))
+            .filter(published_status.eq(format!("{}", PublishStatus::Published)))
+            .get_result::<PostTranslation>(&conn)
+    }
+}
+
+/// Translations waiting for an admin to publish them, oldest request first.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<PostTranslation>, diesel::result::Error>")]
+pub struct GetTranslationReviewQueue;
+
+impl Handler<GetTranslationReviewQueue> for DbActor {
+    type Result = Result<Vec<PostTranslation>, diesel::result::Error>;
+
+    fn handle(&mut self, _: GetTranslationReviewQueue, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+        post_translations
+            .filter(published_status.eq(format!("{}", PublishStatus::RequestToAdminForPublish)))
+            .order(updated_at.asc())
+            .load::<PostTranslation>(&conn)
+    }
+}
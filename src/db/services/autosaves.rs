@@ -0,0 +1,83 @@
+use crate::{
+    autosave::actor::{
+        AutosaveStoreError, DiscardAutosave, GetAutosave, SaveAutosave, SaveOutcome,
+    },
+    db::actor::DbActor,
+    db::models::autosaves::PostAutosave,
+};
+use actix::Handler;
+use diesel::prelude::*;
+
+/// Fallback for when redis is not available, see `AutosaveStore`.
+impl Handler<SaveAutosave> for DbActor {
+    type Result = Result<SaveOutcome, AutosaveStoreError>;
+
+    fn handle(&mut self, msg: SaveAutosave, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_autosaves::dsl::*;
+
+        let autosave = msg.autosave;
+        let saved = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let saved = if msg.base_revision == 0 {
+                diesel::insert_into(post_autosaves)
+                    .values(&autosave)
+                    .on_conflict_do_nothing()
+                    .get_result::<PostAutosave>(&conn)
+                    .optional()?
+            } else {
+                diesel::update(
+                    post_autosaves
+                        .filter(post_id.eq(autosave.post_id))
+                        .filter(user_id.eq(autosave.user_id))
+                        .filter(revision.eq(msg.base_revision)),
+                )
+                .set(&autosave)
+                .get_result::<PostAutosave>(&conn)
+                .optional()?
+            };
+            match saved {
+                Some(saved) => Ok(SaveOutcome::Saved(saved)),
+                None => post_autosaves
+                    .filter(post_id.eq(autosave.post_id))
+                    .filter(user_id.eq(autosave.user_id))
+                    .get_result::<PostAutosave>(&conn)
+                    .optional()
+                    .map(SaveOutcome::Conflict),
+            }
+        })?;
+        Ok(saved)
+    }
+}
+
+impl Handler<GetAutosave> for DbActor {
+    type Result = Result<Option<PostAutosave>, AutosaveStoreError>;
+
+    fn handle(&mut self, msg: GetAutosave, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_autosaves::dsl::*;
+
+        let autosave = post_autosaves
+            .filter(post_id.eq(msg.post_id))
+            .filter(user_id.eq(msg.user_id))
+            .get_result::<PostAutosave>(&conn)
+            .optional()?;
+        Ok(autosave)
+    }
+}
+
+impl Handler<DiscardAutosave> for DbActor {
+    type Result = Result<(), AutosaveStoreError>;
+
+    fn handle(&mut self, msg: DiscardAutosave, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_autosaves::dsl::*;
+
+        diesel::delete(
+            post_autosaves
+                .filter(post_id.eq(msg.post_id))
+                .filter(user_id.eq(msg.user_id)),
+        )
+        .execute(&conn)?;
+        Ok(())
+    }
+}
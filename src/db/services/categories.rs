@@ -0,0 +1,120 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::categories::{Category, NewCategory, PostCategory},
+    slugify::slugify,
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+#[derive(Message)]
+#[rtype(result = "Result<Category, diesel::result::Error>")]
+pub struct AddCategory {
+    pub name: String,
+    pub parent_id: Option<i32>,
+}
+
+impl Handler<AddCategory> for DbActor {
+    type Result = Result<Category, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddCategory, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::categories::dsl::*;
+        let new_category = NewCategory {
+            name: &msg.name,
+            slug: &slugify(&msg.name),
+            parent_id: msg.parent_id,
+        };
+        diesel::insert_into(categories)
+            .values(&new_category)
+            .get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Category, diesel::result::Error>")]
+pub struct UpdateCategory {
+    pub category_id: i32,
+    pub name: String,
+    pub parent_id: Option<i32>,
+}
+
+impl Handler<UpdateCategory> for DbActor {
+    type Result = Result<Category, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdateCategory, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::categories::dsl::*;
+        diesel::update(categories.filter(id.eq(msg.category_id)))
+            .set((
+                name.eq(&msg.name),
+                slug.eq(slugify(&msg.name)),
+                parent_id.eq(msg.parent_id),
+                updated_at.eq(now),
+            ))
+            .get_result(&conn)
+    }
+}
+
+/// Deletes a category, its sub categories are moved up to its parent.
+#[derive(Message)]
+#[rtype(result = "Result<Category, diesel::result::Error>")]
+pub struct DeleteCategory {
+    pub category_id: i32,
+}
+
+impl Handler<DeleteCategory> for DbActor {
+    type Result = Result<Category, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeleteCategory, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::categories::dsl::*;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let category = categories
+                .filter(id.eq(msg.category_id))
+                .for_update()
+                .get_result::<Category>(&conn)?;
+            diesel::update(categories.filter(parent_id.eq(category.id)))
+                .set((parent_id.eq(category.parent_id), updated_at.eq(now)))
+                .execute(&conn)?;
+            diesel::delete(categories.filter(id.eq(category.id))).get_result(&conn)
+        })
+    }
+}
+
+/// Replaces the categories of a post.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Category>, diesel::result::Error>")]
+pub struct SetPostCategories {
+    pub post_id: i32,
+    pub category_ids: Vec<i32>,
+}
+
+impl Handler<SetPostCategories> for DbActor {
+    type Result = Result<Vec<Category>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetPostCategories, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{categories, post_categories};
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(post_categories::table.filter(post_categories::post_id.eq(msg.post_id)))
+                .execute(&conn)?;
+            let new_post_categories = msg
+                .category_ids
+                .iter()
+                .map(|category_id| PostCategory {
+                    post_id: msg.post_id,
+                    category_id: *category_id,
+                })
+                .collect::<Vec<PostCategory>>();
+            diesel::insert_into(post_categories::table)
+                .values(&new_post_categories)
+                .on_conflict_do_nothing()
+                .execute(&conn)?;
+            categories::table
+                .filter(categories::id.eq_any(&msg.category_ids))
+                .order(categories::name.asc())
+                .load::<Category>(&conn)
+        })
+    }
+}
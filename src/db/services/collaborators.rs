@@ -0,0 +1,58 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::collaborators::{CollaboratorRole, NewPostCollaborator, PostCollaborator},
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// Adds a user as collaborator of a post, or changes their role if they already are one.
+#[derive(Message)]
+#[rtype(result = "Result<PostCollaborator, diesel::result::Error>")]
+pub struct SetPostCollaborator {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub role: CollaboratorRole,
+    pub invited_by: i32,
+}
+
+impl Handler<SetPostCollaborator> for DbActor {
+    type Result = Result<PostCollaborator, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetPostCollaborator, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_collaborators::dsl::*;
+        let new_role = format!("{}", msg.role);
+        diesel::insert_into(post_collaborators)
+            .values(&NewPostCollaborator {
+                post_id: msg.post_id,
+                user_id: msg.user_id,
+                role: &new_role,
+                invited_by: msg.invited_by,
+            })
+            .on_conflict((post_id, user_id))
+            .do_update()
+            .set((role.eq(&new_role), updated_at.eq(now)))
+            .get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<PostCollaborator, diesel::result::Error>")]
+pub struct RemovePostCollaborator {
+    pub post_id: i32,
+    pub user_id: i32,
+}
+
+impl Handler<RemovePostCollaborator> for DbActor {
+    type Result = Result<PostCollaborator, diesel::result::Error>;
+
+    fn handle(&mut self, msg: RemovePostCollaborator, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_collaborators::dsl::*;
+        diesel::delete(
+            post_collaborators.filter(post_id.eq(msg.post_id).and(user_id.eq(msg.user_id))),
+        )
+        .get_result(&conn)
+    }
+}
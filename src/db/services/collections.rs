@@ -0,0 +1,238 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::collections::{Collection, CollectionKind, CollectionPost, NewCollection},
+    slugify::slugify,
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// Locks a collection so that changes to its posts are applied one after the other.
+fn lock_collection(
+    conn: &PgConnection,
+    collection_id: i32,
+) -> Result<Collection, diesel::result::Error> {
+    use crate::db::schema::collections::dsl::*;
+
+    collections
+        .filter(id.eq(collection_id))
+        .for_update()
+        .get_result::<Collection>(conn)
+}
+
+/// Ids of the posts of a collection, in order.
+fn collection_post_ids(
+    conn: &PgConnection,
+    collection_id: i32,
+) -> Result<Vec<i32>, diesel::result::Error> {
+    use crate::db::schema::collection_posts;
+
+    collection_posts::table
+        .filter(collection_posts::collection_id.eq(collection_id))
+        .order(collection_posts::position.asc())
+        .select(collection_posts::post_id)
+        .load::<i32>(conn)
+}
+
+/// Replaces the posts of a collection with `post_ids`, in that order.
+fn write_collection_posts<'a>(
+    conn: &PgConnection,
+    collection_id: i32,
+    post_ids: &'a [i32],
+) -> Result<(), diesel::result::Error> {
+    use crate::db::schema::{collection_posts, collections};
+
+    diesel::delete(
+        collection_posts::table.filter(collection_posts::collection_id.eq(collection_id)),
+    )
+    .execute(conn)?;
+    let new_collection_posts = post_ids
+        .iter()
+        .enumerate()
+        .map(|(idx, post_id)| CollectionPost {
+            collection_id: collection_id,
+            post_id: *post_id,
+            position: idx as i32,
+        })
+        .collect::<Vec<CollectionPost>>();
+    diesel::insert_into(collection_posts::table)
+        .values(&new_collection_posts)
+        .execute(conn)?;
+    diesel::update(collections::table.filter(collections::id.eq(collection_id)))
+        .set(collections::updated_at.eq(now))
+        .execute(conn)?;
+    Ok(())
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Collection, diesel::result::Error>")]
+pub struct AddCollection {
+    pub name: String,
+    pub kind: CollectionKind,
+    pub description: String,
+    pub user_id: i32,
+}
+
+impl Handler<AddCollection> for DbActor {
+    type Result = Result<Collection, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddCollection, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::collections::dsl::*;
+        diesel::insert_into(collections)
+            .values(&NewCollection {
+                name: &msg.name,
+                slug: &slugify(&msg.name),
+                kind: &format!("{}", msg.kind),
+                description: &msg.description,
+                user_id: msg.user_id,
+            })
+            .get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Collection, diesel::result::Error>")]
+pub struct UpdateCollection {
+    pub collection_id: i32,
+    pub name: String,
+    pub description: String,
+}
+
+impl Handler<UpdateCollection> for DbActor {
+    type Result = Result<Collection, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdateCollection, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::collections::dsl::*;
+        diesel::update(collections.filter(id.eq(msg.collection_id)))
+            .set((
+                name.eq(&msg.name),
+                slug.eq(slugify(&msg.name)),
+                description.eq(&msg.description),
+                updated_at.eq(now),
+            ))
+            .get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Collection, diesel::result::Error>")]
+pub struct SetCollectionPinned {
+    pub collection_id: i32,
+    pub pinned: bool,
+}
+
+impl Handler<SetCollectionPinned> for DbActor {
+    type Result = Result<Collection, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetCollectionPinned, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::collections::dsl::*;
+        diesel::update(collections.filter(id.eq(msg.collection_id)))
+            .set((pinned.eq(msg.pinned), updated_at.eq(now)))
+            .get_result(&conn)
+    }
+}
+
+/// Deletes a collection, its posts are left as they are.
+#[derive(Message)]
+#[rtype(result = "Result<Collection, diesel::result::Error>")]
+pub struct DeleteCollection {
+    pub collection_id: i32,
+}
+
+impl Handler<DeleteCollection> for DbActor {
+    type Result = Result<Collection, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeleteCollection, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::collections::dsl::*;
+        diesel::delete(collections.filter(id.eq(msg.collection_id))).get_result(&conn)
+    }
+}
+
+/// Puts a post at `position` in a collection, moving it there if it already is in it.
+/// Positions past the last post put it last. Gives the ids of the posts in their new order.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<i32>, diesel::result::Error>")]
+pub struct AddCollectionPost {
+    pub collection_id: i32,
+    pub post_id: i32,
+    pub position: usize,
+}
+
+impl Handler<AddCollectionPost> for DbActor {
+    type Result = Result<Vec<i32>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddCollectionPost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let collection = lock_collection(&conn, msg.collection_id)?;
+            let mut post_ids = collection_post_ids(&conn, collection.id)?;
+            post_ids.retain(|post_id| *post_id != msg.post_id);
+            post_ids.insert(msg.position.min(post_ids.len()), msg.post_id);
+            write_collection_posts(&conn, collection.id, &post_ids)?;
+            Ok(post_ids)
+        })
+    }
+}
+
+/// Gives the ids of the posts left in the collection, in order.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<i32>, diesel::result::Error>")]
+pub struct RemoveCollectionPost {
+    pub collection_id: i32,
+    pub post_id: i32,
+}
+
+impl Handler<RemoveCollectionPost> for DbActor {
+    type Result = Result<Vec<i32>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: RemoveCollectionPost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let collection = lock_collection(&conn, msg.collection_id)?;
+            let mut post_ids = collection_post_ids(&conn, collection.id)?;
+            post_ids.retain(|post_id| *post_id != msg.post_id);
+            write_collection_posts(&conn, collection.id, &post_ids)?;
+            Ok(post_ids)
+        })
+    }
+}
+
+/// Puts the posts of a collection in the order of `post_ids`, which must hold each of them
+/// once. Posts added meanwhile are kept after them, posts removed meanwhile are dropped.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<i32>, diesel::result::Error>")]
+pub struct ReorderCollection {
+    pub collection_id: i32,
+    pub post_ids: Vec<i32>,
+}
+
+impl Handler<ReorderCollection> for DbActor {
+    type Result = Result<Vec<i32>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: ReorderCollection, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let collection = lock_collection(&conn, msg.collection_id)?;
+            let current = collection_post_ids(&conn, collection.id)?;
+            let mut post_ids = msg
+                .post_ids
+                .into_iter()
+                .filter(|post_id| current.contains(post_id))
+                .collect::<Vec<i32>>();
+            let added = current
+                .into_iter()
+                .filter(|post_id| !post_ids.contains(post_id))
+                .collect::<Vec<i32>>();
+            post_ids.extend(added);
+            write_collection_posts(&conn, collection.id, &post_ids)?;
+            Ok(post_ids)
+        })
+    }
+}
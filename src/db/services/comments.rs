@@ -0,0 +1,156 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::comments::{Comment, CommentStatus, NewComment},
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// Comments of users with fewer than `trusted_after_approved` approved
+/// comments go to the moderation queue, unless `skip_moderation` is set.
+#[derive(Message)]
+#[rtype(result = "Result<Comment, diesel::result::Error>")]
+pub struct AddComment {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub parent_id: Option<i32>,
+    pub body: String,
+    pub skip_moderation: bool,
+    pub trusted_after_approved: i64,
+}
+
+impl Handler<AddComment> for DbActor {
+    type Result = Result<Comment, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddComment, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::comments::dsl::*;
+        let approved = format!("{}", CommentStatus::Approved);
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            // replies are only allowed to visible comments of the same post
+            let thread_root_id = match msg.parent_id {
+                None => None,
+                Some(reply_to) => {
+                    let parent = comments
+                        .filter(
+                            id.eq(reply_to)
+                                .and(post_id.eq(msg.post_id))
+                                .and(status.eq(&approved)),
+                        )
+                        .get_result::<Comment>(&conn)?;
+                    Some(parent.root_id.unwrap_or(parent.id))
+                }
+            };
+            let approved_count = comments
+                .filter(user_id.eq(msg.user_id).and(status.eq(&approved)))
+                .count()
+                .get_result::<i64>(&conn)?;
+            let new_status = if msg.skip_moderation || approved_count >= msg.trusted_after_approved
+            {
+                CommentStatus::Approved
+            } else {
+                CommentStatus::Pending
+            };
+            let new_status = format!("{}", new_status);
+            let new_comment = NewComment {
+                post_id: msg.post_id,
+                user_id: msg.user_id,
+                parent_id: msg.parent_id,
+                root_id: thread_root_id,
+                body: &msg.body,
+                status: &new_status,
+            };
+            diesel::insert_into(comments)
+                .values(&new_comment)
+                .get_result(&conn)
+        })
+    }
+}
+
+/// Edited approved comments go back to the moderation queue, on the same terms
+/// as new comments do.
+#[derive(Message)]
+#[rtype(result = "Result<Comment, diesel::result::Error>")]
+pub struct UpdateCommentBody {
+    pub comment_id: i32,
+    pub new_body: String,
+    pub skip_moderation: bool,
+    pub trusted_after_approved: i64,
+}
+
+impl Handler<UpdateCommentBody> for DbActor {
+    type Result = Result<Comment, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdateCommentBody, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::comments::dsl::*;
+        let approved = format!("{}", CommentStatus::Approved);
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let comment = comments
+                .filter(id.eq(msg.comment_id))
+                .for_update()
+                .get_result::<Comment>(&conn)?;
+            // the approval of the comment being edited does not count towards trust
+            let approved_count = comments
+                .filter(
+                    user_id
+                        .eq(comment.user_id)
+                        .and(status.eq(&approved))
+                        .and(id.ne(comment.id)),
+                )
+                .count()
+                .get_result::<i64>(&conn)?;
+            let new_status = if comment.status != approved
+                || msg.skip_moderation
+                || approved_count >= msg.trusted_after_approved
+            {
+                comment.status
+            } else {
+                format!("{}", CommentStatus::Pending)
+            };
+            diesel::update(comments.filter(id.eq(comment.id)))
+                .set((
+                    body.eq(msg.new_body),
+                    status.eq(new_status),
+                    updated_at.eq(now),
+                ))
+                .get_result(&conn)
+        })
+    }
+}
+
+/// Deletes a comment along with all of the replies to it.
+#[derive(Message)]
+#[rtype(result = "Result<Comment, diesel::result::Error>")]
+pub struct DeleteComment {
+    pub comment_id: i32,
+}
+
+impl Handler<DeleteComment> for DbActor {
+    type Result = Result<Comment, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeleteComment, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::comments::dsl::*;
+        diesel::delete(comments.filter(id.eq(msg.comment_id))).get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Comment, diesel::result::Error>")]
+pub struct ModerateComment {
+    pub comment_id: i32,
+    pub new_status: CommentStatus,
+}
+
+impl Handler<ModerateComment> for DbActor {
+    type Result = Result<Comment, diesel::result::Error>;
+
+    fn handle(&mut self, msg: ModerateComment, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::comments::dsl::*;
+        diesel::update(comments.filter(id.eq(msg.comment_id)))
+            .set((status.eq(format!("{}", msg.new_status)), updated_at.eq(now)))
+            .get_result(&conn)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::content_types::{ContentType, NewContentType},
+    slugify::slugify,
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// `field_schema` must have been checked with `FieldSchema::parse` beforehand.
+#[derive(Message)]
+#[rtype(result = "Result<ContentType, diesel::result::Error>")]
+pub struct AddContentType {
+    pub name: String,
+    pub field_schema: serde_json::Value,
+}
+
+impl Handler<AddContentType> for DbActor {
+    type Result = Result<ContentType, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddContentType, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::content_types::dsl::*;
+        diesel::insert_into(content_types)
+            .values(&NewContentType {
+                name: &msg.name,
+                slug: &slugify(&msg.name),
+                field_schema: &msg.field_schema,
+            })
+            .get_result(&conn)
+    }
+}
+
+/// Existing entries are not validated again, they are checked against the new schema the
+/// next time they are written.
+#[derive(Message)]
+#[rtype(result = "Result<ContentType, diesel::result::Error>")]
+pub struct UpdateContentType {
+    pub content_type_id: i32,
+    pub name: String,
+    pub field_schema: serde_json::Value,
+}
+
+impl Handler<UpdateContentType> for DbActor {
+    type Result = Result<ContentType, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdateContentType, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::content_types::dsl::*;
+        diesel::update(content_types.filter(id.eq(msg.content_type_id)))
+            .set((
+                name.eq(&msg.name),
+                slug.eq(slugify(&msg.name)),
+                field_schema.eq(&msg.field_schema),
+                updated_at.eq(now),
+            ))
+            .get_result(&conn)
+    }
+}
+
+/// Fails while the content type still has entries, trashed ones included.
+#[derive(Message)]
+#[rtype(result = "Result<ContentType, diesel::result::Error>")]
+pub struct DeleteContentType {
+    pub content_type_id: i32,
+}
+
+impl Handler<DeleteContentType> for DbActor {
+    type Result = Result<ContentType, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeleteContentType, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::content_types::dsl::*;
+        diesel::delete(content_types.filter(id.eq(msg.content_type_id))).get_result(&conn)
+    }
+}
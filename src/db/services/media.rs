@@ -0,0 +1,161 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::media::{Media, MediaProcessingStatus, NewMedia, NewMediaVariant, NewPostMedia},
+};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+
+#[derive(Message)]
+#[rtype(result = "Result<Media, diesel::result::Error>")]
+pub struct AddMedia {
+    pub user_id: i32,
+    pub storage_key: String,
+    pub url: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub checksum: String,
+    pub processing_status: MediaProcessingStatus,
+}
+
+impl Handler<AddMedia> for DbActor {
+    type Result = Result<Media, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddMedia, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::media::dsl::*;
+        let new_media = NewMedia {
+            user_id: msg.user_id,
+            storage_key: &msg.storage_key,
+            url: &msg.url,
+            file_name: &msg.file_name,
+            mime_type: &msg.mime_type,
+            size_bytes: msg.size_bytes,
+            width: msg.width,
+            height: msg.height,
+            checksum: &msg.checksum,
+            processing_status: format!("{}", msg.processing_status),
+        };
+        diesel::insert_into(media)
+            .values(&new_media)
+            .get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Media, diesel::result::Error>")]
+pub struct DeleteMedia {
+    pub media_id: i32,
+}
+
+impl Handler<DeleteMedia> for DbActor {
+    type Result = Result<Media, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeleteMedia, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::media::dsl::*;
+        diesel::delete(media.filter(id.eq(msg.media_id))).get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<usize, diesel::result::Error>")]
+pub struct AttachMediaToPost {
+    pub post_id: i32,
+    pub media_id: i32,
+}
+
+impl Handler<AttachMediaToPost> for DbActor {
+    type Result = Result<usize, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AttachMediaToPost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_media::dsl::*;
+        diesel::insert_into(post_media)
+            .values(&NewPostMedia {
+                post_id: msg.post_id,
+                media_id: msg.media_id,
+            })
+            .on_conflict_do_nothing()
+            .execute(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<usize, diesel::result::Error>")]
+pub struct DetachMediaFromPost {
+    pub post_id: i32,
+    pub media_id: i32,
+}
+
+impl Handler<DetachMediaFromPost> for DbActor {
+    type Result = Result<usize, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DetachMediaFromPost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_media::dsl::*;
+        diesel::delete(post_media.filter(post_id.eq(msg.post_id).and(media_id.eq(msg.media_id))))
+            .execute(&conn)
+    }
+}
+
+/// Records the outcome of processing an image, the original was replaced by
+/// a copy without metadata so its size and checksum change too.
+#[derive(Message)]
+#[rtype(result = "Result<Media, diesel::result::Error>")]
+pub struct SaveProcessedMedia {
+    pub media_id: i32,
+    pub size_bytes: i64,
+    pub checksum: String,
+    pub width: i32,
+    pub height: i32,
+    pub blurhash: String,
+    pub variants: Vec<NewMediaVariant>,
+}
+
+impl Handler<SaveProcessedMedia> for DbActor {
+    type Result = Result<Media, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SaveProcessedMedia, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{media, media_variants};
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(media_variants::table.filter(media_variants::media_id.eq(msg.media_id)))
+                .execute(&conn)?;
+            diesel::insert_into(media_variants::table)
+                .values(&msg.variants)
+                .execute(&conn)?;
+            diesel::update(media::table.filter(media::id.eq(msg.media_id)))
+                .set((
+                    media::size_bytes.eq(msg.size_bytes),
+                    media::checksum.eq(msg.checksum),
+                    media::width.eq(Some(msg.width)),
+                    media::height.eq(Some(msg.height)),
+                    media::blurhash.eq(Some(msg.blurhash)),
+                    media::processing_status.eq(format!("{}", MediaProcessingStatus::Processed)),
+                ))
+                .get_result(&conn)
+        })
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Media, diesel::result::Error>")]
+pub struct SetMediaProcessingStatus {
+    pub media_id: i32,
+    pub status: MediaProcessingStatus,
+}
+
+impl Handler<SetMediaProcessingStatus> for DbActor {
+    type Result = Result<Media, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetMediaProcessingStatus, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::media::dsl::*;
+        diesel::update(media.filter(id.eq(msg.media_id)))
+            .set(processing_status.eq(format!("{}", msg.status)))
+            .get_result(&conn)
+    }
+}
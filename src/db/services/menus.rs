@@ -0,0 +1,225 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::menus::{Menu, MenuItem, MenuTarget, NewMenu, NewMenuItem},
+    slugify::slugify,
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// Ids of the items of `menu` below `parent`, in order, leaving out `exclude_id`.
+fn child_item_ids(
+    conn: &PgConnection,
+    menu: i32,
+    parent: Option<i32>,
+    exclude_id: Option<i32>,
+) -> Result<Vec<i32>, diesel::result::Error> {
+    use crate::db::schema::menu_items::dsl::*;
+
+    let mut query = menu_items
+        .filter(menu_id.eq(menu))
+        .select(id)
+        .order((position.asc(), id.asc()))
+        .into_boxed();
+    query = match parent {
+        Some(parent) => query.filter(parent_id.eq(parent)),
+        None => query.filter(parent_id.is_null()),
+    };
+    if let Some(exclude_id) = exclude_id {
+        query = query.filter(id.ne(exclude_id));
+    }
+    query.load::<i32>(conn)
+}
+
+/// Sets the position of each item to its index in `item_ids`.
+fn renumber_items<'a>(
+    conn: &PgConnection,
+    item_ids: &'a [i32],
+) -> Result<(), diesel::result::Error> {
+    use crate::db::schema::menu_items::dsl::*;
+
+    for (idx, item_id) in item_ids.iter().enumerate() {
+        diesel::update(menu_items.filter(id.eq(item_id)))
+            .set(position.eq(idx as i32))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+fn lock_item(conn: &PgConnection, item_id: i32) -> Result<MenuItem, diesel::result::Error> {
+    use crate::db::schema::menu_items::dsl::*;
+
+    menu_items
+        .filter(id.eq(item_id))
+        .for_update()
+        .get_result::<MenuItem>(conn)
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Menu, diesel::result::Error>")]
+pub struct AddMenu {
+    pub name: String,
+}
+
+impl Handler<AddMenu> for DbActor {
+    type Result = Result<Menu, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddMenu, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::menus::dsl::*;
+        diesel::insert_into(menus)
+            .values(&NewMenu {
+                name: &msg.name,
+                slug: &slugify(&msg.name),
+            })
+            .get_result(&conn)
+    }
+}
+
+/// Deletes a menu along with its items.
+#[derive(Message)]
+#[rtype(result = "Result<Menu, diesel::result::Error>")]
+pub struct DeleteMenu {
+    pub menu_id: i32,
+}
+
+impl Handler<DeleteMenu> for DbActor {
+    type Result = Result<Menu, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeleteMenu, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::menus::dsl::*;
+        diesel::delete(menus.filter(id.eq(msg.menu_id))).get_result(&conn)
+    }
+}
+
+/// Adds an item after the other items below its parent, the parent must be in the same menu.
+#[derive(Message)]
+#[rtype(result = "Result<MenuItem, diesel::result::Error>")]
+pub struct AddMenuItem {
+    pub menu_id: i32,
+    pub parent_id: Option<i32>,
+    pub label: String,
+    pub target: MenuTarget,
+}
+
+impl Handler<AddMenuItem> for DbActor {
+    type Result = Result<MenuItem, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddMenuItem, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::menu_items::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let siblings = child_item_ids(&conn, msg.menu_id, msg.parent_id, None)?;
+            let (target_page_id, target_post_id, target_category_id, target_url) =
+                msg.target.columns();
+            diesel::insert_into(menu_items)
+                .values(&NewMenuItem {
+                    menu_id: msg.menu_id,
+                    parent_id: msg.parent_id,
+                    position: siblings.len() as i32,
+                    label: &msg.label,
+                    page_id: target_page_id,
+                    post_id: target_post_id,
+                    category_id: target_category_id,
+                    url: target_url,
+                })
+                .get_result(&conn)
+        })
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<MenuItem, diesel::result::Error>")]
+pub struct UpdateMenuItem {
+    pub item_id: i32,
+    pub label: String,
+    pub target: MenuTarget,
+}
+
+impl Handler<UpdateMenuItem> for DbActor {
+    type Result = Result<MenuItem, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdateMenuItem, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::menu_items::dsl::*;
+        let (target_page_id, target_post_id, target_category_id, target_url) = msg.target.columns();
+        diesel::update(menu_items.filter(id.eq(msg.item_id)))
+            .set((
+                label.eq(&msg.label),
+                page_id.eq(target_page_id),
+                post_id.eq(target_post_id),
+                category_id.eq(target_category_id),
+                url.eq(target_url),
+                updated_at.eq(now),
+            ))
+            .get_result(&conn)
+    }
+}
+
+/// Moves an item along with the items below it under `parent_id` of the same menu, at
+/// `position` among its new siblings. Positions past the last sibling put the item last.
+#[derive(Message)]
+#[rtype(result = "Result<MenuItem, diesel::result::Error>")]
+pub struct MoveMenuItem {
+    pub item_id: i32,
+    pub parent_id: Option<i32>,
+    pub position: usize,
+}
+
+impl Handler<MoveMenuItem> for DbActor {
+    type Result = Result<MenuItem, diesel::result::Error>;
+
+    fn handle(&mut self, msg: MoveMenuItem, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::menu_items::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let item = lock_item(&conn, msg.item_id)?;
+            diesel::update(menu_items.filter(id.eq(item.id)))
+                .set((parent_id.eq(msg.parent_id), updated_at.eq(now)))
+                .execute(&conn)?;
+
+            let mut siblings = child_item_ids(&conn, item.menu_id, msg.parent_id, Some(item.id))?;
+            siblings.insert(msg.position.min(siblings.len()), item.id);
+            renumber_items(&conn, &siblings)?;
+            if item.parent_id != msg.parent_id {
+                renumber_items(
+                    &conn,
+                    &child_item_ids(&conn, item.menu_id, item.parent_id, Some(item.id))?,
+                )?;
+            }
+            menu_items
+                .filter(id.eq(item.id))
+                .get_result::<MenuItem>(&conn)
+        })
+    }
+}
+
+/// Deletes an item along with the items below it.
+#[derive(Message)]
+#[rtype(result = "Result<MenuItem, diesel::result::Error>")]
+pub struct DeleteMenuItem {
+    pub item_id: i32,
+}
+
+impl Handler<DeleteMenuItem> for DbActor {
+    type Result = Result<MenuItem, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeleteMenuItem, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::menu_items::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let item = lock_item(&conn, msg.item_id)?;
+            let deleted =
+                diesel::delete(menu_items.filter(id.eq(item.id))).get_result::<MenuItem>(&conn)?;
+            renumber_items(
+                &conn,
+                &child_item_ids(&conn, item.menu_id, item.parent_id, None)?,
+            )?;
+            Ok(deleted)
+        })
+    }
+}
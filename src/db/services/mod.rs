@@ -1,2 +1,16 @@
 pub mod users;
 pub mod posts;
+pub mod categories;
+pub mod tags;
+pub mod media;
+pub mod comments;
+pub mod reactions;
+pub mod stats;
+pub mod collaborators;
+pub mod autosaves;
+pub mod previews;
+pub mod translations;
+pub mod content_types;
+pub mod pages;
+pub mod menus;
+pub mod collections;
@@ -0,0 +1,230 @@
+use crate::{
+    body_renderer::render_body,
+    db::actor::DbActor,
+    db::models::pages::{NewPage, Page, PageChangeset},
+    db::models::posts::BodyFormat,
+    slugify::slugify,
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// Ids of the pages below `parent`, in order, leaving out `exclude_id`.
+fn child_page_ids(
+    conn: &PgConnection,
+    parent: Option<i32>,
+    exclude_id: Option<i32>,
+) -> Result<Vec<i32>, diesel::result::Error> {
+    use crate::db::schema::pages::dsl::*;
+
+    let mut query = pages
+        .select(id)
+        .order((position.asc(), id.asc()))
+        .into_boxed();
+    query = match parent {
+        Some(parent) => query.filter(parent_id.eq(parent)),
+        None => query.filter(parent_id.is_null()),
+    };
+    if let Some(exclude_id) = exclude_id {
+        query = query.filter(id.ne(exclude_id));
+    }
+    query.load::<i32>(conn)
+}
+
+/// Sets the position of each page to its index in `page_ids`.
+fn renumber_pages<'a>(
+    conn: &PgConnection,
+    page_ids: &'a [i32],
+) -> Result<(), diesel::result::Error> {
+    use crate::db::schema::pages::dsl::*;
+
+    for (idx, page_id) in page_ids.iter().enumerate() {
+        diesel::update(pages.filter(id.eq(page_id)))
+            .set(position.eq(idx as i32))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+fn lock_page(conn: &PgConnection, page_id: i32) -> Result<Page, diesel::result::Error> {
+    use crate::db::schema::pages::dsl::*;
+
+    pages
+        .filter(id.eq(page_id))
+        .for_update()
+        .get_result::<Page>(conn)
+}
+
+/// Adds a page after the other pages below its parent.
+#[derive(Message)]
+#[rtype(result = "Result<Page, diesel::result::Error>")]
+pub struct AddPage {
+    pub title: String,
+    pub body: String,
+    pub body_format: BodyFormat,
+    /// Made from the title when not given
+    pub slug: Option<String>,
+    pub parent_id: Option<i32>,
+    pub published: bool,
+    pub user_id: i32,
+}
+
+impl Handler<AddPage> for DbActor {
+    type Result = Result<Page, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddPage, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::pages::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let siblings = child_page_ids(&conn, msg.parent_id, None)?;
+            diesel::insert_into(pages)
+                .values(&NewPage {
+                    title: &msg.title,
+                    body: &msg.body,
+                    body_format: &format!("{}", msg.body_format),
+                    rendered_body: &render_body(&msg.body, &msg.body_format),
+                    slug: &slugify(msg.slug.as_ref().unwrap_or(&msg.title)),
+                    parent_id: msg.parent_id,
+                    position: siblings.len() as i32,
+                    published: msg.published,
+                    created_by: msg.user_id,
+                })
+                .get_result(&conn)
+        })
+    }
+}
+
+/// Fields of a page to change together, `None` leaves a field as it is.
+#[derive(Default)]
+pub struct PageChanges {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub body_format: Option<BodyFormat>,
+    pub slug: Option<String>,
+    pub published: Option<bool>,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Page, diesel::result::Error>")]
+pub struct UpdatePage {
+    pub page_id: i32,
+    pub changes: PageChanges,
+}
+
+impl Handler<UpdatePage> for DbActor {
+    type Result = Result<Page, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdatePage, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::pages::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let page = lock_page(&conn, msg.page_id)?;
+            let changes = msg.changes;
+
+            // the body is rendered again when either it or its format changes
+            let rendered = match (&changes.body, changes.body_format) {
+                (None, None) => None,
+                (new_body, new_format) => {
+                    let new_body = new_body.as_ref().unwrap_or(&page.body);
+                    let new_format = new_format
+                        .or_else(|| page.body_format.parse::<BodyFormat>().ok())
+                        .unwrap_or(BodyFormat::Plain);
+                    Some((
+                        format!("{}", new_format),
+                        render_body(new_body, &new_format),
+                    ))
+                }
+            };
+            let (new_format, new_rendered_body) = match rendered {
+                Some((new_format, new_rendered_body)) => {
+                    (Some(new_format), Some(new_rendered_body))
+                }
+                None => (None, None),
+            };
+            diesel::update(pages.filter(id.eq(page.id)))
+                .set((
+                    &PageChangeset {
+                        title: changes.title,
+                        body: changes.body,
+                        body_format: new_format,
+                        rendered_body: new_rendered_body,
+                        slug: changes.slug.map(|new_slug| slugify(&new_slug)),
+                        published: changes.published,
+                    },
+                    updated_at.eq(now),
+                ))
+                .get_result(&conn)
+        })
+    }
+}
+
+/// Moves a page along with the pages below it under `parent_id`, at `position` among its
+/// new siblings. Positions past the last sibling put the page last.
+#[derive(Message)]
+#[rtype(result = "Result<Page, diesel::result::Error>")]
+pub struct MovePage {
+    pub page_id: i32,
+    pub parent_id: Option<i32>,
+    pub position: usize,
+}
+
+impl Handler<MovePage> for DbActor {
+    type Result = Result<Page, diesel::result::Error>;
+
+    fn handle(&mut self, msg: MovePage, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::pages::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let page = lock_page(&conn, msg.page_id)?;
+            diesel::update(pages.filter(id.eq(page.id)))
+                .set((parent_id.eq(msg.parent_id), updated_at.eq(now)))
+                .execute(&conn)?;
+
+            let mut siblings = child_page_ids(&conn, msg.parent_id, Some(page.id))?;
+            siblings.insert(msg.position.min(siblings.len()), page.id);
+            renumber_pages(&conn, &siblings)?;
+            if page.parent_id != msg.parent_id {
+                renumber_pages(
+                    &conn,
+                    &child_page_ids(&conn, page.parent_id, Some(page.id))?,
+                )?;
+            }
+            pages.filter(id.eq(page.id)).get_result::<Page>(&conn)
+        })
+    }
+}
+
+/// Deletes a page, the pages below it take its place under its parent.
+#[derive(Message)]
+#[rtype(result = "Result<Page, diesel::result::Error>")]
+pub struct DeletePage {
+    pub page_id: i32,
+}
+
+impl Handler<DeletePage> for DbActor {
+    type Result = Result<Page, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeletePage, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::pages::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let page = lock_page(&conn, msg.page_id)?;
+            let children = child_page_ids(&conn, Some(page.id), None)?;
+            diesel::update(pages.filter(parent_id.eq(page.id)))
+                .set((parent_id.eq(page.parent_id), updated_at.eq(now)))
+                .execute(&conn)?;
+            let deleted = diesel::delete(pages.filter(id.eq(page.id))).get_result::<Page>(&conn)?;
+
+            let mut siblings = child_page_ids(&conn, page.parent_id, None)?;
+            siblings.retain(|sibling_id| !children.contains(sibling_id));
+            let at = (page.position.max(0) as usize).min(siblings.len());
+            siblings.splice(at..at, children);
+            renumber_pages(&conn, &siblings)?;
+            Ok(deleted)
+        })
+    }
+}
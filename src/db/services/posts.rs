@@ -1,10 +1,168 @@
 use crate::{
+    argon2_password_hasher::hash_password,
+    body_renderer::render_body,
+    content_blocks::{
+        apply_edit, render_html as render_blocks_html, render_text as render_blocks_text, Block,
+        BlockEdit, BlockEditError,
+    },
     db::actor::DbActor,
-    db::models::posts::{NewPost, Post, PublishStatus},
+    db::models::collaborators::CollaboratorRole,
+    db::models::tags::{PostTag, Tag},
+    db::models::posts::{
+        BodyFormat, NewEntry, NewPost, NewPostSlugRedirect, Post, PostChangeset, PublishStatus,
+        Visibility,
+    },
+    db::services::tags::get_or_create_tag,
+    slugify::{slugify, with_collision_suffix},
 };
 use actix::{Handler, Message};
+use derive_more::Display;
 use diesel::expression::dsl::now;
+use diesel::pg::Pg;
 use diesel::prelude::*;
+use diesel::sql_types::Bool;
+use std::collections::HashMap;
+
+const FALLBACK_SLUG: &'static str = "post";
+
+/// Picks a slug derived from `base` that is neither used by another post or translation nor
+/// reserved as a redirect of another post. Slugs already belonging to `post_id` may be reused.
+pub fn unique_slug<'a>(
+    conn: &PgConnection,
+    base: &'a str,
+    post_id: Option<i32>,
+) -> Result<String, diesel::result::Error> {
+    use crate::db::schema::{post_slug_redirects, post_translations, posts};
+
+    let base = match slugify(base) {
+        slug if slug.is_empty() => FALLBACK_SLUG.to_string(),
+        slug => slug,
+    };
+    let pattern = format!("{}-%", base);
+
+    let mut taken = posts::table
+        .filter(posts::slug.eq(&base).or(posts::slug.like(&pattern)))
+        .select((posts::id, posts::slug))
+        .load::<(i32, String)>(conn)?;
+    taken.extend(
+        post_slug_redirects::table
+            .filter(
+                post_slug_redirects::slug
+                    .eq(&base)
+                    .or(post_slug_redirects::slug.like(&pattern)),
+            )
+            .select((post_slug_redirects::post_id, post_slug_redirects::slug))
+            .load::<(i32, String)>(conn)?,
+    );
+
+    let mut taken = taken
+        .into_iter()
+        .filter(|(owner_id, _)| Some(*owner_id) != post_id)
+        .map(|(_, slug)| slug)
+        .collect::<Vec<String>>();
+    // not even the translations of `post_id` may share its slug, or they could not be told apart
+    taken.extend(
+        post_translations::table
+            .filter(
+                post_translations::slug
+                    .eq(&base)
+                    .or(post_translations::slug.like(&pattern)),
+            )
+            .select(post_translations::slug)
+            .load::<String>(conn)?,
+    );
+    Ok(with_collision_suffix(&base, &taken))
+}
+
+/// Slug of a new post whose subject or requested slug has nothing to derive one from, taken
+/// as soon as its id is known.
+fn placeholder_slug() -> String {
+    uuid::Uuid::new_v4().to_simple().to_string()
+}
+
+/// Replaces the placeholder slug of a new post with `post-{id}`.
+fn with_id_slug(conn: &PgConnection, post: Post) -> Result<Post, diesel::result::Error> {
+    use crate::db::schema::posts;
+
+    let new_slug = unique_slug(
+        conn,
+        &format!("{}-{}", FALLBACK_SLUG, post.id),
+        Some(post.id),
+    )?;
+    diesel::update(posts::table.filter(posts::id.eq(post.id)))
+        .set(posts::slug.eq(new_slug))
+        .get_result(conn)
+}
+
+/// Matches the posts `editor_id` may edit: the ones they own and the ones they collaborate
+/// on with a role that allows editing.
+fn editable_by(
+    editor_id: i32,
+) -> Box<dyn BoxableExpression<crate::db::schema::posts::table, Pg, SqlType = Bool>> {
+    use crate::db::schema::{post_collaborators, posts};
+
+    Box::new(
+        posts::user_id.eq(editor_id).or(posts::id.eq_any(
+            post_collaborators::table
+                .filter(
+                    post_collaborators::user_id
+                        .eq(editor_id)
+                        .and(post_collaborators::role.ne(format!("{}", CollaboratorRole::Viewer))),
+                )
+                .select(post_collaborators::post_id),
+        )),
+    )
+}
+
+/// Picks a new slug for `post` derived from `base`, making its current slug redirect to it.
+/// Returns `None` when the post keeps its slug.
+fn move_slug(
+    conn: &PgConnection,
+    post: &Post,
+    base: &str,
+) -> Result<Option<String>, diesel::result::Error> {
+    use crate::db::schema::post_slug_redirects;
+
+    let new_slug = unique_slug(conn, base, Some(post.id))?;
+    if new_slug == post.slug {
+        return Ok(None);
+    }
+
+    // the post may be taking back one of its own old slugs
+    diesel::delete(post_slug_redirects::table.filter(post_slug_redirects::slug.eq(&new_slug)))
+        .execute(conn)?;
+    diesel::insert_into(post_slug_redirects::table)
+        .values(&NewPostSlugRedirect {
+            slug: &post.slug,
+            post_id: post.id,
+        })
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+    Ok(Some(new_slug))
+}
+
+/// Outcome of an update made against the version of the post the client last saw.
+pub enum VersionedUpdate {
+    Updated(Post),
+    /// The post changed since that version and was left untouched
+    Conflict(Post),
+}
+
+/// Locks a post `editor_id` may edit for the rest of the transaction.
+fn lock_editable_post(
+    conn: &PgConnection,
+    post_id: i32,
+    editor_id: i32,
+) -> Result<Post, diesel::result::Error> {
+    use crate::db::schema::posts;
+
+    posts::table
+        .filter(posts::id.eq(post_id))
+        .filter(posts::deleted_at.is_null())
+        .filter(editable_by(editor_id))
+        .for_update()
+        .get_result::<Post>(conn)
+}
 
 #[derive(Message)]
 #[rtype(result = "Result<Post, diesel::result::Error>")]
@@ -12,6 +170,8 @@ pub struct AddPost {
     pub subject: String,
     pub body: String,
     pub user_id: i32,
+    pub slug: Option<String>,
+    pub body_format: BodyFormat,
 }
 
 impl Handler<AddPost> for DbActor {
@@ -21,63 +181,287 @@ impl Handler<AddPost> for DbActor {
         let conn = self.get_conn();
         use crate::db::schema::posts::dsl::*;
 
-        let new_post = NewPost {
-            post_subject: &msg.subject,
-            post_body: &msg.body,
-            user_id: msg.user_id,
-            published_status: &format!("{}", PublishStatus::Unpublished),
-        };
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let base = msg.slug.as_ref().unwrap_or(&msg.subject);
+            let sluggable = !slugify(base).is_empty();
+            let new_slug = if sluggable {
+                unique_slug(&conn, base, None)?
+            } else {
+                placeholder_slug()
+            };
+            let new_post = NewPost {
+                post_subject: &msg.subject,
+                post_body: &msg.body,
+                user_id: msg.user_id,
+                published_status: &format!("{}", PublishStatus::Unpublished),
+                slug: &new_slug,
+                body_format: &format!("{}", msg.body_format),
+                rendered_body: &render_body(&msg.body, &msg.body_format),
+            };
 
-        let res = diesel::insert_into(posts)
-            .values(&new_post)
-            .get_result(&conn)?;
-        Ok(res)
+            let post = diesel::insert_into(posts)
+                .values(&new_post)
+                .get_result::<Post>(&conn)?;
+            if sluggable {
+                Ok(post)
+            } else {
+                with_id_slug(&conn, post)
+            }
+        })
     }
 }
 
+/// Adds an entry of a content type, `fields` must have been validated against its schema.
 #[derive(Message)]
 #[rtype(result = "Result<Post, diesel::result::Error>")]
+pub struct AddEntry {
+    pub content_type_id: i32,
+    pub subject: String,
+    pub fields: serde_json::Value,
+    pub user_id: i32,
+    pub slug: Option<String>,
+}
+
+impl Handler<AddEntry> for DbActor {
+    type Result = Result<Post, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddEntry, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let base = msg.slug.as_ref().unwrap_or(&msg.subject);
+            let sluggable = !slugify(base).is_empty();
+            let new_slug = if sluggable {
+                unique_slug(&conn, base, None)?
+            } else {
+                placeholder_slug()
+            };
+            let post = diesel::insert_into(posts)
+                .values(&NewEntry {
+                    post_subject: &msg.subject,
+                    post_body: "",
+                    user_id: msg.user_id,
+                    published_status: &format!("{}", PublishStatus::Unpublished),
+                    slug: &new_slug,
+                    content_type_id: msg.content_type_id,
+                    fields: &msg.fields,
+                })
+                .get_result::<Post>(&conn)?;
+            if sluggable {
+                Ok(post)
+            } else {
+                with_id_slug(&conn, post)
+            }
+        })
+    }
+}
+
+/// Changes the slug of a post, the old slug keeps redirecting to the post.
+#[derive(Message)]
+#[rtype(result = "Result<VersionedUpdate, diesel::result::Error>")]
+pub struct UpdatePostSlug {
+    pub post_id: i32,
+    pub new_slug: String,
+    pub user_id: i32,
+    pub expected_version: i32,
+}
+
+impl Handler<UpdatePostSlug> for DbActor {
+    type Result = Result<VersionedUpdate, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdatePostSlug, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let post = lock_editable_post(&conn, msg.post_id, msg.user_id)?;
+            if post.version != msg.expected_version {
+                return Ok(VersionedUpdate::Conflict(post));
+            }
+
+            let new_slug = match move_slug(&conn, &post, &msg.new_slug)? {
+                Some(new_slug) => new_slug,
+                None => return Ok(VersionedUpdate::Updated(post)),
+            };
+
+            diesel::update(posts::table.filter(posts::id.eq(post.id)))
+                .set((
+                    posts::slug.eq(&new_slug),
+                    posts::updated_at.eq(now),
+                    posts::version.eq(posts::version + 1),
+                ))
+                .get_result(&conn)
+                .map(VersionedUpdate::Updated)
+        })
+    }
+}
+
+/// Fields of a post to change together, `None` leaves a field as it is.
+#[derive(Default)]
+pub struct PostChanges {
+    pub subject: Option<String>,
+    pub body: Option<String>,
+    pub body_format: Option<BodyFormat>,
+    pub slug: Option<String>,
+    pub comments_enabled: Option<bool>,
+    /// Only for entries, validated against their content type beforehand
+    pub fields: Option<serde_json::Value>,
+}
+
+impl PostChanges {
+    pub fn is_empty(&self) -> bool {
+        self.subject.is_none()
+            && self.body.is_none()
+            && self.body_format.is_none()
+            && self.slug.is_none()
+            && self.comments_enabled.is_none()
+            && self.fields.is_none()
+    }
+}
+
+/// Applies any subset of `PostChanges` at once, bumping `updated_at` and the version once.
+#[derive(Message)]
+#[rtype(result = "Result<VersionedUpdate, diesel::result::Error>")]
+pub struct UpdatePost {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub changes: PostChanges,
+    pub expected_version: i32,
+}
+
+impl Handler<UpdatePost> for DbActor {
+    type Result = Result<VersionedUpdate, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdatePost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let post = lock_editable_post(&conn, msg.post_id, msg.user_id)?;
+            if post.version != msg.expected_version {
+                return Ok(VersionedUpdate::Conflict(post));
+            }
+            let changes = msg.changes;
+            if changes.is_empty() {
+                return Ok(VersionedUpdate::Updated(post));
+            }
+
+            // the body is rendered again when either it or its format changes
+            let rendered = match (&changes.body, changes.body_format) {
+                (None, None) => None,
+                (new_body, new_body_format) => {
+                    let new_body_format = new_body_format.unwrap_or_else(|| {
+                        post.body_format
+                            .parse::<BodyFormat>()
+                            .unwrap_or(BodyFormat::Plain)
+                    });
+                    Some(render_body(
+                        new_body.as_ref().unwrap_or(&post.post_body),
+                        &new_body_format,
+                    ))
+                }
+            };
+            let new_slug = match &changes.slug {
+                Some(new_slug) => move_slug(&conn, &post, new_slug)?,
+                None => None,
+            };
+
+            let changeset = PostChangeset {
+                post_subject: changes.subject,
+                post_body: changes.body,
+                body_format: changes
+                    .body_format
+                    .map(|new_body_format| format!("{}", new_body_format)),
+                rendered_body: rendered,
+                slug: new_slug,
+                comments_enabled: changes.comments_enabled,
+                fields: changes.fields,
+            };
+            diesel::update(posts.filter(id.eq(post.id)))
+                .set((&changeset, updated_at.eq(now), version.eq(version + 1)))
+                .get_result(&conn)
+                .map(VersionedUpdate::Updated)
+        })
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<VersionedUpdate, diesel::result::Error>")]
 pub struct UpdatePostSubject {
     pub post_id: i32,
     pub new_subject: String,
     pub user_id: i32,
+    pub expected_version: i32,
 }
 
 impl Handler<UpdatePostSubject> for DbActor {
-    type Result = Result<Post, diesel::result::Error>;
+    type Result = Result<VersionedUpdate, diesel::result::Error>;
 
     fn handle(&mut self, msg: UpdatePostSubject, _: &mut Self::Context) -> Self::Result {
         let conn = self.get_conn();
         use crate::db::schema::posts::dsl::*;
 
-        let res = diesel::update(posts)
-            .filter(id.eq(msg.post_id).and(user_id.eq(msg.user_id)))
-            .set((post_subject.eq(msg.new_subject), updated_at.eq(now)))
-            .get_result(&conn)?;
-        Ok(res)
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let post = lock_editable_post(&conn, msg.post_id, msg.user_id)?;
+            if post.version != msg.expected_version {
+                return Ok(VersionedUpdate::Conflict(post));
+            }
+
+            diesel::update(posts.filter(id.eq(post.id)))
+                .set((
+                    post_subject.eq(msg.new_subject),
+                    updated_at.eq(now),
+                    version.eq(version + 1),
+                ))
+                .get_result(&conn)
+                .map(VersionedUpdate::Updated)
+        })
     }
 }
 
 #[derive(Message)]
-#[rtype(result = "Result<Post, diesel::result::Error>")]
+#[rtype(result = "Result<VersionedUpdate, diesel::result::Error>")]
 pub struct UpdatePostBody {
     pub post_id: i32,
     pub new_body: String,
     pub user_id: i32,
+    /// Keeps the current format of the post when `None`
+    pub new_body_format: Option<BodyFormat>,
+    pub expected_version: i32,
 }
 
 impl Handler<UpdatePostBody> for DbActor {
-    type Result = Result<Post, diesel::result::Error>;
+    type Result = Result<VersionedUpdate, diesel::result::Error>;
 
     fn handle(&mut self, msg: UpdatePostBody, _: &mut Self::Context) -> Self::Result {
         let conn = self.get_conn();
         use crate::db::schema::posts::dsl::*;
 
-        let res = diesel::update(posts)
-            .filter(id.eq(msg.post_id).and(user_id.eq(msg.user_id)))
-            .set((post_body.eq(msg.new_body), updated_at.eq(now)))
-            .get_result(&conn)?;
-        Ok(res)
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let post = lock_editable_post(&conn, msg.post_id, msg.user_id)?;
+            if post.version != msg.expected_version {
+                return Ok(VersionedUpdate::Conflict(post));
+            }
+            let new_body_format = match msg.new_body_format {
+                Some(new_body_format) => new_body_format,
+                None => post
+                    .body_format
+                    .parse::<BodyFormat>()
+                    .unwrap_or(BodyFormat::Plain),
+            };
+
+            diesel::update(posts.filter(id.eq(post.id)))
+                .set((
+                    rendered_body.eq(render_body(&msg.new_body, &new_body_format)),
+                    post_body.eq(msg.new_body),
+                    body_format.eq(format!("{}", new_body_format)),
+                    updated_at.eq(now),
+                    version.eq(version + 1),
+                ))
+                .get_result(&conn)
+                .map(VersionedUpdate::Updated)
+        })
     }
 }
 
@@ -95,12 +479,74 @@ impl Handler<DeletePost> for DbActor {
         let conn = self.get_conn();
         use crate::db::schema::posts::dsl::*;
 
-        let res = diesel::delete(posts.filter(id.eq(msg.post_id).and(user_id.eq(msg.user_id))))
-            .get_result(&conn)?;
+        let res = diesel::update(
+            posts.filter(
+                id.eq(msg.post_id)
+                    .and(user_id.eq(msg.user_id))
+                    .and(deleted_at.is_null()),
+            ),
+        )
+        .set((
+            deleted_at.eq(now.nullable()),
+            updated_at.eq(now),
+            version.eq(version + 1),
+        ))
+        .get_result(&conn)?;
+        Ok(res)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Post, diesel::result::Error>")]
+pub struct RestorePost {
+    pub post_id: i32,
+    pub user_id: i32,
+}
+
+impl Handler<RestorePost> for DbActor {
+    type Result = Result<Post, diesel::result::Error>;
+
+    fn handle(&mut self, msg: RestorePost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+
+        let res = diesel::update(
+            posts.filter(
+                id.eq(msg.post_id)
+                    .and(user_id.eq(msg.user_id))
+                    .and(deleted_at.is_not_null()),
+            ),
+        )
+        .set((
+            deleted_at.eq(None::<chrono::NaiveDateTime>),
+            updated_at.eq(now),
+            version.eq(version + 1),
+        ))
+        .get_result(&conn)?;
         Ok(res)
     }
 }
 
+/// Permanently removes posts that have been in the trash for longer than `retention_days`.
+#[derive(Message)]
+#[rtype(result = "Result<usize, diesel::result::Error>")]
+pub struct PurgeTrashedPosts {
+    pub retention_days: i32,
+}
+
+impl Handler<PurgeTrashedPosts> for DbActor {
+    type Result = Result<usize, diesel::result::Error>;
+
+    fn handle(&mut self, msg: PurgeTrashedPosts, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+        use diesel::dsl::IntervalDsl;
+
+        diesel::delete(posts.filter(deleted_at.lt((now - msg.retention_days.days()).nullable())))
+            .execute(&conn)
+    }
+}
+
 #[derive(Message)]
 #[rtype(result = "Result<Post, diesel::result::Error>")]
 pub struct RequestToPublishPost {
@@ -114,12 +560,17 @@ impl Handler<RequestToPublishPost> for DbActor {
     fn handle(&mut self, msg: RequestToPublishPost, _: &mut Self::Context) -> Self::Result {
         let conn = self.get_conn();
         use crate::db::schema::posts::dsl::*;
-        let res = diesel::update(posts.filter(id.eq(msg.post_id).and(user_id.eq(msg.user_id))))
-            .set((
-                published_status.eq(format!("{}", PublishStatus::RequestToAdminForPublish)),
-                updated_at.eq(now),
-            ))
-            .get_result(&conn)?;
+        let res = diesel::update(
+            posts
+                .filter(id.eq(msg.post_id))
+                .filter(editable_by(msg.user_id)),
+        )
+        .set((
+            published_status.eq(format!("{}", PublishStatus::RequestToAdminForPublish)),
+            updated_at.eq(now),
+            version.eq(version + 1),
+        ))
+        .get_result(&conn)?;
         Ok(res)
     }
 }
@@ -136,12 +587,491 @@ impl Handler<PublishPost> for DbActor {
     fn handle(&mut self, msg: PublishPost, _: &mut Self::Context) -> Self::Result {
         let conn = self.get_conn();
         use crate::db::schema::posts::dsl::*;
-        let res = diesel::update(posts.filter(id.eq(msg.post_id)))
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            // an `unpublish_at` that already passed would have the scheduler take the post
+            // right back down
+            diesel::update(
+                posts
+                    .filter(id.eq(msg.post_id).and(deleted_at.is_null()))
+                    .filter(unpublish_at.le(now)),
+            )
+            .set(unpublish_at.eq(None::<chrono::NaiveDateTime>))
+            .execute(&conn)?;
+
+            // posts approved ahead of their `publish_at` wait for the scheduler
+            let scheduled = diesel::update(
+                posts
+                    .filter(id.eq(msg.post_id).and(deleted_at.is_null()))
+                    .filter(publish_at.gt(now)),
+            )
             .set((
-                published_status.eq(format!("{}", PublishStatus::Published)),
+                published_status.eq(format!("{}", PublishStatus::Scheduled)),
                 updated_at.eq(now),
+                version.eq(version + 1),
+            ))
+            .get_result::<Post>(&conn)
+            .optional()?;
+
+            match scheduled {
+                Some(post) => Ok(post),
+                None => diesel::update(posts.filter(id.eq(msg.post_id).and(deleted_at.is_null())))
+                    .set((
+                        published_status.eq(format!("{}", PublishStatus::Published)),
+                        updated_at.eq(now),
+                        version.eq(version + 1),
+                    ))
+                    .get_result(&conn),
+            }
+        })
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Post, diesel::result::Error>")]
+pub struct UnpublishPost {
+    pub post_id: i32,
+}
+
+impl Handler<UnpublishPost> for DbActor {
+    type Result = Result<Post, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UnpublishPost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+        let res = diesel::update(posts.filter(id.eq(msg.post_id).and(deleted_at.is_null())))
+            .set((
+                published_status.eq(format!("{}", PublishStatus::Unpublished)),
+                updated_at.eq(now),
+                version.eq(version + 1),
             ))
             .get_result(&conn)?;
         Ok(res)
     }
 }
+
+#[derive(Message)]
+#[rtype(result = "Result<Post, diesel::result::Error>")]
+pub struct ArchivePost {
+    pub post_id: i32,
+    pub user_id: i32,
+}
+
+impl Handler<ArchivePost> for DbActor {
+    type Result = Result<Post, diesel::result::Error>;
+
+    fn handle(&mut self, msg: ArchivePost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+        let res = diesel::update(
+            posts
+                .filter(id.eq(msg.post_id))
+                .filter(editable_by(msg.user_id)),
+        )
+        .set((
+            published_status.eq(format!("{}", PublishStatus::Archived)),
+            updated_at.eq(now),
+            version.eq(version + 1),
+        ))
+        .get_result(&conn)?;
+        Ok(res)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Post, diesel::result::Error>")]
+pub struct SetPostCommentsEnabled {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub enabled: bool,
+}
+
+impl Handler<SetPostCommentsEnabled> for DbActor {
+    type Result = Result<Post, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetPostCommentsEnabled, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+        diesel::update(
+            posts
+                .filter(id.eq(msg.post_id))
+                .filter(editable_by(msg.user_id)),
+        )
+        .set((
+            comments_enabled.eq(msg.enabled),
+            updated_at.eq(now),
+            version.eq(version + 1),
+        ))
+        .get_result(&conn)
+    }
+}
+
+/// `password` is only kept for `password-protected` posts, the hash is cleared otherwise.
+#[derive(Message)]
+#[rtype(result = "Result<Post, diesel::result::Error>")]
+pub struct SetPostVisibility {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub visibility: Visibility,
+    pub password: Option<String>,
+}
+
+impl Handler<SetPostVisibility> for DbActor {
+    type Result = Result<Post, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetPostVisibility, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+        let password_hash = match (msg.visibility, msg.password) {
+            (Visibility::PasswordProtected, Some(password)) => {
+                Some(hash_password(password.as_bytes()))
+            }
+            _ => None,
+        };
+        diesel::update(
+            posts
+                .filter(id.eq(msg.post_id))
+                .filter(editable_by(msg.user_id)),
+        )
+        .set((
+            visibility.eq(format!("{}", msg.visibility)),
+            access_password_hash.eq(password_hash),
+            updated_at.eq(now),
+            version.eq(version + 1),
+        ))
+        .get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Post, diesel::result::Error>")]
+pub struct SchedulePost {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    pub unpublish_at: Option<chrono::NaiveDateTime>,
+}
+
+impl Handler<SchedulePost> for DbActor {
+    type Result = Result<Post, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SchedulePost, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+        let res = diesel::update(
+            posts
+                .filter(id.eq(msg.post_id))
+                .filter(editable_by(msg.user_id)),
+        )
+        .set((
+            publish_at.eq(msg.publish_at),
+            unpublish_at.eq(msg.unpublish_at),
+            updated_at.eq(now),
+            version.eq(version + 1),
+        ))
+        .get_result(&conn)?;
+        Ok(res)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ScheduleRunSummary {
+    pub published: Vec<i32>,
+    pub unpublished: Vec<i32>,
+}
+
+/// Flips the status of every post whose `publish_at` / `unpublish_at` has passed.
+///
+/// Due rows are locked with `FOR UPDATE SKIP LOCKED`, so several server instances can
+/// run this concurrently without touching the same post twice. Running it again is a
+/// no-op since only posts still in the source status are picked up.
+#[derive(Message)]
+#[rtype(result = "Result<ScheduleRunSummary, diesel::result::Error>")]
+pub struct RunPostSchedules;
+
+impl Handler<RunPostSchedules> for DbActor {
+    type Result = Result<ScheduleRunSummary, diesel::result::Error>;
+
+    fn handle(&mut self, _: RunPostSchedules, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+
+        let scheduled_value = format!("{}", PublishStatus::Scheduled);
+        let published_value = format!("{}", PublishStatus::Published);
+        let unpublished_value = format!("{}", PublishStatus::Unpublished);
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let due_for_publish = posts
+                .select(id)
+                .filter(
+                    published_status
+                        .eq(&scheduled_value)
+                        .and(deleted_at.is_null())
+                        .and(publish_at.is_null().or(publish_at.le(now))),
+                )
+                .for_update()
+                .skip_locked()
+                .load::<i32>(&conn)?;
+
+            let published = diesel::update(posts.filter(id.eq_any(&due_for_publish)))
+                .set((
+                    published_status.eq(&published_value),
+                    updated_at.eq(now),
+                    version.eq(version + 1),
+                ))
+                .returning(id)
+                .get_results::<i32>(&conn)?;
+
+            let due_for_unpublish = posts
+                .select(id)
+                .filter(
+                    published_status
+                        .eq(&published_value)
+                        .and(deleted_at.is_null())
+                        .and(unpublish_at.le(now)),
+                )
+                .for_update()
+                .skip_locked()
+                .load::<i32>(&conn)?;
+
+            let unpublished = diesel::update(posts.filter(id.eq_any(&due_for_unpublish)))
+                .set((
+                    published_status.eq(&unpublished_value),
+                    updated_at.eq(now),
+                    version.eq(version + 1),
+                ))
+                .returning(id)
+                .get_results::<i32>(&conn)?;
+
+            Ok(ScheduleRunSummary {
+                published: published,
+                unpublished: unpublished,
+            })
+        })
+    }
+}
+
+/// Applies an edit to the content blocks of a post and refreshes their rendered html / text.
+#[derive(Message)]
+#[rtype(result = "Result<VersionedUpdate, BlockEditError>")]
+pub struct EditPostBlocks {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub edit: BlockEdit,
+    pub expected_version: i32,
+}
+
+impl Handler<EditPostBlocks> for DbActor {
+    type Result = Result<VersionedUpdate, BlockEditError>;
+
+    fn handle(&mut self, msg: EditPostBlocks, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::posts::dsl::*;
+
+        conn.transaction::<_, BlockEditError, _>(|| {
+            // lock the row so that concurrent block edits dont lose each other
+            let post = lock_editable_post(&conn, msg.post_id, msg.user_id)?;
+            if post.version != msg.expected_version {
+                return Ok(VersionedUpdate::Conflict(post));
+            }
+            let current_blocks = serde_json::from_value::<Vec<Block>>(post.content_blocks)
+                .map_err(|err| BlockEditError::InvalidBlock(err.to_string()))?;
+
+            let new_blocks = apply_edit(current_blocks, msg.edit)?;
+            let new_blocks_json = serde_json::to_value(&new_blocks)
+                .map_err(|err| BlockEditError::InvalidBlock(err.to_string()))?;
+
+            let res = diesel::update(posts.filter(id.eq(msg.post_id)))
+                .set((
+                    content_blocks.eq(new_blocks_json),
+                    content_html.eq(render_blocks_html(&new_blocks)),
+                    content_text.eq(render_blocks_text(&new_blocks)),
+                    updated_at.eq(now),
+                    version.eq(version + 1),
+                ))
+                .get_result(&conn)?;
+            Ok(VersionedUpdate::Updated(res))
+        })
+    }
+}
+
+/// Action applied to every post of a `BulkPostAction`.
+pub enum BulkAction {
+    Delete,
+    Archive,
+    RequestPublish,
+    Publish,
+    AddTag(String),
+    RemoveTag(String),
+}
+
+/// What happened to a single post of a `BulkPostAction`.
+#[derive(Display, Clone, Copy, PartialEq)]
+pub enum BulkOutcome {
+    #[display(fmt = "done")]
+    Done,
+
+    #[display(fmt = "not_found")]
+    NotFound,
+
+    #[display(fmt = "not_permitted")]
+    NotPermitted,
+}
+
+/// Applies `action` to each of `post_ids` in one transaction, checking the permissions of
+/// the user on every post: only owners can delete, only admins can publish and anything
+/// else needs the right to edit.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<(i32, BulkOutcome)>, diesel::result::Error>")]
+pub struct BulkPostAction {
+    pub post_ids: Vec<i32>,
+    pub user_id: i32,
+    pub is_admin: bool,
+    pub action: BulkAction,
+}
+
+impl Handler<BulkPostAction> for DbActor {
+    type Result = Result<Vec<(i32, BulkOutcome)>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: BulkPostAction, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{post_collaborators, post_tags, posts, tags};
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let found = posts::table
+                .filter(posts::id.eq_any(&msg.post_ids))
+                .filter(posts::deleted_at.is_null())
+                .select((posts::id, posts::user_id))
+                .for_update()
+                .load::<(i32, i32)>(&conn)?
+                .into_iter()
+                .collect::<HashMap<i32, i32>>();
+            let roles = post_collaborators::table
+                .filter(post_collaborators::post_id.eq_any(&msg.post_ids))
+                .filter(post_collaborators::user_id.eq(msg.user_id))
+                .select((post_collaborators::post_id, post_collaborators::role))
+                .load::<(i32, String)>(&conn)?
+                .into_iter()
+                .filter_map(|(post_id, role)| {
+                    role.parse::<CollaboratorRole>()
+                        .ok()
+                        .map(|role| (post_id, role))
+                })
+                .collect::<HashMap<i32, CollaboratorRole>>();
+
+            let outcomes = msg
+                .post_ids
+                .iter()
+                .map(|post_id| {
+                    let owner_id = match found.get(post_id) {
+                        Some(owner_id) => *owner_id,
+                        None => return (*post_id, BulkOutcome::NotFound),
+                    };
+                    let is_owner = owner_id == msg.user_id;
+                    let permitted = match msg.action {
+                        BulkAction::Delete => is_owner,
+                        BulkAction::Publish => msg.is_admin,
+                        _ => is_owner || roles.get(post_id).is_some_and(|role| role.can_edit()),
+                    };
+                    if permitted {
+                        (*post_id, BulkOutcome::Done)
+                    } else {
+                        (*post_id, BulkOutcome::NotPermitted)
+                    }
+                })
+                .collect::<Vec<(i32, BulkOutcome)>>();
+            let mut permitted_ids = outcomes
+                .iter()
+                .filter(|(_, outcome)| *outcome == BulkOutcome::Done)
+                .map(|(post_id, _)| *post_id)
+                .collect::<Vec<i32>>();
+            permitted_ids.sort();
+            permitted_ids.dedup();
+            if permitted_ids.is_empty() {
+                return Ok(outcomes);
+            }
+            let permitted = || {
+                posts::table
+                    .filter(posts::id.eq_any(&permitted_ids))
+                    .filter(posts::deleted_at.is_null())
+            };
+
+            match &msg.action {
+                BulkAction::Delete => {
+                    diesel::update(permitted())
+                        .set((
+                            posts::deleted_at.eq(now.nullable()),
+                            posts::updated_at.eq(now),
+                            posts::version.eq(posts::version + 1),
+                        ))
+                        .execute(&conn)?;
+                }
+                BulkAction::Archive | BulkAction::RequestPublish => {
+                    let new_status = match msg.action {
+                        BulkAction::Archive => PublishStatus::Archived,
+                        _ => PublishStatus::RequestToAdminForPublish,
+                    };
+                    diesel::update(permitted())
+                        .set((
+                            posts::published_status.eq(format!("{}", new_status)),
+                            posts::updated_at.eq(now),
+                            posts::version.eq(posts::version + 1),
+                        ))
+                        .execute(&conn)?;
+                }
+                BulkAction::Publish => {
+                    // same as `PublishPost`, a passed `unpublish_at` is dropped
+                    diesel::update(permitted().filter(posts::unpublish_at.le(now)))
+                        .set(posts::unpublish_at.eq(None::<chrono::NaiveDateTime>))
+                        .execute(&conn)?;
+                    // posts approved ahead of their `publish_at` wait for the scheduler
+                    diesel::update(permitted().filter(posts::publish_at.gt(now)))
+                        .set((
+                            posts::published_status.eq(format!("{}", PublishStatus::Scheduled)),
+                            posts::updated_at.eq(now),
+                            posts::version.eq(posts::version + 1),
+                        ))
+                        .execute(&conn)?;
+                    diesel::update(permitted().filter(
+                        posts::publish_at.is_null().or(posts::publish_at.le(now)),
+                    ))
+                    .set((
+                        posts::published_status.eq(format!("{}", PublishStatus::Published)),
+                        posts::updated_at.eq(now),
+                        posts::version.eq(posts::version + 1),
+                    ))
+                    .execute(&conn)?;
+                }
+                BulkAction::AddTag(tag_name) => {
+                    let tag = get_or_create_tag(&conn, tag_name)?;
+                    let new_post_tags = permitted_ids
+                        .iter()
+                        .map(|post_id| PostTag {
+                            post_id: *post_id,
+                            tag_id: tag.id,
+                        })
+                        .collect::<Vec<PostTag>>();
+                    diesel::insert_into(post_tags::table)
+                        .values(&new_post_tags)
+                        .on_conflict_do_nothing()
+                        .execute(&conn)?;
+                }
+                BulkAction::RemoveTag(tag_name) => {
+                    let tag = tags::table
+                        .filter(tags::slug.eq(slugify(tag_name)))
+                        .get_result::<Tag>(&conn)
+                        .optional()?;
+                    // nothing to remove when nobody uses the tag yet
+                    if let Some(tag) = tag {
+                        diesel::delete(
+                            post_tags::table
+                                .filter(post_tags::tag_id.eq(tag.id))
+                                .filter(post_tags::post_id.eq_any(&permitted_ids)),
+                        )
+                        .execute(&conn)?;
+                    }
+                }
+            }
+            Ok(outcomes)
+        })
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::previews::{NewPostPreview, PostPreview},
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// Records a preview link of the current version of a post.
+#[derive(Message)]
+#[rtype(result = "Result<PostPreview, diesel::result::Error>")]
+pub struct AddPostPreview {
+    pub post_id: i32,
+    pub post_version: i32,
+    pub created_by: i32,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+impl Handler<AddPostPreview> for DbActor {
+    type Result = Result<PostPreview, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddPostPreview, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_previews::dsl::*;
+        diesel::insert_into(post_previews)
+            .values(&NewPostPreview {
+                post_id: msg.post_id,
+                post_version: msg.post_version,
+                created_by: msg.created_by,
+                expires_at: msg.expires_at,
+            })
+            .get_result(&conn)
+    }
+}
+
+/// Stops a preview link from working, the token itself stays valid until it expires.
+#[derive(Message)]
+#[rtype(result = "Result<PostPreview, diesel::result::Error>")]
+pub struct RevokePostPreview {
+    pub preview_id: i32,
+}
+
+impl Handler<RevokePostPreview> for DbActor {
+    type Result = Result<PostPreview, diesel::result::Error>;
+
+    fn handle(&mut self, msg: RevokePostPreview, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_previews::dsl::*;
+        diesel::update(post_previews.filter(id.eq(msg.preview_id).and(revoked_at.is_null())))
+            .set(revoked_at.eq(now.nullable()))
+            .get_result(&conn)
+    }
+}
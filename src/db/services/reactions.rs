@@ -0,0 +1,78 @@
+use crate::{db::actor::DbActor, db::models::reactions::NewPostReaction};
+use actix::{Handler, Message};
+use diesel::prelude::*;
+
+fn add_to_count(counts: &mut serde_json::Map<String, serde_json::Value>, emoji: &str, delta: i64) {
+    let count = counts
+        .get(emoji)
+        .and_then(|count| count.as_i64())
+        .unwrap_or(0)
+        + delta;
+    if count > 0 {
+        counts.insert(emoji.to_string(), serde_json::Value::from(count));
+    } else {
+        counts.remove(emoji);
+    }
+}
+
+/// Sets (or with `None` removes) the reaction of a user to a post and
+/// returns the updated `reaction_counts` of the post.
+#[derive(Message)]
+#[rtype(result = "Result<serde_json::Value, diesel::result::Error>")]
+pub struct SetPostReaction {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub emoji: Option<String>,
+}
+
+impl Handler<SetPostReaction> for DbActor {
+    type Result = Result<serde_json::Value, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetPostReaction, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{post_reactions, posts};
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            // the post row lock serializes concurrent reactions to the same post
+            let reaction_counts = posts::table
+                .filter(posts::id.eq(msg.post_id))
+                .select(posts::reaction_counts)
+                .for_update()
+                .get_result::<serde_json::Value>(&conn)?;
+            let mut counts = match reaction_counts {
+                serde_json::Value::Object(counts) => counts,
+                _ => serde_json::Map::new(),
+            };
+
+            let previous_emoji = diesel::delete(
+                post_reactions::table.filter(
+                    post_reactions::post_id
+                        .eq(msg.post_id)
+                        .and(post_reactions::user_id.eq(msg.user_id)),
+                ),
+            )
+            .returning(post_reactions::emoji)
+            .get_result::<String>(&conn)
+            .optional()?;
+            if let Some(previous_emoji) = previous_emoji {
+                add_to_count(&mut counts, &previous_emoji, -1);
+            }
+
+            if let Some(new_emoji) = msg.emoji {
+                diesel::insert_into(post_reactions::table)
+                    .values(&NewPostReaction {
+                        post_id: msg.post_id,
+                        user_id: msg.user_id,
+                        emoji: &new_emoji,
+                    })
+                    .execute(&conn)?;
+                add_to_count(&mut counts, &new_emoji, 1);
+            }
+
+            let counts = serde_json::Value::Object(counts);
+            diesel::update(posts::table.filter(posts::id.eq(msg.post_id)))
+                .set(posts::reaction_counts.eq(&counts))
+                .execute(&conn)?;
+            Ok(counts)
+        })
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{db::actor::DbActor, db::models::stats::NewPostStatsDaily};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::pg::upsert::excluded;
+use diesel::prelude::*;
+
+/// Stores the unique views of `day`. The counts are totals for the day, so
+/// running this again with newer counts simply overwrites the old ones.
+#[derive(Message)]
+#[rtype(result = "Result<usize, diesel::result::Error>")]
+pub struct UpsertPostStatsDaily {
+    pub day: chrono::NaiveDate,
+    /// `(post_id, views)`
+    pub views_by_post: Vec<(i32, i64)>,
+}
+
+impl Handler<UpsertPostStatsDaily> for DbActor {
+    type Result = Result<usize, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpsertPostStatsDaily, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{post_stats_daily, posts};
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            // posts can be purged while their views are still in redis
+            let post_ids = msg
+                .views_by_post
+                .iter()
+                .map(|(post_id, _)| *post_id)
+                .collect::<Vec<i32>>();
+            let existing_post_ids = posts::table
+                .filter(posts::id.eq_any(post_ids))
+                .select(posts::id)
+                .load::<i32>(&conn)?;
+            let new_stats = msg
+                .views_by_post
+                .iter()
+                .filter(|(post_id, _)| existing_post_ids.contains(post_id))
+                .map(|(post_id, views)| NewPostStatsDaily {
+                    post_id: *post_id,
+                    day: msg.day,
+                    views: *views as i32,
+                })
+                .collect::<Vec<NewPostStatsDaily>>();
+            diesel::insert_into(post_stats_daily::table)
+                .values(&new_stats)
+                .on_conflict((post_stats_daily::post_id, post_stats_daily::day))
+                .do_update()
+                .set((
+                    post_stats_daily::views.eq(excluded(post_stats_daily::views)),
+                    post_stats_daily::updated_at.eq(now),
+                ))
+                .execute(&conn)
+        })
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{
+    db::actor::DbActor,
+    db::models::tags::{NewTag, PostTag, Tag},
+    slugify::slugify,
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// Returns the tag with the same slug as `tag_name`, creating it if there is none yet.
+pub fn get_or_create_tag<'a>(
+    conn: &PgConnection,
+    tag_name: &'a str,
+) -> Result<Tag, diesel::result::Error> {
+    use crate::db::schema::tags::dsl::*;
+    let tag_slug = slugify(tag_name);
+    diesel::insert_into(tags)
+        .values(&NewTag {
+            name: tag_name.trim(),
+            slug: &tag_slug,
+        })
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+    tags.filter(slug.eq(&tag_slug)).get_result::<Tag>(conn)
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Tag, diesel::result::Error>")]
+pub struct AddTag {
+    pub name: String,
+}
+
+impl Handler<AddTag> for DbActor {
+    type Result = Result<Tag, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddTag, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        get_or_create_tag(&conn, &msg.name)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Tag, diesel::result::Error>")]
+pub struct UpdateTag {
+    pub tag_id: i32,
+    pub name: String,
+}
+
+impl Handler<UpdateTag> for DbActor {
+    type Result = Result<Tag, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdateTag, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::tags::dsl::*;
+        diesel::update(tags.filter(id.eq(msg.tag_id)))
+            .set((name.eq(msg.name.trim()), slug.eq(slugify(&msg.name))))
+            .get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Tag, diesel::result::Error>")]
+pub struct DeleteTag {
+    pub tag_id: i32,
+}
+
+impl Handler<DeleteTag> for DbActor {
+    type Result = Result<Tag, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeleteTag, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::tags::dsl::*;
+        diesel::delete(tags.filter(id.eq(msg.tag_id))).get_result(&conn)
+    }
+}
+
+/// Replaces the tags of a post, tags that dont exist yet are created. Bumps the `updated_at` of
+/// the post so that its related posts get recomputed.
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Tag>, diesel::result::Error>")]
+pub struct SetPostTags {
+    pub post_id: i32,
+    pub tag_names: Vec<String>,
+}
+
+impl Handler<SetPostTags> for DbActor {
+    type Result = Result<Vec<Tag>, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetPostTags, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::{post_tags, posts};
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let mut new_tags: Vec<Tag> = vec![];
+            for tag_name in msg.tag_names.iter() {
+                let tag = get_or_create_tag(&conn, tag_name)?;
+                // "Rust" and "rust" end up as the same tag
+                if !new_tags.iter().any(|new_tag| new_tag.id == tag.id) {
+                    new_tags.push(tag);
+                }
+            }
+            diesel::delete(post_tags::table.filter(post_tags::post_id.eq(msg.post_id)))
+                .execute(&conn)?;
+            let new_post_tags = new_tags
+                .iter()
+                .map(|tag| PostTag {
+                    post_id: msg.post_id,
+                    tag_id: tag.id,
+                })
+                .collect::<Vec<PostTag>>();
+            diesel::insert_into(post_tags::table)
+                .values(&new_post_tags)
+                .on_conflict_do_nothing()
+                .execute(&conn)?;
+            diesel::update(posts::table.filter(posts::id.eq(msg.post_id)))
+                .set(posts::updated_at.eq(now))
+                .execute(&conn)?;
+            Ok(new_tags)
+        })
+    }
+}
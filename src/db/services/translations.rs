@@ -0,0 +1,151 @@
+use crate::{
+    body_renderer::render_body,
+    db::actor::DbActor,
+    db::models::posts::{BodyFormat, PublishStatus},
+    db::models::translations::{NewPostTranslation, PostTranslation, PostTranslationChangeset},
+    db::services::posts::unique_slug,
+};
+use actix::{Handler, Message};
+use diesel::expression::dsl::now;
+use diesel::prelude::*;
+
+/// Adds a translation of a post, it starts out unpublished.
+#[derive(Message)]
+#[rtype(result = "Result<PostTranslation, diesel::result::Error>")]
+pub struct AddPostTranslation {
+    pub post_id: i32,
+    pub locale: String,
+    pub subject: String,
+    pub body: String,
+    pub body_format: BodyFormat,
+    pub slug: Option<String>,
+    pub created_by: i32,
+}
+
+impl Handler<AddPostTranslation> for DbActor {
+    type Result = Result<PostTranslation, diesel::result::Error>;
+
+    fn handle(&mut self, msg: AddPostTranslation, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let new_slug = unique_slug(&conn, msg.slug.as_ref().unwrap_or(&msg.subject), None)?;
+            diesel::insert_into(post_translations)
+                .values(&NewPostTranslation {
+                    post_id: msg.post_id,
+                    locale: &msg.locale,
+                    post_subject: &msg.subject,
+                    post_body: &msg.body,
+                    body_format: &format!("{}", msg.body_format),
+                    rendered_body: &render_body(&msg.body, &msg.body_format),
+                    slug: &new_slug,
+                    published_status: &format!("{}", PublishStatus::Unpublished),
+                    created_by: msg.created_by,
+                })
+                .get_result(&conn)
+        })
+    }
+}
+
+/// Changes the given fields of a translation, `None` fields are left as they are.
+#[derive(Message)]
+#[rtype(result = "Result<PostTranslation, diesel::result::Error>")]
+pub struct UpdatePostTranslation {
+    pub translation_id: i32,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+    pub body_format: Option<BodyFormat>,
+    pub slug: Option<String>,
+}
+
+impl Handler<UpdatePostTranslation> for DbActor {
+    type Result = Result<PostTranslation, diesel::result::Error>;
+
+    fn handle(&mut self, msg: UpdatePostTranslation, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let translation = post_translations
+                .filter(id.eq(msg.translation_id))
+                .for_update()
+                .get_result::<PostTranslation>(&conn)?;
+
+            let rendered = if msg.body.is_some() || msg.body_format.is_some() {
+                let new_body_format = msg.body_format.unwrap_or(
+                    translation
+                        .body_format
+                        .parse::<BodyFormat>()
+                        .unwrap_or(BodyFormat::Plain),
+                );
+                Some(render_body(
+                    msg.body.as_ref().unwrap_or(&translation.post_body),
+                    &new_body_format,
+                ))
+            } else {
+                None
+            };
+            let new_slug = match msg.slug {
+                Some(new_slug) if new_slug != translation.slug => {
+                    Some(unique_slug(&conn, &new_slug, None)?)
+                }
+                _ => None,
+            };
+
+            diesel::update(post_translations.filter(id.eq(translation.id)))
+                .set((
+                    &PostTranslationChangeset {
+                        post_subject: msg.subject,
+                        post_body: msg.body,
+                        body_format: msg
+                            .body_format
+                            .map(|new_body_format| format!("{}", new_body_format)),
+                        rendered_body: rendered,
+                        slug: new_slug,
+                    },
+                    updated_at.eq(now),
+                ))
+                .get_result(&conn)
+        })
+    }
+}
+
+/// Moves a translation through review, independent of the post it translates.
+#[derive(Message)]
+#[rtype(result = "Result<PostTranslation, diesel::result::Error>")]
+pub struct SetPostTranslationStatus {
+    pub translation_id: i32,
+    pub status: PublishStatus,
+}
+
+impl Handler<SetPostTranslationStatus> for DbActor {
+    type Result = Result<PostTranslation, diesel::result::Error>;
+
+    fn handle(&mut self, msg: SetPostTranslationStatus, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+        diesel::update(post_translations.filter(id.eq(msg.translation_id)))
+            .set((
+                published_status.eq(format!("{}", msg.status)),
+                updated_at.eq(now),
+            ))
+            .get_result(&conn)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<PostTranslation, diesel::result::Error>")]
+pub struct DeletePostTranslation {
+    pub translation_id: i32,
+}
+
+impl Handler<DeletePostTranslation> for DbActor {
+    type Result = Result<PostTranslation, diesel::result::Error>;
+
+    fn handle(&mut self, msg: DeletePostTranslation, _: &mut Self::Context) -> Self::Result {
+        let conn = self.get_conn();
+        use crate::db::schema::post_translations::dsl::*;
+        diesel::delete(post_translations.filter(id.eq(msg.translation_id))).get_result(&conn)
+    }
+}
@@ -5,6 +5,7 @@ use actix_web::{
 };
 use derive_more::{Display, Error};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Display, Error)]
 pub enum MyError {
@@ -33,17 +34,219 @@ pub enum MyError {
 
     #[display(fmt = "You dont own this post")]
     YouDontOwnThisPost,
+
+    #[display(fmt = "`unpublish_at` must be after `publish_at`!")]
+    InvalidSchedule,
+
+    #[display(fmt = "Post Not Found!")]
+    PostNotFound,
+
+    #[display(fmt = "Category Not Found!")]
+    CategoryNotFound,
+
+    #[display(fmt = "Category Already Exists!")]
+    CategoryAlreadyExists,
+
+    #[display(fmt = "A category cannot be moved under itself!")]
+    CategoryCycle,
+
+    #[display(fmt = "Tag Not Found!")]
+    TagNotFound,
+
+    #[display(fmt = "Tag Already Exists!")]
+    TagAlreadyExists,
+
+    #[display(fmt = "Name must contain at least one letter or digit!")]
+    InvalidName,
+
+    #[display(fmt = "Body format must be one of `plain`, `markdown` or `html`!")]
+    InvalidBodyFormat,
+
+    #[display(fmt = "Block `{}` Not Found!", _0)]
+    BlockNotFound(#[error(not(source))] String),
+
+    #[display(fmt = "Invalid block: {}", _0)]
+    InvalidBlock(#[error(not(source))] String),
+
+    #[display(fmt = "Media Not Found!")]
+    MediaNotFound,
+
+    #[display(fmt = "You dont own this media")]
+    YouDontOwnThisMedia,
+
+    #[display(fmt = "File is larger than {} bytes!", _0)]
+    MediaTooLarge(#[error(not(source))] usize),
+
+    #[display(fmt = "Unsupported media type!")]
+    UnsupportedMediaType,
+
+    #[display(fmt = "Invalid upload: {}", _0)]
+    InvalidUpload(#[error(not(source))] String),
+
+    #[display(fmt = "Storage Error!")]
+    StorageError,
+
+    #[display(fmt = "Comment Not Found!")]
+    CommentNotFound,
+
+    #[display(fmt = "You dont own this comment")]
+    YouDontOwnThisComment,
+
+    #[display(fmt = "Comments are closed for this post!")]
+    CommentsClosed,
+
+    #[display(fmt = "Invalid comment: {}", _0)]
+    InvalidComment(#[error(not(source))] String),
+
+    #[display(fmt = "Reaction is not one of the allowed emojis!")]
+    InvalidReaction,
+
+    #[display(fmt = "Collaborator Not Found!")]
+    CollaboratorNotFound,
+
+    #[display(fmt = "Role must be one of `co-author`, `editor` or `viewer`!")]
+    InvalidCollaboratorRole,
+
+    #[display(fmt = "The owner of a post cannot be its collaborator!")]
+    OwnerCannotCollaborate,
+
+    #[display(fmt = "Send the version being edited as `If-Match` or `expected_version`!")]
+    PreconditionRequired,
+
+    /// Holds the current state of the post
+    #[display(fmt = "Post was changed by someone else!")]
+    VersionConflict(#[error(not(source))] serde_json::Value),
+
+    /// Holds what is wrong with each invalid field
+    #[display(fmt = "Some fields are invalid!")]
+    InvalidFields(#[error(not(source))] BTreeMap<String, String>),
+
+    #[display(fmt = "At most {} posts can be changed at once!", _0)]
+    TooManyPosts(#[error(not(source))] usize),
+
+    #[display(fmt = "Autosave Not Found!")]
+    AutosaveNotFound,
+
+    /// Holds the current autosave, `null` if it was discarded
+    #[display(fmt = "Autosave was changed from another tab!")]
+    AutosaveConflict(#[error(not(source))] serde_json::Value),
+
+    #[display(fmt = "Preview link is invalid, expired or revoked!")]
+    PreviewNotFound,
+
+    #[display(fmt = "Post was changed since the preview link was created!")]
+    PreviewOutdated,
+
+    #[display(
+        fmt = "Visibility must be one of `public`, `unlisted`, `members-only` or `password-protected`!"
+    )]
+    InvalidVisibility,
+
+    #[display(fmt = "A password-protected post needs a password!")]
+    PasswordRequired,
+
+    #[display(fmt = "Sign in to read this post!")]
+    MembersOnly,
+
+    #[display(fmt = "This post is password-protected, unlock it first!")]
+    PostLocked,
+
+    #[display(fmt = "Translation Not Found!")]
+    TranslationNotFound,
+
+    #[display(fmt = "The post already has a translation into this locale!")]
+    TranslationAlreadyExists,
+
+    #[display(fmt = "Locale is not supported!")]
+    UnsupportedLocale,
+
+    #[display(fmt = "Content Type Not Found!")]
+    ContentTypeNotFound,
+
+    #[display(fmt = "Content Type Already Exists!")]
+    ContentTypeAlreadyExists,
+
+    #[display(fmt = "Content type still has entries!")]
+    ContentTypeInUse,
+
+    #[display(fmt = "Invalid content type: {}", _0)]
+    InvalidContentType(#[error(not(source))] String),
+
+    #[display(fmt = "Post is not an entry of a content type!")]
+    NotAnEntry,
+
+    #[display(fmt = "Page Not Found!")]
+    PageNotFound,
+
+    #[display(fmt = "Page Already Exists!")]
+    PageAlreadyExists,
+
+    #[display(fmt = "A page cannot be moved below itself!")]
+    PageCycle,
+
+    #[display(fmt = "Menu Not Found!")]
+    MenuNotFound,
+
+    #[display(fmt = "Menu Already Exists!")]
+    MenuAlreadyExists,
+
+    #[display(fmt = "Menu Item Not Found!")]
+    MenuItemNotFound,
+
+    #[display(fmt = "A menu item cannot be moved below itself!")]
+    MenuItemCycle,
+
+    #[display(fmt = "Invalid menu item: {}", _0)]
+    InvalidMenuItem(#[error(not(source))] String),
+
+    #[display(fmt = "Collection Not Found!")]
+    CollectionNotFound,
+
+    #[display(fmt = "Collection Already Exists!")]
+    CollectionAlreadyExists,
+
+    #[display(fmt = "Collection kind must be either series or collection!")]
+    InvalidCollectionKind,
+
+    #[display(fmt = "You Don't Own This Collection!")]
+    YouDontOwnThisCollection,
+
+    #[display(fmt = "This post already is in another series!")]
+    AlreadyInSeries,
+
+    #[display(fmt = "The new order must hold every post of the collection once!")]
+    InvalidCollectionOrder,
+
+    #[display(fmt = "Feed Not Found!")]
+    FeedNotFound,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ErrorResponse {
     error: String,
+    /// Current state of the resource, on conflicts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current: Option<serde_json::Value>,
+    /// What is wrong with each field, on validation errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<BTreeMap<String, String>>,
 }
 
 impl ResponseError for MyError {
     fn error_response(&self) -> HttpResponse {
+        let current = match self {
+            MyError::VersionConflict(current) => Some(current.clone()),
+            MyError::AutosaveConflict(current) => Some(current.clone()),
+            _ => None,
+        };
+        let fields = match self {
+            MyError::InvalidFields(fields) => Some(fields.clone()),
+            _ => None,
+        };
         let (status_code, json) = match serde_json::to_string(&ErrorResponse {
             error: self.to_string(),
+            current: current,
+            fields: fields,
         }) {
             Ok(json) => (self.status_code(), json),
             Err(_) => (
@@ -69,6 +272,66 @@ impl ResponseError for MyError {
             IncorrectPassword => StatusCode::BAD_REQUEST,
             NotAdmin => StatusCode::UNAUTHORIZED,
             YouDontOwnThisPost => StatusCode::UNAUTHORIZED,
+            InvalidSchedule => StatusCode::BAD_REQUEST,
+            PostNotFound => StatusCode::NOT_FOUND,
+            CategoryNotFound => StatusCode::NOT_FOUND,
+            CategoryAlreadyExists => StatusCode::BAD_REQUEST,
+            CategoryCycle => StatusCode::BAD_REQUEST,
+            TagNotFound => StatusCode::NOT_FOUND,
+            TagAlreadyExists => StatusCode::BAD_REQUEST,
+            InvalidName => StatusCode::BAD_REQUEST,
+            InvalidBodyFormat => StatusCode::BAD_REQUEST,
+            BlockNotFound(_) => StatusCode::NOT_FOUND,
+            InvalidBlock(_) => StatusCode::BAD_REQUEST,
+            MediaNotFound => StatusCode::NOT_FOUND,
+            YouDontOwnThisMedia => StatusCode::UNAUTHORIZED,
+            MediaTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            InvalidUpload(_) => StatusCode::BAD_REQUEST,
+            StorageError => StatusCode::INTERNAL_SERVER_ERROR,
+            CommentNotFound => StatusCode::NOT_FOUND,
+            YouDontOwnThisComment => StatusCode::UNAUTHORIZED,
+            CommentsClosed => StatusCode::BAD_REQUEST,
+            InvalidComment(_) => StatusCode::BAD_REQUEST,
+            InvalidReaction => StatusCode::BAD_REQUEST,
+            CollaboratorNotFound => StatusCode::NOT_FOUND,
+            InvalidCollaboratorRole => StatusCode::BAD_REQUEST,
+            OwnerCannotCollaborate => StatusCode::BAD_REQUEST,
+            PreconditionRequired => StatusCode::PRECONDITION_REQUIRED,
+            VersionConflict(_) => StatusCode::CONFLICT,
+            InvalidFields(_) => StatusCode::BAD_REQUEST,
+            TooManyPosts(_) => StatusCode::BAD_REQUEST,
+            AutosaveNotFound => StatusCode::NOT_FOUND,
+            AutosaveConflict(_) => StatusCode::CONFLICT,
+            PreviewNotFound => StatusCode::NOT_FOUND,
+            PreviewOutdated => StatusCode::GONE,
+            InvalidVisibility => StatusCode::BAD_REQUEST,
+            PasswordRequired => StatusCode::BAD_REQUEST,
+            MembersOnly => StatusCode::UNAUTHORIZED,
+            PostLocked => StatusCode::FORBIDDEN,
+            TranslationNotFound => StatusCode::NOT_FOUND,
+            TranslationAlreadyExists => StatusCode::BAD_REQUEST,
+            UnsupportedLocale => StatusCode::BAD_REQUEST,
+            ContentTypeNotFound => StatusCode::NOT_FOUND,
+            ContentTypeAlreadyExists => StatusCode::BAD_REQUEST,
+            ContentTypeInUse => StatusCode::BAD_REQUEST,
+            InvalidContentType(_) => StatusCode::BAD_REQUEST,
+            NotAnEntry => StatusCode::BAD_REQUEST,
+            PageNotFound => StatusCode::NOT_FOUND,
+            PageAlreadyExists => StatusCode::BAD_REQUEST,
+            PageCycle => StatusCode::BAD_REQUEST,
+            MenuNotFound => StatusCode::NOT_FOUND,
+            MenuAlreadyExists => StatusCode::BAD_REQUEST,
+            MenuItemNotFound => StatusCode::NOT_FOUND,
+            MenuItemCycle => StatusCode::BAD_REQUEST,
+            InvalidMenuItem(_) => StatusCode::BAD_REQUEST,
+            CollectionNotFound => StatusCode::NOT_FOUND,
+            CollectionAlreadyExists => StatusCode::BAD_REQUEST,
+            InvalidCollectionKind => StatusCode::BAD_REQUEST,
+            YouDontOwnThisCollection => StatusCode::UNAUTHORIZED,
+            AlreadyInSeries => StatusCode::BAD_REQUEST,
+            InvalidCollectionOrder => StatusCode::BAD_REQUEST,
+            FeedNotFound => StatusCode::NOT_FOUND,
         }
     }
 }
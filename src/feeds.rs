@@ -0,0 +1,201 @@
+use crate::body_renderer::escape_html;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::json;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+/// Parses the file name a feed is served under.
+impl std::str::FromStr for FeedFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rss.xml" => Ok(FeedFormat::Rss),
+            "atom.xml" => Ok(FeedFormat::Atom),
+            "feed.json" => Ok(FeedFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct FeedEntry {
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    /// Sanitized html
+    pub content_html: String,
+    pub published_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+pub struct Feed {
+    pub title: String,
+    /// Page of the site the feed is about
+    pub home_url: String,
+    /// Where the feed itself is served
+    pub feed_url: String,
+    /// Newest first
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    /// Last time any entry was updated, `None` for an empty feed.
+    pub fn updated_at(&self) -> Option<NaiveDateTime> {
+        self.entries.iter().map(|entry| entry.updated_at).max()
+    }
+
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Rss => self.render_rss(),
+            FeedFormat::Atom => self.render_atom(),
+            FeedFormat::Json => self.render_json(),
+        }
+    }
+
+    /// RSS 2.0
+    fn render_rss(&self) -> String {
+        let mut rss = String::from(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" \
+             xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n<channel>\n",
+        );
+        rss.push_str(&format!("<title>{}</title>\n", escape_html(&self.title)));
+        rss.push_str(&format!("<link>{}</link>\n", escape_html(&self.home_url)));
+        rss.push_str(&format!(
+            "<description>{}</description>\n",
+            escape_html(&self.title)
+        ));
+        rss.push_str(&format!(
+            "<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\n",
+            escape_html(&self.feed_url)
+        ));
+        if let Some(updated_at) = self.updated_at() {
+            rss.push_str(&format!(
+                "<lastBuildDate>{}</lastBuildDate>\n",
+                utc(updated_at).to_rfc2822()
+            ));
+        }
+        for entry in self.entries.iter() {
+            rss.push_str("<item>\n");
+            rss.push_str(&format!("<title>{}</title>\n", escape_html(&entry.title)));
+            rss.push_str(&format!("<link>{}</link>\n", escape_html(&entry.url)));
+            rss.push_str(&format!(
+                "<guid isPermaLink=\"true\">{}</guid>\n",
+                escape_html(&entry.url)
+            ));
+            // `author` would need an email address
+            rss.push_str(&format!(
+                "<dc:creator>{}</dc:creator>\n",
+                escape_html(&entry.author)
+            ));
+            rss.push_str(&format!(
+                "<pubDate>{}</pubDate>\n",
+                utc(entry.published_at).to_rfc2822()
+            ));
+            rss.push_str(&format!(
+                "<description>{}</description>\n",
+                escape_html(&entry.content_html)
+            ));
+            rss.push_str("</item>\n");
+        }
+        rss.push_str("</channel>\n</rss>\n");
+        rss
+    }
+
+    fn render_atom(&self) -> String {
+        let mut atom = String::from(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <feed xmlns=\"http://www.w3.org/2005/Atom\">\n",
+        );
+        atom.push_str(&format!("<title>{}</title>\n", escape_html(&self.title)));
+        atom.push_str(&format!("<id>{}</id>\n", escape_html(&self.feed_url)));
+        atom.push_str(&format!(
+            "<link href=\"{}\"/>\n",
+            escape_html(&self.home_url)
+        ));
+        atom.push_str(&format!(
+            "<link href=\"{}\" rel=\"self\"/>\n",
+            escape_html(&self.feed_url)
+        ));
+        // required, an empty feed was last updated when it began
+        let updated_at = self
+            .updated_at()
+            .unwrap_or_else(|| NaiveDateTime::from_timestamp(0, 0));
+        atom.push_str(&format!(
+            "<updated>{}</updated>\n",
+            utc(updated_at).to_rfc3339()
+        ));
+        for entry in self.entries.iter() {
+            atom.push_str("<entry>\n");
+            atom.push_str(&format!("<title>{}</title>\n", escape_html(&entry.title)));
+            atom.push_str(&format!("<id>{}</id>\n", escape_html(&entry.url)));
+            atom.push_str(&format!("<link href=\"{}\"/>\n", escape_html(&entry.url)));
+            atom.push_str(&format!(
+                "<author><name>{}</name></author>\n",
+                escape_html(&entry.author)
+            ));
+            atom.push_str(&format!(
+                "<published>{}</published>\n",
+                utc(entry.published_at).to_rfc3339()
+            ));
+            atom.push_str(&format!(
+                "<updated>{}</updated>\n",
+                utc(entry.updated_at).to_rfc3339()
+            ));
+            atom.push_str(&format!(
+                "<content type=\"html\">{}</content>\n",
+                escape_html(&entry.content_html)
+            ));
+            atom.push_str("</entry>\n");
+        }
+        atom.push_str("</feed>\n");
+        atom
+    }
+
+    /// JSON Feed 1.1
+    fn render_json(&self) -> String {
+        let items = self
+            .entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "id": entry.url,
+                    "url": entry.url,
+                    "title": entry.title,
+                    "content_html": entry.content_html,
+                    "date_published": utc(entry.published_at).to_rfc3339(),
+                    "date_modified": utc(entry.updated_at).to_rfc3339(),
+                    "authors": [{ "name": entry.author }],
+                })
+            })
+            .collect::<Vec<serde_json::Value>>();
+        json!({
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": self.title,
+            "home_page_url": self.home_url,
+            "feed_url": self.feed_url,
+            "items": items,
+        })
+        .to_string()
+    }
+}
+
+/// Timestamps are stored in UTC without a zone.
+fn utc(timestamp: NaiveDateTime) -> DateTime<Utc> {
+    DateTime::<Utc>::from_utc(timestamp, Utc)
+}
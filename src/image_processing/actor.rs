@@ -0,0 +1,284 @@
+use actix::{Actor, Handler, Message, SyncContext};
+use derive_more::Display;
+#[cfg(feature = "avif")]
+use image::codecs::avif::AvifEncoder;
+use image::{
+    codecs::{jpeg::JpegEncoder, png::PngEncoder},
+    imageops::FilterType,
+    ColorType, DynamicImage, ImageEncoder, ImageFormat,
+};
+use serde::Deserialize;
+use std::io::Cursor;
+
+/// Width of the thumbnail the blurhash is computed from, the hash only
+/// captures a handful of colors so there is no point using the full image.
+const BLURHASH_SOURCE_WIDTH: u32 = 32;
+const BLURHASH_COMPONENTS_X: u32 = 4;
+const BLURHASH_COMPONENTS_Y: u32 = 3;
+#[cfg(feature = "avif")]
+const AVIF_SPEED: u8 = 8;
+
+#[derive(Display, Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum VariantFormat {
+    #[display(fmt = "webp")]
+    WebP,
+
+    #[cfg(feature = "avif")]
+    #[display(fmt = "avif")]
+    Avif,
+
+    /// Same format as the uploaded file
+    #[display(fmt = "original")]
+    Original,
+}
+
+impl std::str::FromStr for VariantFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "webp" => Ok(VariantFormat::WebP),
+            #[cfg(feature = "avif")]
+            "avif" => Ok(VariantFormat::Avif),
+            "original" => Ok(VariantFormat::Original),
+            _ => Err(()),
+        }
+    }
+}
+
+impl VariantFormat {
+    fn image_format(&self, original_format: ImageFormat) -> ImageFormat {
+        match self {
+            VariantFormat::WebP => ImageFormat::WebP,
+            #[cfg(feature = "avif")]
+            VariantFormat::Avif => ImageFormat::Avif,
+            VariantFormat::Original => original_format,
+        }
+    }
+}
+
+#[derive(Debug, Display)]
+pub enum ImageProcessingError {
+    #[display(fmt = "Unsupported image type `{}`", _0)]
+    UnsupportedType(String),
+
+    #[display(fmt = "{}", _0)]
+    Image(image::ImageError),
+
+    #[display(fmt = "{}", _0)]
+    Encoding(String),
+}
+
+impl From<image::ImageError> for ImageProcessingError {
+    fn from(err: image::ImageError) -> Self {
+        ImageProcessingError::Image(err)
+    }
+}
+
+pub struct EncodedImage {
+    pub mime_type: String,
+    pub extension: String,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+pub struct ProcessedImage {
+    /// The uploaded image re-encoded without any metadata, rotated upright
+    pub original: EncodedImage,
+    pub blurhash: String,
+    pub variants: Vec<EncodedImage>,
+}
+
+/// Decodes, resizes and encodes images. This is cpu heavy, it runs on its own
+/// `SyncArbiter` so that it never blocks the server workers.
+#[derive(Clone)]
+pub struct ImageProcessor {
+    variant_widths: Vec<u32>,
+    variant_formats: Vec<VariantFormat>,
+    quality: u8,
+}
+
+impl ImageProcessor {
+    pub fn new(variant_widths: Vec<u32>, variant_formats: Vec<VariantFormat>, quality: u8) -> Self {
+        ImageProcessor {
+            variant_widths: variant_widths,
+            variant_formats: variant_formats,
+            quality: quality,
+        }
+    }
+
+    /// Images that can be processed, gifs are left alone so that animations survive.
+    pub fn can_process(mime_type: &str) -> bool {
+        Self::image_format(mime_type).is_some()
+    }
+
+    fn image_format(mime_type: &str) -> Option<ImageFormat> {
+        match mime_type {
+            "image/jpeg" => Some(ImageFormat::Jpeg),
+            "image/png" => Some(ImageFormat::Png),
+            "image/webp" => Some(ImageFormat::WebP),
+            _ => None,
+        }
+    }
+
+    fn encode(
+        &self,
+        image: &DynamicImage,
+        format: ImageFormat,
+    ) -> Result<EncodedImage, ImageProcessingError> {
+        let mut data = Vec::new();
+        let (mime_type, extension) = match format {
+            ImageFormat::Jpeg => {
+                let rgb = image.to_rgb8();
+                JpegEncoder::new_with_quality(&mut data, self.quality).write_image(
+                    rgb.as_raw(),
+                    rgb.width(),
+                    rgb.height(),
+                    ColorType::Rgb8,
+                )?;
+                ("image/jpeg", "jpg")
+            }
+            ImageFormat::Png => {
+                let rgba = image.to_rgba8();
+                PngEncoder::new(&mut data).write_image(
+                    rgba.as_raw(),
+                    rgba.width(),
+                    rgba.height(),
+                    ColorType::Rgba8,
+                )?;
+                ("image/png", "png")
+            }
+            ImageFormat::WebP => {
+                let encoder = webp::Encoder::from_image(image)
+                    .map_err(|err| ImageProcessingError::Encoding(err.to_string()))?;
+                data.extend_from_slice(&encoder.encode(self.quality as f32));
+                ("image/webp", "webp")
+            }
+            #[cfg(feature = "avif")]
+            ImageFormat::Avif => {
+                let rgba = image.to_rgba8();
+                AvifEncoder::new_with_speed_quality(&mut data, AVIF_SPEED, self.quality)
+                    .write_image(rgba.as_raw(), rgba.width(), rgba.height(), ColorType::Rgba8)?;
+                ("image/avif", "avif")
+            }
+            _ => {
+                return Err(ImageProcessingError::UnsupportedType(format!(
+                    "{:?}",
+                    format
+                )))
+            }
+        };
+        Ok(EncodedImage {
+            mime_type: mime_type.to_string(),
+            extension: extension.to_string(),
+            width: image.width(),
+            height: image.height(),
+            data: data,
+        })
+    }
+
+    fn process(
+        &self,
+        data: &[u8],
+        mime_type: &str,
+    ) -> Result<ProcessedImage, ImageProcessingError> {
+        let format = Self::image_format(mime_type)
+            .ok_or_else(|| ImageProcessingError::UnsupportedType(mime_type.to_string()))?;
+        let image = upright(image::load_from_memory_with_format(data, format)?, data);
+
+        // re-encoding drops EXIF (GPS included) and any other metadata
+        let original = self.encode(&image, format)?;
+
+        let thumbnail = image
+            .resize(BLURHASH_SOURCE_WIDTH, u32::MAX, FilterType::Triangle)
+            .to_rgba8();
+        let blurhash = blurhash::encode(
+            BLURHASH_COMPONENTS_X,
+            BLURHASH_COMPONENTS_Y,
+            thumbnail.width(),
+            thumbnail.height(),
+            thumbnail.as_raw(),
+        );
+
+        let mut widths = self
+            .variant_widths
+            .iter()
+            .cloned()
+            .filter(|width| *width < image.width())
+            .collect::<Vec<u32>>();
+        widths.sort_unstable();
+        widths.dedup();
+
+        // e.g. `original` and `webp` are the same thing for webp uploads
+        let mut formats: Vec<ImageFormat> = Vec::new();
+        for variant_format in self.variant_formats.iter() {
+            let variant_format = variant_format.image_format(format);
+            if !formats.contains(&variant_format) {
+                formats.push(variant_format);
+            }
+        }
+
+        let mut variants = Vec::new();
+        for width in widths {
+            let resized = image.resize(width, u32::MAX, FilterType::Lanczos3);
+            for variant_format in formats.iter() {
+                variants.push(self.encode(&resized, *variant_format)?);
+            }
+        }
+        // full size copies in the other formats, the original already is one
+        for variant_format in formats
+            .iter()
+            .filter(|variant_format| **variant_format != format)
+        {
+            variants.push(self.encode(&image, *variant_format)?);
+        }
+
+        Ok(ProcessedImage {
+            original: original,
+            blurhash: blurhash,
+            variants: variants,
+        })
+    }
+}
+
+/// Applies the EXIF orientation, since the tag itself is about to be stripped.
+fn upright(image: DynamicImage, data: &[u8]) -> DynamicImage {
+    let orientation = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .unwrap_or(1);
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+impl Actor for ImageProcessor {
+    type Context = SyncContext<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<ProcessedImage, ImageProcessingError>")]
+pub struct ProcessImage {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+}
+
+impl Handler<ProcessImage> for ImageProcessor {
+    type Result = Result<ProcessedImage, ImageProcessingError>;
+
+    fn handle(&mut self, msg: ProcessImage, _: &mut Self::Context) -> Self::Result {
+        self.process(&msg.data, &msg.mime_type)
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    db::{
+        actor::DbActor,
+        models::media::{Media, MediaProcessingStatus, NewMediaVariant},
+        services,
+    },
+    image_processing::actor::{EncodedImage, ImageProcessor, ProcessImage},
+    storage::actor::{DeleteObject, PutObject, StorageActor},
+};
+use actix::Addr;
+use sha2::{Digest, Sha256};
+
+/// `{user_id}/{uuid}.jpg` -> `{user_id}/{uuid}_640w.webp`
+fn variant_key(storage_key: &str, variant: &EncodedImage) -> String {
+    let stem = match storage_key.rfind('.') {
+        Some(idx) if !storage_key[idx..].contains('/') => &storage_key[..idx],
+        _ => storage_key,
+    };
+    format!("{}_{}w.{}", stem, variant.width, variant.extension)
+}
+
+async fn put_object(
+    storage_addr: &Addr<StorageActor>,
+    key: String,
+    image: EncodedImage,
+) -> Result<String, String> {
+    storage_addr
+        .send(PutObject {
+            key: key,
+            data: image.data,
+            content_type: image.mime_type,
+        })
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(|err| err.to_string())
+}
+
+async fn delete_objects(storage_addr: &Addr<StorageActor>, keys: Vec<String>) {
+    for key in keys {
+        let _ = storage_addr.send(DeleteObject { key: key }).await;
+    }
+}
+
+async fn run(
+    db_actor_addr: &Addr<DbActor>,
+    storage_addr: &Addr<StorageActor>,
+    image_processor_addr: &Addr<ImageProcessor>,
+    media: &Media,
+    data: Vec<u8>,
+) -> Result<(), String> {
+    let processed = image_processor_addr
+        .send(ProcessImage {
+            data: data,
+            mime_type: media.mime_type.clone(),
+        })
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(|err| err.to_string())?;
+
+    // the first write of the original, the upload itself never is stored
+    let original = processed.original;
+    let (width, height) = (original.width as i32, original.height as i32);
+    let size_bytes = original.data.len() as i64;
+    let checksum = format!("{:x}", Sha256::digest(&original.data));
+    put_object(storage_addr, media.storage_key.clone(), original).await?;
+
+    let mut new_variants = Vec::new();
+    for variant in processed.variants {
+        let key = variant_key(&media.storage_key, &variant);
+        let (mime_type, width, height) = (variant.mime_type.clone(), variant.width, variant.height);
+        let size_bytes = variant.data.len() as i64;
+        match put_object(storage_addr, key.clone(), variant).await {
+            Ok(url) => new_variants.push(NewMediaVariant {
+                media_id: media.id,
+                storage_key: key,
+                url: url,
+                mime_type: mime_type,
+                width: width as i32,
+                height: height as i32,
+                size_bytes: size_bytes,
+            }),
+            Err(err) => {
+                delete_objects(
+                    storage_addr,
+                    std::iter::once(media.storage_key.clone())
+                        .chain(new_variants.into_iter().map(|variant| variant.storage_key))
+                        .collect(),
+                )
+                .await;
+                return Err(err);
+            }
+        }
+    }
+
+    let storage_keys = std::iter::once(media.storage_key.clone())
+        .chain(
+            new_variants
+                .iter()
+                .map(|variant| variant.storage_key.clone()),
+        )
+        .collect::<Vec<String>>();
+    let saved = db_actor_addr
+        .send(services::media::SaveProcessedMedia {
+            media_id: media.id,
+            size_bytes: size_bytes,
+            checksum: checksum,
+            width: width,
+            height: height,
+            blurhash: processed.blurhash,
+            variants: new_variants,
+        })
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|res| res.map_err(|err| err.to_string()));
+    if let Err(err) = saved {
+        // e.g. the media was deleted in the meantime
+        delete_objects(storage_addr, storage_keys).await;
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Stores an uploaded image without its metadata and generates its variants,
+/// meant to be spawned after the upload request has been answered. Nothing is
+/// left in storage when it fails.
+pub async fn process_media(
+    db_actor_addr: Addr<DbActor>,
+    storage_addr: Addr<StorageActor>,
+    image_processor_addr: Addr<ImageProcessor>,
+    media: Media,
+    data: Vec<u8>,
+) {
+    if let Err(err) = run(
+        &db_actor_addr,
+        &storage_addr,
+        &image_processor_addr,
+        &media,
+        data,
+    )
+    .await
+    {
+        log::error!("Failed processing media `{}`: {}", media.id, err);
+        let _ = db_actor_addr
+            .send(services::media::SetMediaProcessingStatus {
+                media_id: media.id,
+                status: MediaProcessingStatus::Failed,
+            })
+            .await;
+    }
+}
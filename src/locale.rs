@@ -0,0 +1,129 @@
+/// Lowercases a language tag like `de-AT`, `None` if it is not one.
+pub fn normalize_locale<'a>(locale: &'a str) -> Option<String> {
+    let locale = locale.trim().to_ascii_lowercase();
+    let valid = !locale.is_empty()
+        && locale.len() <= 16
+        && locale
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()));
+    if valid {
+        Some(locale)
+    } else {
+        None
+    }
+}
+
+/// Locales of an `Accept-Language` header, most preferred first, e.g.
+/// `"de-AT, en;q=0.5, hi;q=0.8"` -> `["de-at", "hi", "en"]`.
+///
+/// `*`, entries with `q=0` and anything unparsable are left out.
+pub fn parse_accept_language<'a>(header: &'a str) -> Vec<String> {
+    let mut locales = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let locale = normalize_locale(parts.next()?)?;
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .next()
+                .map(|quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(Some(1.0))?;
+            if quality > 0.0 {
+                Some((locale, quality))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<(String, f32)>>();
+    // stable, so equally preferred locales keep the order they were sent in
+    locales.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    locales.into_iter().map(|(locale, _)| locale).collect()
+}
+
+/// Locales to try in order: the `requested` ones, each followed by its language if it has
+/// a region (`de-at` -> `de`), then the `fallbacks`. Only `supported` locales are kept, once.
+pub fn locale_chain<'a>(
+    requested: &'a [String],
+    fallbacks: &'a [String],
+    supported: &'a [String],
+) -> Vec<String> {
+    let mut chain: Vec<String> = vec![];
+    let candidates = requested.iter().flat_map(|locale| {
+        let language = locale
+            .split('-')
+            .next()
+            .map(|language| language.to_string());
+        std::iter::once(locale.clone()).chain(language)
+    });
+    for locale in candidates.chain(fallbacks.iter().cloned()) {
+        if supported.contains(&locale) && !chain.contains(&locale) {
+            chain.push(locale);
+        }
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locales(locales: &[&str]) -> Vec<String> {
+        locales.iter().map(|locale| locale.to_string()).collect()
+    }
+
+    #[test]
+    fn orders_by_quality() {
+        assert_eq!(
+            parse_accept_language("de-AT, en;q=0.5, hi;q=0.8"),
+            locales(&["de-at", "hi", "en"])
+        );
+        assert_eq!(
+            parse_accept_language("en;q=0.9,fr;q=0.9,de"),
+            locales(&["de", "en", "fr"])
+        );
+    }
+
+    #[test]
+    fn leaves_out_wildcards_and_zero_quality() {
+        assert_eq!(
+            parse_accept_language("*, fr;q=0, en;q=0.1"),
+            locales(&["en"])
+        );
+        assert_eq!(parse_accept_language("*"), locales(&[]));
+    }
+
+    #[test]
+    fn leaves_out_malformed_entries() {
+        assert_eq!(
+            parse_accept_language("en;q=abc, , de-, <script>, pt-BR;q=0.7, fr"),
+            locales(&["fr", "pt-br"])
+        );
+        assert_eq!(parse_accept_language(""), locales(&[]));
+    }
+
+    #[test]
+    fn falls_back_from_region_to_language() {
+        let supported = locales(&["en", "pt", "de"]);
+        assert_eq!(
+            locale_chain(&locales(&["pt-br"]), &locales(&["en"]), &supported),
+            locales(&["pt", "en"])
+        );
+    }
+
+    #[test]
+    fn keeps_supported_locales_once_in_order() {
+        let supported = locales(&["en", "de", "de-at", "hi"]);
+        assert_eq!(
+            locale_chain(
+                &locales(&["de-at", "fr", "de", "en"]),
+                &locales(&["en", "hi"]),
+                &supported
+            ),
+            locales(&["de-at", "de", "en", "hi"])
+        );
+        assert_eq!(
+            locale_chain(&locales(&["fr"]), &locales(&[]), &supported),
+            locales(&[])
+        );
+    }
+}
@@ -1,24 +1,73 @@
+// `field: field` initializers, closures around enum variants and spelled out lifetimes are the
+// style of this codebase, the diesel 1.4 derives expand to non-local impls
+#![allow(
+    clippy::redundant_field_names,
+    clippy::redundant_closure,
+    clippy::needless_lifetimes,
+    clippy::redundant_static_lifetimes,
+    non_local_definitions
+)]
 #[macro_use]
 extern crate diesel;
-use crate::{auth::actor::AuthManager, config::Config, db::actor::DbActor};
-use actix::{Addr, SyncArbiter};
+use crate::{
+    analytics::actor::ViewCounter,
+    auth::actor::AuthManager,
+    autosave::actor::AutosaveStore,
+    config::{Config, StorageConfig},
+    db::actor::DbActor,
+    image_processing::actor::ImageProcessor,
+    recommendations::actor::RelatedPostsCache,
+    scheduler::actor::{PostScheduler, TrashPurger, ViewRollup},
+    storage::actor::StorageActor,
+};
+use actix::{Actor, Addr, SyncArbiter};
 use actix_cors::Cors;
+use actix_files::Files;
 use actix_web::{middleware::Logger, web::Data, App, HttpServer};
 use dotenv::dotenv;
 use std::cmp::max;
+use std::net::IpAddr;
 
+mod analytics;
 mod argon2_password_hasher;
 mod auth;
+mod autosave;
+mod body_renderer;
 mod config;
+mod content_blocks;
+mod content_types;
 mod db;
 mod errors;
+mod feeds;
+mod image_processing;
+mod locale;
 mod openapi;
+mod recommendations;
+mod scheduler;
+mod slugify;
+mod storage;
 mod views;
 
 #[derive(Clone)]
 pub struct AppState {
     db_actor_addr: Addr<DbActor>,
     auth_mgr_addr: Addr<AuthManager>,
+    storage_addr: Addr<StorageActor>,
+    image_processor_addr: Addr<ImageProcessor>,
+    view_counter_addr: Addr<ViewCounter>,
+    /// `None` when redis is not available, autosaves then go to the `DbActor`
+    autosave_store_addr: Option<Addr<AutosaveStore>>,
+    related_posts_cache_addr: Addr<RelatedPostsCache>,
+    media_max_size_bytes: usize,
+    media_allowed_types: Vec<String>,
+    comment_trusted_after_approved: i64,
+    reaction_emojis: Vec<String>,
+    trusted_proxies: Vec<IpAddr>,
+    default_locale: String,
+    supported_locales: Vec<String>,
+    locale_fallbacks: Vec<String>,
+    site_url: String,
+    site_title: String,
 }
 
 fn cors(cors_allow_all: bool) -> Cors {
@@ -46,41 +95,138 @@ async fn main() -> std::io::Result<()> {
     let auth_mgr = AuthManager::new(
         config.jwt_secret,
         config.jwt_expiration_duration,
+        config.redis_server_url.clone(),
+        config.redis_server_get_connection_timeout,
+    );
+    let view_counter = ViewCounter::new(
+        config.redis_server_url.clone(),
+        config.redis_server_get_connection_timeout,
+    );
+    let related_posts_cache = RelatedPostsCache::new(
+        config.redis_server_url.clone(),
+        config.redis_server_get_connection_timeout,
+    );
+    let autosave_store = AutosaveStore::new(
         config.redis_server_url,
         config.redis_server_get_connection_timeout,
     );
+    let storage_actor = StorageActor::new(storage::backend::from_config(&config.storage));
+    let image_processor = ImageProcessor::new(
+        config.image_variant_widths.clone(),
+        config.image_variant_formats.clone(),
+        config.image_variant_quality,
+    );
 
     // Worker allocation
     let num_of_cpus = num_cpus::get();
     let server_workers = max(num_of_cpus >> 1, 1);
     let db_actor_workers = max((((num_of_cpus - server_workers) as f32) * 0.7).floor() as usize, 1);
     let auth_mgr_workers = max(num_of_cpus - server_workers - db_actor_workers, 1);
+    let storage_workers = max(num_of_cpus >> 2, 1);
+    let image_processor_workers = max(num_of_cpus >> 2, 1);
+    let view_counter_workers = max(num_of_cpus >> 2, 1);
+    let autosave_store_workers = max(num_of_cpus >> 2, 1);
+    let related_posts_cache_workers = max(num_of_cpus >> 2, 1);
 
     log::info!("Number of Logical Cores: {}", num_of_cpus);
     log::info!("Worker Allocation:");
     log::info!("Num Server Workers: {}", server_workers);
     log::info!("Num DB Workers: {}", db_actor_workers);
     log::info!("Num Auth Mgr Workers: {}", auth_mgr_workers);
+    log::info!("Num Storage Workers: {}", storage_workers);
+    log::info!("Num Image Processor Workers: {}", image_processor_workers);
+    log::info!("Num View Counter Workers: {}", view_counter_workers);
+    log::info!("Num Autosave Store Workers: {}", autosave_store_workers);
+    log::info!(
+        "Num Related Posts Cache Workers: {}",
+        related_posts_cache_workers
+    );
 
     // Spawning workers
     let db_actor_addr = SyncArbiter::start(db_actor_workers, move || db_actor.clone());
     let auth_mgr_addr = SyncArbiter::start(auth_mgr_workers, move || auth_mgr.clone());
+    let storage_addr = SyncArbiter::start(storage_workers, move || storage_actor.clone());
+    let image_processor_addr =
+        SyncArbiter::start(image_processor_workers, move || image_processor.clone());
+    let view_counter_addr = SyncArbiter::start(view_counter_workers, move || view_counter.clone());
+    let related_posts_cache_addr = SyncArbiter::start(related_posts_cache_workers, move || {
+        related_posts_cache.clone()
+    });
+    let autosave_store_addr = autosave_store.map(|autosave_store| {
+        SyncArbiter::start(autosave_store_workers, move || autosave_store.clone())
+    });
+
+    // Background jobs
+    let _ = PostScheduler::new(db_actor_addr.clone(), config.post_scheduler_interval).start();
+    let _ = TrashPurger::new(
+        db_actor_addr.clone(),
+        config.trash_purge_interval,
+        config.trash_retention_days,
+    )
+    .start();
+    let _ = ViewRollup::new(
+        db_actor_addr.clone(),
+        view_counter_addr.clone(),
+        config.view_rollup_interval,
+    )
+    .start();
 
     let app_state = AppState {
         db_actor_addr: db_actor_addr,
         auth_mgr_addr: auth_mgr_addr,
+        storage_addr: storage_addr,
+        image_processor_addr: image_processor_addr,
+        view_counter_addr: view_counter_addr,
+        autosave_store_addr: autosave_store_addr,
+        related_posts_cache_addr: related_posts_cache_addr,
+        media_max_size_bytes: config.media_max_size_bytes,
+        media_allowed_types: config.media_allowed_types,
+        comment_trusted_after_approved: config.comment_trusted_after_approved,
+        reaction_emojis: config.reaction_emojis,
+        trusted_proxies: config.trusted_proxies,
+        default_locale: config.default_locale,
+        supported_locales: config.supported_locales,
+        locale_fallbacks: config.locale_fallbacks,
+        site_url: config.site_url,
+        site_title: config.site_title,
+    };
+
+    // Uploaded files are only served by the app when they are stored locally
+    let local_storage_root = match &config.storage {
+        StorageConfig::LocalFs { root, .. } => Some(root.clone()),
+        StorageConfig::S3 { .. } => None,
     };
 
     log::info!("Starting server on: http://{}:{}", host, port);
     HttpServer::new(move || {
         let cors = cors(config.cors_allow_all);
-        App::new()
+        let mut app = App::new()
             .wrap(cors)
             .app_data(Data::new(app_state.clone()))
             .wrap(Logger::default()) // enable logger
             .configure(views::users::config)
             .configure(views::posts::config)
-            .configure(views::swagger_ui::config)
+            .configure(views::categories::config)
+            .configure(views::tags::config)
+            .configure(views::media::config)
+            .configure(views::comments::config)
+            .configure(views::reactions::config)
+            .configure(views::analytics::config)
+            .configure(views::collaborators::config)
+            .configure(views::autosaves::config)
+            .configure(views::previews::config)
+            .configure(views::translations::config)
+            .configure(views::content_types::config)
+            .configure(views::pages::config)
+            .configure(views::menus::config)
+            .configure(views::collections::config)
+            .configure(views::recommendations::config)
+            .configure(views::feeds::config)
+            .configure(views::swagger_ui::config);
+        if let Some(root) = &local_storage_root {
+            app = app.service(Files::new("/media/files", root));
+        }
+        app
     })
     .workers(server_workers)
     .bind((host, port))?
@@ -0,0 +1,116 @@
+use crate::auth::actor::{RedisPool, RedisPoolConnection};
+use actix::{Actor, Handler, Message, SyncContext};
+use r2d2_redis::redis::{Commands, RedisError};
+use r2d2_redis::RedisConnectionManager;
+
+/// Entries of older stamps are never read again, they are left to expire.
+const RELATED_POSTS_TTL_SECS: usize = 24 * 60 * 60;
+
+/// Caches the related posts of each post in redis, under the stamp of the posts they were
+/// computed from (see `GetRelatedPostsStamp`). Nothing is cached when redis is not available.
+#[derive(Clone)]
+pub struct RelatedPostsCache {
+    redis_pool: Option<RedisPool>,
+}
+
+impl RelatedPostsCache {
+    pub fn new(redis_server_url: String, redis_server_get_connection_timeout: u64) -> Self {
+        let redis_pool = RedisConnectionManager::new(redis_server_url)
+            .map_err(|err| err.to_string())
+            .and_then(|manager| {
+                r2d2::Pool::builder()
+                    .connection_timeout(std::time::Duration::from_secs(
+                        redis_server_get_connection_timeout,
+                    ))
+                    .build(manager)
+                    .map_err(|err| err.to_string())
+            });
+        match redis_pool {
+            Ok(redis_pool) => RelatedPostsCache {
+                redis_pool: Some(redis_pool),
+            },
+            Err(err) => {
+                log::error!(
+                    "Failed connecting to redis, related posts will not be cached: {}",
+                    err
+                );
+                RelatedPostsCache { redis_pool: None }
+            }
+        }
+    }
+
+    fn related_posts_key(stamp: &str, post_id: i32) -> String {
+        format!("related_posts:{}:{}", stamp, post_id)
+    }
+
+    fn get_conn(&self) -> Option<RedisPoolConnection> {
+        match self.redis_pool.as_ref()?.get() {
+            Ok(conn) => Some(conn),
+            Err(err) => {
+                log::error!("Failed getting redis connection: {}", err);
+                None
+            }
+        }
+    }
+}
+
+impl Actor for RelatedPostsCache {
+    type Context = SyncContext<Self>;
+}
+
+/// Ids of the related posts of `post_id` computed under `stamp`, `None` when not cached.
+#[derive(Message)]
+#[rtype(result = "Result<Option<Vec<i32>>, RedisError>")]
+pub struct GetCachedRelatedPosts {
+    pub post_id: i32,
+    pub stamp: String,
+}
+
+impl Handler<GetCachedRelatedPosts> for RelatedPostsCache {
+    type Result = Result<Option<Vec<i32>>, RedisError>;
+
+    fn handle(&mut self, msg: GetCachedRelatedPosts, _: &mut Self::Context) -> Self::Result {
+        let mut conn = match self.get_conn() {
+            Some(conn) => conn,
+            None => return Ok(None),
+        };
+        let related: Option<String> = conn.get(Self::related_posts_key(&msg.stamp, msg.post_id))?;
+        // ids are kept comma separated, an empty string is a post without related posts
+        Ok(related.map(|related| {
+            related
+                .split(',')
+                .filter_map(|post_id| post_id.parse::<i32>().ok())
+                .collect()
+        }))
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), RedisError>")]
+pub struct CacheRelatedPosts {
+    pub post_id: i32,
+    pub stamp: String,
+    pub related_post_ids: Vec<i32>,
+}
+
+impl Handler<CacheRelatedPosts> for RelatedPostsCache {
+    type Result = Result<(), RedisError>;
+
+    fn handle(&mut self, msg: CacheRelatedPosts, _: &mut Self::Context) -> Self::Result {
+        let mut conn = match self.get_conn() {
+            Some(conn) => conn,
+            None => return Ok(()),
+        };
+        let related = msg
+            .related_post_ids
+            .iter()
+            .map(|post_id| post_id.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        conn.set_ex(
+            Self::related_posts_key(&msg.stamp, msg.post_id),
+            related,
+            RELATED_POSTS_TTL_SECS,
+        )
+    }
+}
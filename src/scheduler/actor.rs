@@ -0,0 +1,163 @@
+use crate::analytics::actor::{GetDailyViews, ViewCounter};
+use crate::db::{actor::DbActor, services};
+use actix::{Actor, ActorFutureExt, Addr, AsyncContext, Context, WrapFuture};
+use std::time::Duration;
+
+/// Periodically asks the `DbActor` to publish / unpublish posts whose schedule is due.
+pub struct PostScheduler {
+    db_actor_addr: Addr<DbActor>,
+    interval: Duration,
+}
+
+impl PostScheduler {
+    pub fn new(db_actor_addr: Addr<DbActor>, interval_secs: u64) -> Self {
+        PostScheduler {
+            db_actor_addr: db_actor_addr,
+            interval: Duration::from_secs(interval_secs),
+        }
+    }
+
+    fn run_schedules(&mut self, ctx: &mut Context<Self>) {
+        let db_actor_addr = self.db_actor_addr.clone();
+        let fut = async move { db_actor_addr.send(services::posts::RunPostSchedules).await }
+            .into_actor(self)
+            .map(|res, _, _| match res {
+                Ok(Ok(summary)) => {
+                    if !summary.published.is_empty() || !summary.unpublished.is_empty() {
+                        log::info!(
+                            "Post scheduler published: {:?}, unpublished: {:?}",
+                            summary.published,
+                            summary.unpublished
+                        );
+                    }
+                }
+                Ok(Err(err)) => log::error!("Post scheduler failed: {}", err),
+                Err(err) => log::error!("Post scheduler could not reach db actor: {}", err),
+            });
+        // wait so that runs never overlap on a slow database
+        ctx.wait(fut);
+    }
+}
+
+impl Actor for PostScheduler {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        log::info!("Starting post scheduler, interval: {:?}", self.interval);
+        self.run_schedules(ctx);
+        ctx.run_interval(self.interval, |act, ctx| act.run_schedules(ctx));
+    }
+}
+
+/// Periodically purges posts that stayed in the trash longer than the retention period.
+pub struct TrashPurger {
+    db_actor_addr: Addr<DbActor>,
+    interval: Duration,
+    retention_days: i32,
+}
+
+impl TrashPurger {
+    pub fn new(db_actor_addr: Addr<DbActor>, interval_secs: u64, retention_days: i32) -> Self {
+        TrashPurger {
+            db_actor_addr: db_actor_addr,
+            interval: Duration::from_secs(interval_secs),
+            retention_days: retention_days,
+        }
+    }
+
+    fn purge(&mut self, ctx: &mut Context<Self>) {
+        let db_actor_addr = self.db_actor_addr.clone();
+        let msg = services::posts::PurgeTrashedPosts {
+            retention_days: self.retention_days,
+        };
+        let fut = async move { db_actor_addr.send(msg).await }
+            .into_actor(self)
+            .map(|res, _, _| match res {
+                Ok(Ok(0)) => {}
+                Ok(Ok(purged)) => log::info!("Trash purger removed {} posts", purged),
+                Ok(Err(err)) => log::error!("Trash purger failed: {}", err),
+                Err(err) => log::error!("Trash purger could not reach db actor: {}", err),
+            });
+        ctx.wait(fut);
+    }
+}
+
+impl Actor for TrashPurger {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        log::info!(
+            "Starting trash purger, interval: {:?}, retention: {} days",
+            self.interval,
+            self.retention_days
+        );
+        self.purge(ctx);
+        ctx.run_interval(self.interval, |act, ctx| act.purge(ctx));
+    }
+}
+
+/// Periodically copies the unique views counted in redis to `post_stats_daily`.
+pub struct ViewRollup {
+    db_actor_addr: Addr<DbActor>,
+    view_counter_addr: Addr<ViewCounter>,
+    interval: Duration,
+}
+
+impl ViewRollup {
+    pub fn new(
+        db_actor_addr: Addr<DbActor>,
+        view_counter_addr: Addr<ViewCounter>,
+        interval_secs: u64,
+    ) -> Self {
+        ViewRollup {
+            db_actor_addr: db_actor_addr,
+            view_counter_addr: view_counter_addr,
+            interval: Duration::from_secs(interval_secs),
+        }
+    }
+
+    fn roll_up(&mut self, ctx: &mut Context<Self>) {
+        let db_actor_addr = self.db_actor_addr.clone();
+        let view_counter_addr = self.view_counter_addr.clone();
+        let today = chrono::Utc::now().naive_utc().date();
+        let fut = async move {
+            // yesterday too, for the views that came in after its last run
+            for day in [today.pred(), today] {
+                let views_by_post = view_counter_addr
+                    .send(GetDailyViews { day: day })
+                    .await
+                    .map_err(|err| err.to_string())?
+                    .map_err(|err| err.to_string())?;
+                if views_by_post.is_empty() {
+                    continue;
+                }
+                db_actor_addr
+                    .send(services::stats::UpsertPostStatsDaily {
+                        day: day,
+                        views_by_post: views_by_post,
+                    })
+                    .await
+                    .map_err(|err| err.to_string())?
+                    .map_err(|err| err.to_string())?;
+            }
+            Ok::<(), String>(())
+        }
+        .into_actor(self)
+        .map(|res, _, _| {
+            if let Err(err) = res {
+                log::error!("View rollup failed: {}", err);
+            }
+        });
+        ctx.wait(fut);
+    }
+}
+
+impl Actor for ViewRollup {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        log::info!("Starting view rollup, interval: {:?}", self.interval);
+        self.roll_up(ctx);
+        ctx.run_interval(self.interval, |act, ctx| act.roll_up(ctx));
+    }
+}
@@ -0,0 +1,86 @@
+use deunicode::deunicode;
+
+const MAX_SLUG_LENGTH: usize = 80;
+
+/// Turns arbitrary text into a url safe slug, e.g. `"Héllo, Wörld!"` -> `"hello-world"`.
+///
+/// Unicode is transliterated to ascii first, so `"Привет мир"` becomes `"privet-mir"`.
+/// Returns an empty string if `input` has nothing sluggable in it.
+pub fn slugify<'a>(input: &'a str) -> String {
+    let mut slug = String::with_capacity(input.len());
+    for c in deunicode(input).chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    if slug.len() > MAX_SLUG_LENGTH {
+        // cut at the last word boundary so that we dont end up with half words
+        slug.truncate(MAX_SLUG_LENGTH);
+        if let Some(idx) = slug.rfind('-') {
+            slug.truncate(idx);
+        }
+    }
+
+    slug.trim_matches('-').to_string()
+}
+
+/// Appends `-2`, `-3`, ... to `base` until it does not collide with any of `taken`.
+pub fn with_collision_suffix<'a>(base: &'a str, taken: &'a [String]) -> String {
+    if !taken.iter().any(|slug| slug == base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|suffix| format!("{}-{}", base, suffix))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("Ran out of slug suffixes!")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transliterates_unicode() {
+        assert_eq!(slugify("Héllo, Wörld!"), "hello-world");
+        assert_eq!(slugify("Привет мир"), "privet-mir");
+        assert_eq!(slugify("Ærøskøbing straße"), "aeroskobing-strasse");
+    }
+
+    #[test]
+    fn collapses_separators() {
+        assert_eq!(slugify("  --Rust   &  Actix--  "), "rust-actix");
+        assert_eq!(slugify("a_b.c/d"), "a-b-c-d");
+    }
+
+    #[test]
+    fn empty_when_nothing_is_sluggable() {
+        assert_eq!(slugify(""), "");
+        assert_eq!(slugify("   "), "");
+        assert_eq!(slugify("!?... --- ,;"), "");
+    }
+
+    #[test]
+    fn cuts_long_input_at_a_word_boundary() {
+        let slug = slugify(&"word ".repeat(40));
+        assert!(slug.len() <= MAX_SLUG_LENGTH);
+        assert!(slug.ends_with("word"));
+    }
+
+    #[test]
+    fn fallback_slug_keeps_the_post_id() {
+        // posts with an empty slug fall back to `post-{id}`, which slugify leaves alone
+        assert_eq!(slugify("post-42"), "post-42");
+        assert_eq!(with_collision_suffix("post-42", &[]), "post-42");
+    }
+
+    #[test]
+    fn suffixes_colliding_slugs() {
+        let taken = vec!["hello".to_string(), "hello-2".to_string()];
+        assert_eq!(with_collision_suffix("hello", &taken), "hello-3");
+        assert_eq!(with_collision_suffix("world", &taken), "world");
+        assert_eq!(with_collision_suffix("hello-2", &taken), "hello-2-2");
+    }
+}
@@ -0,0 +1,65 @@
+use crate::storage::backend::{StorageBackend, StorageError};
+use actix::{Actor, Handler, Message, SyncContext};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct StorageActor {
+    backend: Arc<dyn StorageBackend>,
+}
+
+impl StorageActor {
+    pub fn new(backend: Arc<dyn StorageBackend>) -> Self {
+        StorageActor { backend: backend }
+    }
+}
+
+impl Actor for StorageActor {
+    type Context = SyncContext<Self>;
+}
+
+/// Stores `data` under `key`, returns the public url of the object.
+#[derive(Message)]
+#[rtype(result = "Result<String, StorageError>")]
+pub struct PutObject {
+    pub key: String,
+    pub data: Vec<u8>,
+    pub content_type: String,
+}
+
+impl Handler<PutObject> for StorageActor {
+    type Result = Result<String, StorageError>;
+
+    fn handle(&mut self, msg: PutObject, _: &mut Self::Context) -> Self::Result {
+        self.backend.put(&msg.key, &msg.data, &msg.content_type)?;
+        Ok(self.backend.url(&msg.key))
+    }
+}
+
+/// Public url an object under `key` is served from, whether it is stored yet or not.
+#[derive(Message)]
+#[rtype(result = "String")]
+pub struct ObjectUrl {
+    pub key: String,
+}
+
+impl Handler<ObjectUrl> for StorageActor {
+    type Result = String;
+
+    fn handle(&mut self, msg: ObjectUrl, _: &mut Self::Context) -> Self::Result {
+        self.backend.url(&msg.key)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), StorageError>")]
+pub struct DeleteObject {
+    pub key: String,
+}
+
+impl Handler<DeleteObject> for StorageActor {
+    type Result = Result<(), StorageError>;
+
+    fn handle(&mut self, msg: DeleteObject, _: &mut Self::Context) -> Self::Result {
+        self.backend.delete(&msg.key)
+    }
+}
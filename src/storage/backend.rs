@@ -0,0 +1,73 @@
+use crate::config::StorageConfig;
+use crate::storage::{local_fs::LocalFsBackend, s3::S3Backend};
+use derive_more::Display;
+use std::sync::Arc;
+
+#[derive(Debug, Display)]
+pub enum StorageError {
+    #[display(fmt = "{}", _0)]
+    Io(std::io::Error),
+
+    #[display(fmt = "{}", _0)]
+    S3(String),
+
+    #[display(fmt = "Invalid storage key `{}`", _0)]
+    InvalidKey(String),
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(err: std::io::Error) -> Self {
+        StorageError::Io(err)
+    }
+}
+
+/// Where uploaded files end up. Implementations are blocking, they are only
+/// called from the `StorageActor` workers.
+pub trait StorageBackend: Send + Sync {
+    fn put(&self, key: &str, data: &[u8], content_type: &str) -> Result<(), StorageError>;
+
+    fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// Public url the object can be downloaded from
+    fn url(&self, key: &str) -> String;
+}
+
+/// Keys are generated by the server, this only guards against path traversal
+/// in case that ever changes.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('/')
+        && key
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+}
+
+pub fn from_config(storage_config: &StorageConfig) -> Arc<dyn StorageBackend> {
+    match storage_config {
+        StorageConfig::LocalFs { root, base_url } => {
+            log::info!("Using local filesystem storage at `{}`", root);
+            Arc::new(LocalFsBackend::new(root.clone(), base_url.clone()))
+        }
+        StorageConfig::S3 {
+            bucket,
+            region,
+            endpoint,
+            access_key,
+            secret_key,
+            public_url,
+        } => {
+            log::info!("Using S3 storage, bucket `{}` at `{}`", bucket, endpoint);
+            Arc::new(
+                S3Backend::new(
+                    bucket,
+                    region,
+                    endpoint,
+                    access_key,
+                    secret_key,
+                    public_url.clone(),
+                )
+                .expect("Error creating S3 storage backend!"),
+            )
+        }
+    }
+}
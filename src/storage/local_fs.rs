@@ -0,0 +1,47 @@
+use crate::storage::backend::{is_valid_key, StorageBackend, StorageError};
+use std::path::PathBuf;
+
+/// Stores files under `root`, they are served by the app itself under `/media/files`.
+pub struct LocalFsBackend {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalFsBackend {
+    pub fn new(root: String, base_url: String) -> Self {
+        LocalFsBackend {
+            root: PathBuf::from(root),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn path_of(&self, key: &str) -> Result<PathBuf, StorageError> {
+        if is_valid_key(key) {
+            Ok(self.root.join(key))
+        } else {
+            Err(StorageError::InvalidKey(key.to_string()))
+        }
+    }
+}
+
+impl StorageBackend for LocalFsBackend {
+    fn put(&self, key: &str, data: &[u8], _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path_of(key)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match std::fs::remove_file(self.path_of(key)?) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(StorageError::Io(err)),
+            _ => Ok(()),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, key)
+    }
+}
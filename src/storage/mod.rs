@@ -0,0 +1,4 @@
+pub mod actor;
+pub mod backend;
+mod local_fs;
+mod s3;
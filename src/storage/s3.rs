@@ -0,0 +1,66 @@
+use crate::storage::backend::{is_valid_key, StorageBackend, StorageError};
+use s3::{bucket::Bucket, creds::Credentials, region::Region};
+
+/// Any S3 compatible object store, path style addressing is used so that it
+/// also works against MinIO.
+pub struct S3Backend {
+    bucket: Bucket,
+    public_url: String,
+}
+
+impl S3Backend {
+    pub fn new(
+        bucket_name: &str,
+        region: &str,
+        endpoint: &str,
+        access_key: &str,
+        secret_key: &str,
+        public_url: Option<String>,
+    ) -> Result<Self, StorageError> {
+        let region = Region::Custom {
+            region: region.to_string(),
+            endpoint: endpoint.to_string(),
+        };
+        let credentials = Credentials::new(Some(access_key), Some(secret_key), None, None, None)
+            .map_err(|err| StorageError::S3(err.to_string()))?;
+        let bucket = Bucket::new(bucket_name, region, credentials)
+            .map_err(|err| StorageError::S3(err.to_string()))?
+            .with_path_style();
+        let public_url = public_url
+            .unwrap_or_else(|| format!("{}/{}", endpoint.trim_end_matches('/'), bucket_name));
+        Ok(S3Backend {
+            bucket: bucket,
+            public_url: public_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn ensure_valid_key(key: &str) -> Result<(), StorageError> {
+        if is_valid_key(key) {
+            Ok(())
+        } else {
+            Err(StorageError::InvalidKey(key.to_string()))
+        }
+    }
+}
+
+impl StorageBackend for S3Backend {
+    fn put(&self, key: &str, data: &[u8], content_type: &str) -> Result<(), StorageError> {
+        Self::ensure_valid_key(key)?;
+        self.bucket
+            .put_object_with_content_type(key, data, content_type)
+            .map_err(|err| StorageError::S3(err.to_string()))?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StorageError> {
+        Self::ensure_valid_key(key)?;
+        self.bucket
+            .delete_object(key)
+            .map_err(|err| StorageError::S3(err.to_string()))?;
+        Ok(())
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+}
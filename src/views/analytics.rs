@@ -0,0 +1,213 @@
+use crate::{
+    analytics::{actor::RecordView, bots::is_bot},
+    db::{models::posts::PublishStatus, models::users::User, selectors},
+    errors::MyError,
+    views::{self, posts::ensure_user_can_edit_post_or_is_admin},
+    AppState,
+};
+use actix_web::{get, http::header, web, HttpRequest};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::vec::Vec;
+use utoipa::{Component, IntoParams};
+
+const DEFAULT_STATS_DAYS: i64 = 30;
+const MAX_STATS_DAYS: i64 = 365;
+const DEFAULT_TOP_POSTS_DAYS: i64 = 7;
+const DEFAULT_TOP_POSTS_LIMIT: i64 = 10;
+const MAX_TOP_POSTS_LIMIT: i64 = 100;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_post_stats).service(get_top_posts);
+}
+
+/// Counts a view of a published post, unless it comes from a bot or the owner.
+/// Fire and forget, the response never waits for redis.
+pub fn record_view(
+    app_state: &AppState,
+    req: &HttpRequest,
+    post: &selectors::posts::PostData,
+    user_maybe: &Option<User>,
+) {
+    if post.status != format!("{}", PublishStatus::Published) {
+        return;
+    }
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|user_agent| user_agent.to_str().ok());
+    if is_bot(user_agent) {
+        return;
+    }
+    let visitor_id = match user_maybe {
+        Some(user) if user.id == post.user_id => return,
+        Some(user) => format!("user:{}", user.id),
+        None => {
+            // visitors are told apart by a hash, the address itself is not kept. Any client can
+            // send forwarded headers, so they are only believed when the peer is a trusted proxy
+            let remote_ip = match req.peer_addr() {
+                Some(peer_addr) if app_state.trusted_proxies.contains(&peer_addr.ip()) => req
+                    .connection_info()
+                    .realip_remote_addr()
+                    .unwrap_or_default()
+                    .to_string(),
+                Some(peer_addr) => peer_addr.ip().to_string(),
+                None => String::new(),
+            };
+            let digest =
+                Sha256::digest(format!("{}|{}", remote_ip, user_agent.unwrap_or("")).as_bytes());
+            format!("anon:{:x}", digest)
+        }
+    };
+    app_state.view_counter_addr.do_send(RecordView {
+        post_id: post.id,
+        visitor_id: visitor_id,
+        day: chrono::Utc::now().naive_utc().date(),
+    });
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct DailyViews {
+    day: chrono::NaiveDate,
+    views: i32,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PostStatsData {
+    post_id: i32,
+    total_views: i64,
+    /// One entry per day, oldest first, days without views included
+    daily_views: Vec<DailyViews>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct PostStatsQuery {
+    /// Number of days up to today, 30 by default, at most 365
+    days: Option<i64>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Unique views per day of a Post, for its owner and admins", body = PostStatsData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/posts/{post_id}/stats")]
+async fn get_post_stats(
+    path: web::Path<i32>,
+    query: web::Query<PostStatsQuery>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostStatsData>, MyError> {
+    let post_id = path.into_inner();
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_STATS_DAYS)
+        .clamp(1, MAX_STATS_DAYS);
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ =
+        ensure_user_can_edit_post_or_is_admin(db_actor_addr.clone(), &authed_user.user, post_id)
+            .await?;
+    let today = chrono::Utc::now().naive_utc().date();
+    let since = today - chrono::Duration::days(days - 1);
+    let stats = db_actor_addr
+        .send(selectors::stats::GetPostStats {
+            post_id: post_id,
+            since: since,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let views_by_day = stats
+        .iter()
+        .map(|day_stats| (day_stats.day, day_stats.views))
+        .collect::<HashMap<chrono::NaiveDate, i32>>();
+    let daily_views = (0..days)
+        .map(|offset| since + chrono::Duration::days(offset))
+        .map(|day| DailyViews {
+            day: day,
+            views: views_by_day.get(&day).cloned().unwrap_or(0),
+        })
+        .collect::<Vec<DailyViews>>();
+    Ok(web::Json(PostStatsData {
+        post_id: post_id,
+        total_views: daily_views.iter().map(|day| day.views as i64).sum(),
+        daily_views: daily_views,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct TopPostData {
+    id: i32,
+    subject: String,
+    slug: String,
+    views: i64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct TopPostsQuery {
+    /// Number of days up to today, 7 by default, at most 365
+    days: Option<i64>,
+    /// Number of posts, 10 by default, at most 100
+    limit: Option<i64>,
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Most viewed posts, admins only", body = [TopPostData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/admins/top_posts")]
+async fn get_top_posts(
+    query: web::Query<TopPostsQuery>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<TopPostData>>, MyError> {
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_TOP_POSTS_DAYS)
+        .clamp(1, MAX_STATS_DAYS);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_TOP_POSTS_LIMIT)
+        .clamp(1, MAX_TOP_POSTS_LIMIT);
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let since = chrono::Utc::now().naive_utc().date() - chrono::Duration::days(days - 1);
+    let top_posts = db_actor_addr
+        .send(selectors::stats::GetTopPosts {
+            since: since,
+            limit: limit,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        top_posts
+            .into_iter()
+            .map(|top_post| TopPostData {
+                id: top_post.id,
+                subject: top_post.subject,
+                slug: top_post.slug,
+                views: top_post.views.unwrap_or(0),
+            })
+            .collect::<Vec<TopPostData>>(),
+    ))
+}
@@ -0,0 +1,300 @@
+use crate::{
+    autosave::actor::{
+        AutosaveStore, AutosaveStoreError, DiscardAutosave, GetAutosave, SaveAutosave, SaveOutcome,
+    },
+    db::{actor::DbActor, models::autosaves::PostAutosave, services},
+    errors::MyError,
+    views::{
+        self,
+        posts::{
+            ensure_user_can_edit_post, expected_version, parse_body_format, post_response,
+            versioned_post_data,
+        },
+    },
+    AppState,
+};
+use actix::{Handler, Message};
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_autosave)
+        .service(save_autosave)
+        .service(discard_autosave)
+        .service(promote_autosave);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct AutosaveData {
+    post_id: i32,
+    /// Send it back as `base_revision` with the next save
+    revision: i64,
+    /// Version of the post the edits are based on
+    post_version: i32,
+    subject: Option<String>,
+    body: Option<String>,
+    body_format: Option<String>,
+    saved_at: chrono::NaiveDateTime,
+}
+
+impl AutosaveData {
+    fn from_autosave(autosave: &PostAutosave) -> Self {
+        AutosaveData {
+            post_id: autosave.post_id,
+            revision: autosave.revision,
+            post_version: autosave.post_version,
+            subject: autosave.subject.clone(),
+            body: autosave.body.clone(),
+            body_format: autosave.body_format.clone(),
+            saved_at: autosave.saved_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct SaveAutosaveData {
+    /// `revision` of the last autosave seen by this tab, 0 when starting a new one
+    base_revision: i64,
+    /// Version of the post the edits are based on
+    post_version: i32,
+    /// Fields left out are kept as they are in the post
+    subject: Option<String>,
+    body: Option<String>,
+    /// One of `plain`, `markdown` or `html`
+    body_format: Option<String>,
+}
+
+/// Sends `msg` to redis, or to postgres when redis is not available. Redis going away after
+/// boot is not fatal either, the message is retried on postgres.
+async fn send_to_store<M, T>(app_state: &AppState, msg: M) -> Result<T, MyError>
+where
+    M: Message<Result = Result<T, AutosaveStoreError>> + Clone + Send + 'static,
+    T: Send + 'static,
+    AutosaveStore: Handler<M>,
+    DbActor: Handler<M>,
+{
+    let res = match &app_state.autosave_store_addr {
+        Some(autosave_store_addr) => match autosave_store_addr.send(msg.clone()).await {
+            Ok(Err(err @ AutosaveStoreError::Pool(_)))
+            | Ok(Err(err @ AutosaveStoreError::Redis(_))) => {
+                log::warn!("Autosave store unavailable, using postgres: {}", err);
+                app_state.db_actor_addr.send(msg).await
+            }
+            res => res,
+        },
+        None => app_state.db_actor_addr.send(msg).await,
+    };
+    res.map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| match err {
+            AutosaveStoreError::Diesel(err) => MyError::DieselError(err),
+            err => {
+                log::error!("Autosave store failed: {}", err);
+                MyError::InternalServerError
+            }
+        })
+}
+
+async fn stored_autosave(
+    app_state: &AppState,
+    post_id: i32,
+    user_id: i32,
+) -> Result<PostAutosave, MyError> {
+    send_to_store(
+        app_state,
+        GetAutosave {
+            post_id: post_id,
+            user_id: user_id,
+        },
+    )
+    .await?
+    .ok_or(MyError::AutosaveNotFound)
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Get the autosave of the caller for a Post", body = AutosaveData),
+        (status = 404, description = "Autosave Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/posts/autosave/{post_id}")]
+async fn get_autosave(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<AutosaveData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr, authed_user.user.id, post_id).await?;
+    let autosave = stored_autosave(app_state.get_ref(), post_id, authed_user.user.id).await?;
+    Ok(web::Json(AutosaveData::from_autosave(&autosave)))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=SaveAutosaveData,
+    responses(
+        (status = 200, description = "Autosave edits to a Post, without touching the Post itself", body = AutosaveData),
+        (status = 409, description = "Another tab saved since `base_revision`, holds the current autosave")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/autosave/{post_id}")]
+async fn save_autosave(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    save_autosave_data: web::Json<SaveAutosaveData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<AutosaveData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let save_autosave_data = save_autosave_data.into_inner();
+    let body_format = parse_body_format(save_autosave_data.body_format)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr, authed_user.user.id, post_id).await?;
+    let autosave = PostAutosave {
+        post_id: post_id,
+        user_id: authed_user.user.id,
+        revision: save_autosave_data.base_revision + 1,
+        post_version: save_autosave_data.post_version,
+        subject: save_autosave_data.subject,
+        body: save_autosave_data.body,
+        body_format: body_format.map(|body_format| format!("{}", body_format)),
+        saved_at: chrono::Utc::now().naive_utc(),
+    };
+    let outcome = send_to_store(
+        app_state.get_ref(),
+        SaveAutosave {
+            autosave: autosave,
+            base_revision: save_autosave_data.base_revision,
+        },
+    )
+    .await?;
+    match outcome {
+        SaveOutcome::Saved(autosave) => Ok(web::Json(AutosaveData::from_autosave(&autosave))),
+        SaveOutcome::Conflict(current) => {
+            let current = current.map(|current| AutosaveData::from_autosave(&current));
+            let current =
+                serde_json::to_value(&current).map_err(|_| MyError::InternalServerError)?;
+            Err(MyError::AutosaveConflict(current))
+        }
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Discard the autosave of the caller for a Post", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/autosave/discard/{post_id}")]
+async fn discard_autosave(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<String, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user =
+        views::users::AuthedUser::from_bearer_token(db_actor_addr, auth_mgr_addr, bearer_auth)
+            .await?;
+    send_to_store(
+        app_state.get_ref(),
+        DiscardAutosave {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+        },
+    )
+    .await?;
+    Ok("Success!".to_string())
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+        ("If-Match" = Option<String>, header, description = "ETag of the version being edited, `post_version` of the autosave by default"),
+    ),
+    responses(
+        (status = 200, description = "Apply the autosave of the caller to the Post and discard it", body = PostData),
+        (status = 404, description = "Autosave Not Found"),
+        (status = 409, description = "Post was changed since the autosave was started, holds the current Post")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/autosave/promote/{post_id}")]
+async fn promote_autosave(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let autosave = stored_autosave(app_state.get_ref(), post_id, authed_user.user.id).await?;
+    let expected_version = expected_version(&req, Some(autosave.post_version))?;
+    let update = db_actor_addr
+        .send(services::posts::UpdatePost {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            changes: services::posts::PostChanges {
+                subject: autosave.subject,
+                body: autosave.body,
+                body_format: parse_body_format(autosave.body_format)?,
+                ..Default::default()
+            },
+            expected_version: expected_version,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let post = versioned_post_data(db_actor_addr, update, authed_user.user.clone()).await?;
+    send_to_store(
+        app_state.get_ref(),
+        DiscardAutosave {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+        },
+    )
+    .await?;
+    Ok(post_response(post))
+}
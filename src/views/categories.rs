@@ -0,0 +1,193 @@
+use crate::{
+    db::{
+        actor::DbActor,
+        models::categories::Category,
+        selectors::{self, categories::descendant_ids},
+        services,
+    },
+    errors::MyError,
+    slugify::slugify,
+    views, AppState,
+};
+use actix::Addr;
+use actix_web::{get, post, web};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError, Error::NotFound};
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+use utoipa::Component;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_categories)
+        .service(create_category)
+        .service(update_category)
+        .service(delete_category);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CategoryData {
+    id: i32,
+    name: String,
+    slug: String,
+    parent_id: Option<i32>,
+}
+
+impl CategoryData {
+    pub fn from_category(category: &Category) -> Self {
+        CategoryData {
+            id: category.id,
+            name: category.name.clone(),
+            slug: category.slug.clone(),
+            parent_id: category.parent_id,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CategoryInput {
+    name: String,
+    parent_id: Option<i32>,
+}
+
+fn category_error(err: diesel::result::Error) -> MyError {
+    match err {
+        NotFound => MyError::CategoryNotFound,
+        DatabaseError(DatabaseErrorKind::UniqueViolation, _) => MyError::CategoryAlreadyExists,
+        // parent category does not exist
+        DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => MyError::CategoryNotFound,
+        _ => MyError::DieselError(err),
+    }
+}
+
+async fn get_all_categories(db_actor_addr: Addr<DbActor>) -> Result<Vec<Category>, MyError> {
+    db_actor_addr
+        .send(selectors::categories::GetCategories)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get Categories, the tree is described by `parent_id`", body = [CategoryData])
+    )
+)]
+#[get("/categories/get_categories")]
+async fn get_categories(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<CategoryData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let categories = get_all_categories(db_actor_addr).await?;
+    Ok(web::Json(
+        categories
+            .iter()
+            .map(CategoryData::from_category)
+            .collect::<Vec<CategoryData>>(),
+    ))
+}
+
+#[utoipa::path(
+    request_body=CategoryInput,
+    responses(
+        (status = 200, description = "Create Category", body = CategoryData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/categories/create")]
+async fn create_category(
+    bearer_auth: BearerAuth,
+    category_input: web::Json<CategoryInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CategoryData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let category_input = category_input.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    if slugify(&category_input.name).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    let category = db_actor_addr
+        .send(services::categories::AddCategory {
+            name: category_input.name,
+            parent_id: category_input.parent_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(category_error)?;
+    Ok(web::Json(CategoryData::from_category(&category)))
+}
+
+#[utoipa::path(
+    params(
+        ("category_id" = i32, path, description = "Category database id"),
+    ),
+    request_body=CategoryInput,
+    responses(
+        (status = 200, description = "Rename or move Category", body = CategoryData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/categories/update/{category_id}")]
+async fn update_category(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    category_input: web::Json<CategoryInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CategoryData>, MyError> {
+    let category_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let category_input = category_input.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    if slugify(&category_input.name).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    if let Some(parent_id) = category_input.parent_id {
+        let categories = get_all_categories(db_actor_addr.clone()).await?;
+        if descendant_ids(&categories, category_id).contains(&parent_id) {
+            return Err(MyError::CategoryCycle);
+        }
+    }
+    let category = db_actor_addr
+        .send(services::categories::UpdateCategory {
+            category_id: category_id,
+            name: category_input.name,
+            parent_id: category_input.parent_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(category_error)?;
+    Ok(web::Json(CategoryData::from_category(&category)))
+}
+
+#[utoipa::path(
+    params(
+        ("category_id" = i32, path, description = "Category database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete Category, sub categories move up to its parent", body = CategoryData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/categories/delete/{category_id}")]
+async fn delete_category(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CategoryData>, MyError> {
+    let category_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let category = db_actor_addr
+        .send(services::categories::DeleteCategory {
+            category_id: category_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(category_error)?;
+    Ok(web::Json(CategoryData::from_category(&category)))
+}
@@ -0,0 +1,235 @@
+use crate::{
+    db::{
+        actor::DbActor,
+        models::collaborators::{CollaboratorRole, PostCollaborator},
+        selectors, services,
+    },
+    errors::MyError,
+    views::{
+        self,
+        posts::{collaborator_role, ensure_user_owns_post, post_lookup_error},
+    },
+    AppState,
+};
+use actix::Addr;
+use actix_web::{get, post, web};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+use utoipa::Component;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_post_collaborators)
+        .service(invite_post_collaborator)
+        .service(remove_post_collaborator);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CollaboratorData {
+    user_id: i32,
+    username: String,
+    /// One of `co-author`, `editor` or `viewer`
+    role: String,
+    invited_by: i32,
+    created_at: chrono::NaiveDateTime,
+}
+
+impl CollaboratorData {
+    fn from_collaborator(collaborator: &PostCollaborator, username: String) -> Self {
+        CollaboratorData {
+            user_id: collaborator.user_id,
+            username: username,
+            role: collaborator.role.clone(),
+            invited_by: collaborator.invited_by,
+            created_at: collaborator.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct InviteCollaborator {
+    username: String,
+    /// One of `co-author`, `editor` or `viewer`
+    role: String,
+}
+
+fn collaborator_lookup_error(err: diesel::result::Error) -> MyError {
+    match err {
+        diesel::result::Error::NotFound => MyError::CollaboratorNotFound,
+        _ => MyError::DieselError(err),
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Get the collaborators of a Post, visible to its owner, collaborators and admins", body = [CollaboratorData]),
+        (status = 404, description = "Post Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/posts/collaborators/{post_id}")]
+async fn get_post_collaborators(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<CollaboratorData>>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    if post.user_id != authed_user.user.id
+        && !authed_user.user.is_admin
+        && collaborator_role(db_actor_addr.clone(), post_id, authed_user.user.id)
+            .await?
+            .is_none()
+    {
+        return Err(MyError::YouDontOwnThisPost);
+    }
+    let collaborators = db_actor_addr
+        .send(selectors::collaborators::GetPostCollaborators { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        collaborators
+            .into_iter()
+            .map(|(collaborator, username)| {
+                CollaboratorData::from_collaborator(&collaborator, username)
+            })
+            .collect::<Vec<CollaboratorData>>(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=InviteCollaborator,
+    responses(
+        (status = 200, description = "Add a collaborator to a Post or change their role, only the owner can", body = CollaboratorData),
+        (status = 404, description = "Post Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/collaborators/invite/{post_id}")]
+async fn invite_post_collaborator(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    invite: web::Json<InviteCollaborator>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollaboratorData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let invite = invite.into_inner();
+    let role = invite
+        .role
+        .parse::<CollaboratorRole>()
+        .map_err(|_| MyError::InvalidCollaboratorRole)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let post = ensure_user_owns_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let invitee = db_actor_addr
+        .send(selectors::users::GetUserByUsername {
+            username: invite.username,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|_| MyError::UserDoesNotExists)?;
+    if invitee.id == post.user_id {
+        return Err(MyError::OwnerCannotCollaborate);
+    }
+    let collaborator = db_actor_addr
+        .send(services::collaborators::SetPostCollaborator {
+            post_id: post_id,
+            user_id: invitee.id,
+            role: role,
+            invited_by: authed_user.user.id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(CollaboratorData::from_collaborator(
+        &collaborator,
+        invitee.username,
+    )))
+}
+
+async fn remove_collaborator(
+    db_actor_addr: Addr<DbActor>,
+    post_id: i32,
+    user_id: i32,
+) -> Result<PostCollaborator, MyError> {
+    db_actor_addr
+        .send(services::collaborators::RemovePostCollaborator {
+            post_id: post_id,
+            user_id: user_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collaborator_lookup_error)
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+        ("user_id" = i32, path, description = "User database id of the collaborator"),
+    ),
+    responses(
+        (status = 200, description = "Remove a collaborator from a Post, done by the owner or by collaborators leaving", body = CollaboratorData),
+        (status = 404, description = "Post or Collaborator Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/collaborators/remove/{post_id}/{user_id}")]
+async fn remove_post_collaborator(
+    path: web::Path<(i32, i32)>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollaboratorData>, MyError> {
+    let (post_id, user_id) = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    if user_id != authed_user.user.id {
+        let _ = ensure_user_owns_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    }
+    let collaborator = remove_collaborator(db_actor_addr.clone(), post_id, user_id).await?;
+    let user = db_actor_addr
+        .send(selectors::users::GetUserByUserId { user_id: user_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(CollaboratorData::from_collaborator(
+        &collaborator,
+        user.username,
+    )))
+}
@@ -0,0 +1,592 @@
+use crate::{
+    db::{
+        actor::DbActor,
+        models::{
+            collections::{Collection, CollectionKind},
+            users::User,
+        },
+        selectors, services,
+    },
+    errors::MyError,
+    slugify::slugify,
+    views::{
+        self,
+        posts::{
+            can_view_post, ensure_user_can_edit_post_or_is_admin, is_visible_to, optional_user,
+            post_lookup_error, with_authors, with_translations, PostData,
+        },
+        translations::{negotiate_locales, LangQuery},
+    },
+    AppState,
+};
+use actix::Addr;
+use actix_web::{get, post, web, HttpRequest};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError, Error::NotFound};
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+use utoipa::{Component, IntoParams};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_collections)
+        .service(get_collection)
+        .service(create_collection)
+        .service(update_collection)
+        .service(pin_collection)
+        .service(delete_collection)
+        .service(add_collection_post)
+        .service(remove_collection_post)
+        .service(reorder_collection);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CollectionData {
+    id: i32,
+    name: String,
+    slug: String,
+    /// Either `series` or `collection`
+    kind: String,
+    description: String,
+    /// Posts of pinned collections come first on the home listing
+    pinned: bool,
+    owner_id: i32,
+}
+
+impl CollectionData {
+    pub fn from_collection(collection: &Collection) -> Self {
+        CollectionData {
+            id: collection.id,
+            name: collection.name.clone(),
+            slug: collection.slug.clone(),
+            kind: collection.kind.clone(),
+            description: collection.description.clone(),
+            pinned: collection.pinned,
+            owner_id: collection.user_id,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CollectionPostsData {
+    collection: CollectionData,
+    /// Posts of the collection the caller can see, in order
+    posts: Vec<PostData>,
+}
+
+fn collection_error(err: diesel::result::Error) -> MyError {
+    match err {
+        NotFound => MyError::CollectionNotFound,
+        DatabaseError(DatabaseErrorKind::UniqueViolation, _) => MyError::CollectionAlreadyExists,
+        // the post was deleted meanwhile
+        DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => MyError::PostNotFound,
+        _ => MyError::DieselError(err),
+    }
+}
+
+async fn find_collection(
+    db_actor_addr: Addr<DbActor>,
+    lookup: selectors::collections::GetCollection,
+) -> Result<(Collection, Vec<i32>), MyError> {
+    db_actor_addr
+        .send(lookup)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collection_error)
+}
+
+/// The owner of a collection and admins can manage it.
+async fn ensure_user_can_manage_collection(
+    db_actor_addr: Addr<DbActor>,
+    user: &User,
+    collection_id: i32,
+) -> Result<(Collection, Vec<i32>), MyError> {
+    let (collection, post_ids) = find_collection(
+        db_actor_addr,
+        selectors::collections::GetCollection::ById(collection_id),
+    )
+    .await?;
+    if collection.user_id == user.id || user.is_admin {
+        Ok((collection, post_ids))
+    } else {
+        Err(MyError::YouDontOwnThisCollection)
+    }
+}
+
+/// Posts of `post_ids` that `user_maybe` can see, in the same order.
+async fn visible_posts(
+    db_actor_addr: Addr<DbActor>,
+    post_ids: &[i32],
+    user_maybe: &Option<User>,
+) -> Result<Vec<PostData>, MyError> {
+    let posts = db_actor_addr
+        .send(selectors::posts::GetPostsDataByIds {
+            post_ids: post_ids.to_vec(),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let posts = post_ids
+        .iter()
+        .filter_map(|post_id| posts.iter().find(|post| post.id == *post_id))
+        .filter(|post| is_visible_to(post, user_maybe, false))
+        .map(|post| PostData::from_post_data(post, user_maybe.clone()))
+        .collect::<Vec<PostData>>();
+    with_authors(db_actor_addr, posts).await
+}
+
+/// `CollectionPostsData` of a collection right after `user` changed it.
+async fn changed_collection_data(
+    db_actor_addr: Addr<DbActor>,
+    collection: &Collection,
+    post_ids: &[i32],
+    user: User,
+) -> Result<CollectionPostsData, MyError> {
+    Ok(CollectionPostsData {
+        collection: CollectionData::from_collection(collection),
+        posts: visible_posts(db_actor_addr, post_ids, &Some(user)).await?,
+    })
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get Series and Collections", body = [CollectionData])
+    )
+)]
+#[get("/collections")]
+async fn get_collections(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<CollectionData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let collections = db_actor_addr
+        .send(selectors::collections::GetCollections)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        collections
+            .iter()
+            .map(CollectionData::from_collection)
+            .collect::<Vec<CollectionData>>(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("collection_slug" = String, path, description = "Series or Collection slug"),
+    ),
+    responses(
+        (status = 200, description = "Get a Series or Collection with its posts in order", body = CollectionPostsData)
+    )
+)]
+#[get("/collections/{collection_slug}")]
+async fn get_collection(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<LangQuery>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollectionPostsData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let (collection, post_ids) = find_collection(
+        db_actor_addr.clone(),
+        selectors::collections::GetCollection::BySlug(path.into_inner()),
+    )
+    .await?;
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr, bearer_auth).await;
+    let posts = visible_posts(db_actor_addr, &post_ids, &user_maybe).await?;
+    let locales = negotiate_locales(app_state.get_ref(), &req, &query.lang);
+    Ok(web::Json(CollectionPostsData {
+        collection: CollectionData::from_collection(&collection),
+        posts: with_translations(app_state.get_ref(), &locales, posts).await?,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CreateCollectionData {
+    name: String,
+    /// Either `series` or `collection`
+    kind: String,
+    description: Option<String>,
+}
+
+#[utoipa::path(
+    request_body=CreateCollectionData,
+    responses(
+        (status = 200, description = "Create a Series or Collection", body = CollectionPostsData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/collections/create")]
+async fn create_collection(
+    bearer_auth: BearerAuth,
+    collection_data: web::Json<CreateCollectionData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollectionPostsData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let collection_data = collection_data.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let kind = collection_data
+        .kind
+        .parse::<CollectionKind>()
+        .map_err(|_| MyError::InvalidCollectionKind)?;
+    if slugify(&collection_data.name).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    let collection = db_actor_addr
+        .send(services::collections::AddCollection {
+            name: collection_data.name,
+            kind: kind,
+            description: collection_data.description.unwrap_or_default(),
+            user_id: authed_user.user.id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collection_error)?;
+    Ok(web::Json(
+        changed_collection_data(db_actor_addr, &collection, &[], authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdateCollectionData {
+    name: String,
+    description: String,
+}
+
+#[utoipa::path(
+    params(
+        ("collection_id" = i32, path, description = "Series or Collection database id"),
+    ),
+    request_body=UpdateCollectionData,
+    responses(
+        (status = 200, description = "Rename a Series or Collection", body = CollectionPostsData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/collections/update/{collection_id}")]
+async fn update_collection(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    collection_data: web::Json<UpdateCollectionData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollectionPostsData>, MyError> {
+    let collection_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let collection_data = collection_data.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let (_, post_ids) =
+        ensure_user_can_manage_collection(db_actor_addr.clone(), &authed_user.user, collection_id)
+            .await?;
+    if slugify(&collection_data.name).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    let collection = db_actor_addr
+        .send(services::collections::UpdateCollection {
+            collection_id: collection_id,
+            name: collection_data.name,
+            description: collection_data.description,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collection_error)?;
+    Ok(web::Json(
+        changed_collection_data(db_actor_addr, &collection, &post_ids, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PinCollectionData {
+    pinned: bool,
+}
+
+#[utoipa::path(
+    params(
+        ("collection_id" = i32, path, description = "Series or Collection database id"),
+    ),
+    request_body=PinCollectionData,
+    responses(
+        (status = 200, description = "Pin the posts of a Series or Collection to the top of the home listing, or unpin them", body = CollectionPostsData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/collections/pin/{collection_id}")]
+async fn pin_collection(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    pin_data: web::Json<PinCollectionData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollectionPostsData>, MyError> {
+    let collection_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let authed_user = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let (_, post_ids) = find_collection(
+        db_actor_addr.clone(),
+        selectors::collections::GetCollection::ById(collection_id),
+    )
+    .await?;
+    let collection = db_actor_addr
+        .send(services::collections::SetCollectionPinned {
+            collection_id: collection_id,
+            pinned: pin_data.pinned,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collection_error)?;
+    Ok(web::Json(
+        changed_collection_data(db_actor_addr, &collection, &post_ids, authed_user.user).await?,
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("collection_id" = i32, path, description = "Series or Collection database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete a Series or Collection, its posts are kept", body = CollectionData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/collections/delete/{collection_id}")]
+async fn delete_collection(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollectionData>, MyError> {
+    let collection_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ =
+        ensure_user_can_manage_collection(db_actor_addr.clone(), &authed_user.user, collection_id)
+            .await?;
+    let collection = db_actor_addr
+        .send(services::collections::DeleteCollection {
+            collection_id: collection_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collection_error)?;
+    Ok(web::Json(CollectionData::from_collection(&collection)))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct AddCollectionPostData {
+    post_id: i32,
+    /// Index in the collection, the post goes last when not given
+    position: Option<usize>,
+}
+
+#[utoipa::path(
+    params(
+        ("collection_id" = i32, path, description = "Series or Collection database id"),
+    ),
+    request_body=AddCollectionPostData,
+    responses(
+        (status = 200, description = "Add a post to a Series or Collection, or move it if it already is in it. Only posts the caller can edit go into a series", body = CollectionPostsData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/collections/posts/add/{collection_id}")]
+async fn add_collection_post(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    post_data: web::Json<AddCollectionPostData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollectionPostsData>, MyError> {
+    let collection_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let post_data = post_data.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let (collection, _) =
+        ensure_user_can_manage_collection(db_actor_addr.clone(), &authed_user.user, collection_id)
+            .await?;
+    match collection.kind.parse::<CollectionKind>() {
+        Ok(CollectionKind::Series) => {
+            let _ = ensure_user_can_edit_post_or_is_admin(
+                db_actor_addr.clone(),
+                &authed_user.user,
+                post_data.post_id,
+            )
+            .await?;
+            let series = db_actor_addr
+                .send(selectors::collections::GetPostSeries {
+                    post_id: post_data.post_id,
+                })
+                .await
+                .map_err(|_| MyError::InternalServerError)?
+                .map_err(|err| MyError::DieselError(err))?;
+            if let Some(series) = series {
+                if series.id != collection.id {
+                    return Err(MyError::AlreadyInSeries);
+                }
+            }
+        }
+        _ => {
+            let post = db_actor_addr
+                .send(selectors::posts::GetPostDataById {
+                    post_id: post_data.post_id,
+                })
+                .await
+                .map_err(|_| MyError::InternalServerError)?
+                .map_err(post_lookup_error)?;
+            let user_maybe = Some(authed_user.user.clone());
+            if !can_view_post(db_actor_addr.clone(), &post, &user_maybe, false).await? {
+                return Err(MyError::PostNotFound);
+            }
+        }
+    }
+    let post_ids = db_actor_addr
+        .send(services::collections::AddCollectionPost {
+            collection_id: collection.id,
+            post_id: post_data.post_id,
+            position: post_data.position.unwrap_or(usize::MAX),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collection_error)?;
+    Ok(web::Json(
+        changed_collection_data(db_actor_addr, &collection, &post_ids, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct RemoveCollectionPostData {
+    post_id: i32,
+}
+
+#[utoipa::path(
+    params(
+        ("collection_id" = i32, path, description = "Series or Collection database id"),
+    ),
+    request_body=RemoveCollectionPostData,
+    responses(
+        (status = 200, description = "Remove a post from a Series or Collection", body = CollectionPostsData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/collections/posts/remove/{collection_id}")]
+async fn remove_collection_post(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    post_data: web::Json<RemoveCollectionPostData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollectionPostsData>, MyError> {
+    let collection_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let (collection, _) =
+        ensure_user_can_manage_collection(db_actor_addr.clone(), &authed_user.user, collection_id)
+            .await?;
+    let post_ids = db_actor_addr
+        .send(services::collections::RemoveCollectionPost {
+            collection_id: collection.id,
+            post_id: post_data.post_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collection_error)?;
+    Ok(web::Json(
+        changed_collection_data(db_actor_addr, &collection, &post_ids, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct ReorderCollectionData {
+    /// Every post of the collection, once, in the new order
+    post_ids: Vec<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("collection_id" = i32, path, description = "Series or Collection database id"),
+    ),
+    request_body=ReorderCollectionData,
+    responses(
+        (status = 200, description = "Reorder the posts of a Series or Collection", body = CollectionPostsData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/collections/reorder/{collection_id}")]
+async fn reorder_collection(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    order_data: web::Json<ReorderCollectionData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CollectionPostsData>, MyError> {
+    let collection_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let order_data = order_data.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let (collection, current) =
+        ensure_user_can_manage_collection(db_actor_addr.clone(), &authed_user.user, collection_id)
+            .await?;
+    let mut wanted = order_data.post_ids.clone();
+    wanted.sort();
+    wanted.dedup();
+    let mut current_sorted = current.clone();
+    current_sorted.sort();
+    if wanted.len() != order_data.post_ids.len() || wanted != current_sorted {
+        return Err(MyError::InvalidCollectionOrder);
+    }
+    let post_ids = db_actor_addr
+        .send(services::collections::ReorderCollection {
+            collection_id: collection.id,
+            post_ids: order_data.post_ids,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(collection_error)?;
+    Ok(web::Json(
+        changed_collection_data(db_actor_addr, &collection, &post_ids, authed_user.user).await?,
+    ))
+}
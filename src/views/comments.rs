@@ -0,0 +1,532 @@
+use crate::{
+    db::{
+        actor::DbActor,
+        models::{
+            comments::{Comment, CommentStatus},
+            posts::{Post, PublishStatus},
+            users::User,
+        },
+        selectors::{self, comments::CommentPage},
+        services,
+    },
+    errors::MyError,
+    views::{
+        self,
+        posts::{
+            can_view_post, ensure_user_can_edit_post_or_is_admin, has_post_access, is_visible_to,
+            not_visible_error, optional_user, post_lookup_error,
+        },
+    },
+    AppState,
+};
+use actix::Addr;
+use actix_web::{get, post, web, HttpRequest};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::vec::Vec;
+use utoipa::{Component, IntoParams};
+
+const MAX_COMMENT_LENGTH: usize = 10_000;
+const DEFAULT_PER_PAGE: i64 = 20;
+const MAX_PER_PAGE: i64 = 100;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_post_comments)
+        .service(create_comment)
+        .service(update_comment)
+        .service(delete_comment)
+        .service(moderate_comment)
+        .service(get_moderation_queue);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CommentData {
+    id: i32,
+    post_id: i32,
+    parent_id: Option<i32>,
+    user_id: i32,
+    author_name: String,
+    body: String,
+    status: String,
+    created_at: chrono::NaiveDateTime,
+    updated_at: chrono::NaiveDateTime,
+    replies: Vec<CommentData>,
+}
+
+impl CommentData {
+    pub fn from_comment(comment: &Comment, author_name: String) -> Self {
+        CommentData {
+            id: comment.id,
+            post_id: comment.post_id,
+            parent_id: comment.parent_id,
+            user_id: comment.user_id,
+            author_name: author_name,
+            body: comment.body.clone(),
+            status: comment.status.clone(),
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+            replies: vec![],
+        }
+    }
+
+    fn from_thread(
+        comment: &Comment,
+        replies_by_parent: &HashMap<i32, Vec<&Comment>>,
+        author_names: &HashMap<i32, String>,
+    ) -> Self {
+        let mut comment_data = Self::from_comment(comment, author_name(author_names, comment));
+        comment_data.replies = replies_by_parent
+            .get(&comment.id)
+            .map(|replies| {
+                replies
+                    .iter()
+                    .map(|reply| Self::from_thread(reply, replies_by_parent, author_names))
+                    .collect()
+            })
+            .unwrap_or_default();
+        comment_data
+    }
+}
+
+fn author_name(author_names: &HashMap<i32, String>, comment: &Comment) -> String {
+    author_names
+        .get(&comment.user_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Nests the replies of a page under their parents. Replies whose parent is
+/// not visible to the viewer are left out.
+fn comment_threads(comment_page: &CommentPage) -> Vec<CommentData> {
+    let mut replies_by_parent: HashMap<i32, Vec<&Comment>> = HashMap::new();
+    for comment in comment_page.comments.iter() {
+        if let Some(parent_id) = comment.parent_id {
+            replies_by_parent
+                .entry(parent_id)
+                .or_default()
+                .push(comment);
+        }
+    }
+    comment_page
+        .comments
+        .iter()
+        .filter(|comment| comment.parent_id.is_none())
+        .map(|comment| {
+            CommentData::from_thread(comment, &replies_by_parent, &comment_page.author_names)
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CommentPageData {
+    comments: Vec<CommentData>,
+    page: i64,
+    per_page: i64,
+    /// Total number of (top level) comments across all pages
+    total: i64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct PageQuery {
+    /// Page number, starts at 1
+    page: Option<i64>,
+    /// Comments per page, top level ones for threads, at most 100
+    per_page: Option<i64>,
+}
+
+impl PageQuery {
+    /// `(page, per_page)`, pages start at 1
+    fn page(&self) -> (i64, i64) {
+        (
+            self.page.unwrap_or(1).max(1),
+            self.per_page
+                .unwrap_or(DEFAULT_PER_PAGE)
+                .clamp(1, MAX_PER_PAGE),
+        )
+    }
+}
+
+fn comment_lookup_error(err: diesel::result::Error) -> MyError {
+    match err {
+        diesel::result::Error::NotFound => MyError::CommentNotFound,
+        _ => MyError::DieselError(err),
+    }
+}
+
+fn validate_comment_body(body: &str) -> Result<(), MyError> {
+    if body.trim().is_empty() {
+        Err(MyError::InvalidComment("comment is empty".to_string()))
+    } else if body.chars().count() > MAX_COMMENT_LENGTH {
+        Err(MyError::InvalidComment(format!(
+            "comment is longer than {} characters",
+            MAX_COMMENT_LENGTH
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+async fn get_comment(db_actor_addr: Addr<DbActor>, comment_id: i32) -> Result<Comment, MyError> {
+    db_actor_addr
+        .send(selectors::comments::GetCommentById {
+            comment_id: comment_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(comment_lookup_error)
+}
+
+async fn comment_data(
+    db_actor_addr: Addr<DbActor>,
+    comment: &Comment,
+) -> Result<CommentData, MyError> {
+    let author = db_actor_addr
+        .send(selectors::users::GetUserByUserId {
+            user_id: comment.user_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(CommentData::from_comment(comment, author.username))
+}
+
+/// Whoever can edit a post (and admins) moderates its comments.
+async fn ensure_user_moderates_comment(
+    db_actor_addr: Addr<DbActor>,
+    user: &User,
+    comment: &Comment,
+) -> Result<Post, MyError> {
+    ensure_user_can_edit_post_or_is_admin(db_actor_addr, user, comment.post_id).await
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Get the approved comments of a Post (and the callers own ones), threaded", body = CommentPageData),
+        (status = 404, description = "Post Not Found")
+    )
+)]
+#[get("/comments/post/{post_id}")]
+async fn get_post_comments(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    query: web::Query<PageQuery>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CommentPageData>, MyError> {
+    let post_id = path.into_inner();
+    let (page, per_page) = query.into_inner().page();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr.clone(), bearer_auth).await;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    if !can_view_post(db_actor_addr.clone(), &post, &user_maybe, has_access).await? {
+        return Err(not_visible_error(&post));
+    }
+    let comment_page = db_actor_addr
+        .send(selectors::comments::GetPostComments {
+            post_id: post_id,
+            viewer_id: user_maybe.map(|user| user.id),
+            page: page,
+            per_page: per_page,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(CommentPageData {
+        comments: comment_threads(&comment_page),
+        page: page,
+        per_page: per_page,
+        total: comment_page.total,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CreateCommentData {
+    body: String,
+    /// Comment to reply to
+    parent_id: Option<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=CreateCommentData,
+    responses(
+        (status = 200, description = "Comment on a published Post, comments of new users are held for moderation", body = CommentData),
+        (status = 404, description = "Post or parent Comment Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/comments/create/{post_id}")]
+async fn create_comment(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    comment_data: web::Json<CreateCommentData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CommentData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let comment_data = comment_data.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr.clone(),
+        bearer_auth,
+    )
+    .await?;
+    validate_comment_body(&comment_data.body)?;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    let user_maybe = Some(authed_user.user.clone());
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    if !is_visible_to(&post, &user_maybe, has_access) {
+        return Err(not_visible_error(&post));
+    }
+    if post.status != format!("{}", PublishStatus::Published) || !post.comments_enabled {
+        return Err(MyError::CommentsClosed);
+    }
+    let comment = db_actor_addr
+        .send(services::comments::AddComment {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            parent_id: comment_data.parent_id,
+            body: comment_data.body,
+            skip_moderation: authed_user.user.is_admin || post.user_id == authed_user.user.id,
+            trusted_after_approved: app_state.get_ref().comment_trusted_after_approved,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(comment_lookup_error)?;
+    Ok(web::Json(CommentData::from_comment(
+        &comment,
+        authed_user.user.username,
+    )))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdateCommentData {
+    body: String,
+}
+
+#[utoipa::path(
+    params(
+        ("comment_id" = i32, path, description = "Comment database id"),
+    ),
+    request_body=UpdateCommentData,
+    responses(
+        (status = 200, description = "Edit own Comment, approved comments go back to moderation unless their author is trusted", body = CommentData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/comments/update/{comment_id}")]
+async fn update_comment(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    comment_data: web::Json<UpdateCommentData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CommentData>, MyError> {
+    let comment_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let comment_data = comment_data.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    validate_comment_body(&comment_data.body)?;
+    let comment = get_comment(db_actor_addr.clone(), comment_id).await?;
+    if comment.user_id != authed_user.user.id {
+        return Err(MyError::YouDontOwnThisComment);
+    }
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostById {
+            post_id: comment.post_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    let comment = db_actor_addr
+        .send(services::comments::UpdateCommentBody {
+            comment_id: comment_id,
+            new_body: comment_data.body,
+            skip_moderation: authed_user.user.is_admin || post.user_id == authed_user.user.id,
+            trusted_after_approved: app_state.get_ref().comment_trusted_after_approved,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(comment_lookup_error)?;
+    Ok(web::Json(CommentData::from_comment(
+        &comment,
+        authed_user.user.username,
+    )))
+}
+
+#[utoipa::path(
+    params(
+        ("comment_id" = i32, path, description = "Comment database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete a Comment and its replies, allowed for its author, the post owner and admins", body = CommentData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/comments/delete/{comment_id}")]
+async fn delete_comment(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CommentData>, MyError> {
+    let comment_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let comment = get_comment(db_actor_addr.clone(), comment_id).await?;
+    if comment.user_id != authed_user.user.id {
+        let _ = ensure_user_moderates_comment(db_actor_addr.clone(), &authed_user.user, &comment)
+            .await?;
+    }
+    let comment = db_actor_addr
+        .send(services::comments::DeleteComment {
+            comment_id: comment_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(comment_lookup_error)?;
+    Ok(web::Json(comment_data(db_actor_addr, &comment).await?))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    Approve,
+    Hide,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct ModerateCommentData {
+    /// `approve` or `hide`
+    action: ModerationAction,
+}
+
+#[utoipa::path(
+    params(
+        ("comment_id" = i32, path, description = "Comment database id"),
+    ),
+    request_body=ModerateCommentData,
+    responses(
+        (status = 200, description = "Approve or hide a Comment, allowed for the post owner and admins", body = CommentData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/comments/moderate/{comment_id}")]
+async fn moderate_comment(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    moderate_comment_data: web::Json<ModerateCommentData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CommentData>, MyError> {
+    let comment_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let comment = get_comment(db_actor_addr.clone(), comment_id).await?;
+    let _ =
+        ensure_user_moderates_comment(db_actor_addr.clone(), &authed_user.user, &comment).await?;
+    let new_status = match moderate_comment_data.into_inner().action {
+        ModerationAction::Approve => CommentStatus::Approved,
+        ModerationAction::Hide => CommentStatus::Hidden,
+    };
+    let comment = db_actor_addr
+        .send(services::comments::ModerateComment {
+            comment_id: comment_id,
+            new_status: new_status,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(comment_lookup_error)?;
+    Ok(web::Json(comment_data(db_actor_addr, &comment).await?))
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Pending comments on the callers posts, or on all posts for admins", body = CommentPageData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/comments/moderation_queue")]
+async fn get_moderation_queue(
+    query: web::Query<PageQuery>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<CommentPageData>, MyError> {
+    let (page, per_page) = query.into_inner().page();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let comment_page = db_actor_addr
+        .send(selectors::comments::GetModerationQueue {
+            post_owner_id: if authed_user.user.is_admin {
+                None
+            } else {
+                Some(authed_user.user.id)
+            },
+            page: page,
+            per_page: per_page,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(CommentPageData {
+        comments: comment_page
+            .comments
+            .iter()
+            .map(|comment| {
+                CommentData::from_comment(comment, author_name(&comment_page.author_names, comment))
+            })
+            .collect::<Vec<CommentData>>(),
+        page: page,
+        per_page: per_page,
+        total: comment_page.total,
+    }))
+}
@@ -0,0 +1,478 @@
+use crate::{
+    content_types::{FieldSchema, FieldType},
+    db::{actor::DbActor, models::content_types::ContentType, selectors, services},
+    errors::MyError,
+    slugify::slugify,
+    views::{
+        self,
+        posts::{
+            changed_post_data, ensure_user_can_edit_post, expected_version, optional_user,
+            post_response, versioned_post_data, with_authors, with_translations, PostData,
+        },
+        translations::negotiate_locales,
+    },
+    AppState,
+};
+use actix::Addr;
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError, Error::NotFound};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::vec::Vec;
+use utoipa::{Component, IntoParams};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_content_types)
+        .service(get_content_type)
+        .service(create_content_type)
+        .service(update_content_type)
+        .service(delete_content_type)
+        .service(get_entries)
+        .service(create_entry)
+        .service(update_entry);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct ContentTypeData {
+    id: i32,
+    name: String,
+    slug: String,
+    /// e.g. `{"fields": [{"name": "starts_at", "type": "date", "required": true}]}`
+    field_schema: serde_json::Value,
+}
+
+impl ContentTypeData {
+    pub fn from_content_type(content_type: &ContentType) -> Self {
+        ContentTypeData {
+            id: content_type.id,
+            name: content_type.name.clone(),
+            slug: content_type.slug.clone(),
+            field_schema: content_type.field_schema.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct ContentTypeInput {
+    name: String,
+    /// A list of `fields`, each with a `name`, a `type` (one of `string`, `rich_text`,
+    /// `number`, `date`, `reference`, `media` or `enum`) and optionally `required`,
+    /// `max_length` for strings and `values` for enums
+    field_schema: serde_json::Value,
+}
+
+impl ContentTypeInput {
+    /// The schema as it is stored, with defaults filled in.
+    fn checked_schema(&self) -> Result<serde_json::Value, MyError> {
+        if slugify(&self.name).is_empty() {
+            return Err(MyError::InvalidName);
+        }
+        let schema = FieldSchema::parse(self.field_schema.clone())
+            .map_err(|err| MyError::InvalidContentType(err))?;
+        serde_json::to_value(&schema).map_err(|_| MyError::InternalServerError)
+    }
+}
+
+fn content_type_error(err: diesel::result::Error) -> MyError {
+    match err {
+        NotFound => MyError::ContentTypeNotFound,
+        DatabaseError(DatabaseErrorKind::UniqueViolation, _) => MyError::ContentTypeAlreadyExists,
+        // entries still point to it
+        DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => MyError::ContentTypeInUse,
+        _ => MyError::DieselError(err),
+    }
+}
+
+async fn find_content_type(
+    db_actor_addr: Addr<DbActor>,
+    lookup: selectors::content_types::GetContentType,
+) -> Result<ContentType, MyError> {
+    db_actor_addr
+        .send(lookup)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(content_type_error)
+}
+
+fn field_schema(content_type: &ContentType) -> Result<FieldSchema, MyError> {
+    FieldSchema::parse(content_type.field_schema.clone())
+        .map_err(|err| MyError::InvalidContentType(err))
+}
+
+/// Validates `fields` of an entry against `schema`, referenced posts and media must exist.
+async fn validate_fields(
+    db_actor_addr: Addr<DbActor>,
+    schema: &FieldSchema,
+    fields: &serde_json::Value,
+) -> Result<serde_json::Value, MyError> {
+    let entry = schema
+        .validate_entry(fields)
+        .map_err(|invalid| MyError::InvalidFields(invalid))?;
+    if entry.post_ids.is_empty() && entry.media_ids.is_empty() {
+        return Ok(entry.fields);
+    }
+    let (missing_post_ids, missing_media_ids) = db_actor_addr
+        .send(selectors::content_types::GetMissingReferences {
+            post_ids: entry.post_ids.clone(),
+            media_ids: entry.media_ids.clone(),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let mut invalid = BTreeMap::new();
+    for field in schema.fields.iter() {
+        let id = match entry
+            .fields
+            .get(&field.name)
+            .and_then(|value| value.as_i64())
+        {
+            Some(id) => id as i32,
+            None => continue,
+        };
+        if missing_post_ids.contains(&id) && field.field_type == FieldType::Reference {
+            invalid.insert(field.name.clone(), "post does not exist".to_string());
+        }
+        if missing_media_ids.contains(&id) && field.field_type == FieldType::Media {
+            invalid.insert(field.name.clone(), "media does not exist".to_string());
+        }
+    }
+    if !invalid.is_empty() {
+        return Err(MyError::InvalidFields(invalid));
+    }
+    Ok(entry.fields)
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get Content Types", body = [ContentTypeData])
+    )
+)]
+#[get("/content_types")]
+async fn get_content_types(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<ContentTypeData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let content_types = db_actor_addr
+        .send(selectors::content_types::GetContentTypes)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        content_types
+            .iter()
+            .map(ContentTypeData::from_content_type)
+            .collect::<Vec<ContentTypeData>>(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("content_type_slug" = String, path, description = "Content Type slug"),
+    ),
+    responses(
+        (status = 200, description = "Get Content Type", body = ContentTypeData)
+    )
+)]
+#[get("/content_types/{content_type_slug}")]
+async fn get_content_type(
+    path: web::Path<String>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<ContentTypeData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let content_type = find_content_type(
+        db_actor_addr,
+        selectors::content_types::GetContentType::BySlug(path.into_inner()),
+    )
+    .await?;
+    Ok(web::Json(ContentTypeData::from_content_type(&content_type)))
+}
+
+#[utoipa::path(
+    request_body=ContentTypeInput,
+    responses(
+        (status = 200, description = "Create Content Type", body = ContentTypeData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/content_types/create")]
+async fn create_content_type(
+    bearer_auth: BearerAuth,
+    content_type_input: web::Json<ContentTypeInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<ContentTypeData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let content_type_input = content_type_input.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let field_schema = content_type_input.checked_schema()?;
+    let content_type = db_actor_addr
+        .send(services::content_types::AddContentType {
+            name: content_type_input.name,
+            field_schema: field_schema,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(content_type_error)?;
+    Ok(web::Json(ContentTypeData::from_content_type(&content_type)))
+}
+
+#[utoipa::path(
+    params(
+        ("content_type_id" = i32, path, description = "Content Type database id"),
+    ),
+    request_body=ContentTypeInput,
+    responses(
+        (status = 200, description = "Rename Content Type or change its fields, existing entries are validated again on their next update", body = ContentTypeData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/content_types/update/{content_type_id}")]
+async fn update_content_type(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    content_type_input: web::Json<ContentTypeInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<ContentTypeData>, MyError> {
+    let content_type_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let content_type_input = content_type_input.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let field_schema = content_type_input.checked_schema()?;
+    let content_type = db_actor_addr
+        .send(services::content_types::UpdateContentType {
+            content_type_id: content_type_id,
+            name: content_type_input.name,
+            field_schema: field_schema,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(content_type_error)?;
+    Ok(web::Json(ContentTypeData::from_content_type(&content_type)))
+}
+
+#[utoipa::path(
+    params(
+        ("content_type_id" = i32, path, description = "Content Type database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete Content Type, only once it has no entries left", body = ContentTypeData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/content_types/delete/{content_type_id}")]
+async fn delete_content_type(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<ContentTypeData>, MyError> {
+    let content_type_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let content_type = db_actor_addr
+        .send(services::content_types::DeleteContentType {
+            content_type_id: content_type_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(content_type_error)?;
+    Ok(web::Json(ContentTypeData::from_content_type(&content_type)))
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetEntriesQuery {
+    /// Locale to show entries in, `Accept-Language` is used otherwise
+    lang: Option<String>,
+}
+
+#[utoipa::path(
+    params(
+        ("content_type_slug" = String, path, description = "Content Type slug"),
+    ),
+    responses(
+        (status = 200, description = "Get Published Entries of a Content Type", body = [PostData])
+    )
+)]
+#[get("/entries/{content_type_slug}")]
+async fn get_entries(
+    req: HttpRequest,
+    path: web::Path<String>,
+    bearer_auth: Option<BearerAuth>,
+    query: web::Query<GetEntriesQuery>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<PostData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let content_type = find_content_type(
+        db_actor_addr.clone(),
+        selectors::content_types::GetContentType::BySlug(path.into_inner()),
+    )
+    .await?;
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr, bearer_auth).await;
+    let entries = db_actor_addr
+        .send(selectors::posts::GetPosts::GetPublishedPosts(
+            selectors::posts::PostFilter {
+                tag: None,
+                category: None,
+                content_type_id: Some(content_type.id),
+                include_members_only: user_maybe.is_some(),
+            },
+        ))
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let entries = entries
+        .iter()
+        .map(|entry| PostData::from_post_data(entry, user_maybe.clone()))
+        .collect::<Vec<PostData>>();
+    let entries = with_authors(db_actor_addr, entries).await?;
+    let locales = negotiate_locales(app_state.get_ref(), &req, &query.lang);
+    Ok(web::Json(
+        with_translations(app_state.get_ref(), &locales, entries).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CreateEntryData {
+    subject: String,
+    /// Values by field name, checked against the schema of the content type
+    fields: serde_json::Value,
+    slug: Option<String>,
+}
+
+#[utoipa::path(
+    params(
+        ("content_type_slug" = String, path, description = "Content Type slug"),
+    ),
+    request_body=CreateEntryData,
+    responses(
+        (status = 200, description = "Create an Entry, it is published like any post", body = PostData),
+        (status = 400, description = "Some fields are invalid, `fields` holds what is wrong with each")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/entries/create/{content_type_slug}")]
+async fn create_entry(
+    path: web::Path<String>,
+    bearer_auth: BearerAuth,
+    entry_data: web::Json<CreateEntryData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let entry_data = entry_data.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let content_type = find_content_type(
+        db_actor_addr.clone(),
+        selectors::content_types::GetContentType::BySlug(path.into_inner()),
+    )
+    .await?;
+    let fields = validate_fields(
+        db_actor_addr.clone(),
+        &field_schema(&content_type)?,
+        &entry_data.fields,
+    )
+    .await?;
+    let entry = db_actor_addr
+        .send(services::posts::AddEntry {
+            content_type_id: content_type.id,
+            subject: entry_data.subject,
+            fields: fields,
+            user_id: authed_user.user.id,
+            slug: entry_data.slug,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        changed_post_data(db_actor_addr, &entry, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdateEntryData {
+    /// Replaces all fields of the entry
+    fields: serde_json::Value,
+    subject: Option<String>,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id of the entry"),
+    ),
+    request_body=UpdateEntryData,
+    responses(
+        (status = 200, description = "Update the fields of an Entry", body = PostData),
+        (status = 400, description = "Some fields are invalid, `fields` holds what is wrong with each"),
+        (status = 409, description = "Entry was changed since the expected version, holds the current Entry"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/entries/update/{post_id}")]
+async fn update_entry(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    entry_data: web::Json<UpdateEntryData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let entry_data = entry_data.into_inner();
+    let expected_version = expected_version(&req, entry_data.expected_version)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let entry =
+        ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let content_type_id = entry.content_type_id.ok_or(MyError::NotAnEntry)?;
+    let content_type = find_content_type(
+        db_actor_addr.clone(),
+        selectors::content_types::GetContentType::ById(content_type_id),
+    )
+    .await?;
+    let fields = validate_fields(
+        db_actor_addr.clone(),
+        &field_schema(&content_type)?,
+        &entry_data.fields,
+    )
+    .await?;
+    let update = db_actor_addr
+        .send(services::posts::UpdatePost {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            changes: services::posts::PostChanges {
+                subject: entry_data.subject,
+                fields: Some(fields),
+                ..Default::default()
+            },
+            expected_version: expected_version,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
+}
@@ -0,0 +1,216 @@
+use crate::{
+    db::selectors::{
+        self,
+        categories::descendant_ids,
+        feeds::{FeedFilter, FeedPost},
+    },
+    errors::MyError,
+    feeds::{Feed, FeedEntry, FeedFormat},
+    AppState,
+};
+use actix_web::{
+    get,
+    http::header::{self, Header},
+    web, HttpRequest, HttpResponse,
+};
+use diesel::result::Error::NotFound;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::vec::Vec;
+
+/// Number of posts in a feed.
+const FEED_SIZE: i64 = 50;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_feed).service(get_scoped_feed);
+}
+
+fn feed_format(feed_file: &str) -> Result<FeedFormat, MyError> {
+    feed_file
+        .parse::<FeedFormat>()
+        .map_err(|_| MyError::FeedNotFound)
+}
+
+fn feed_entry(app_state: &AppState, post: FeedPost) -> FeedEntry {
+    FeedEntry {
+        title: post.subject,
+        url: format!("{}/posts/{}", app_state.site_url, post.slug),
+        author: post.owner_name,
+        // posts made of content blocks have an empty body and the other way around
+        content_html: format!("{}{}", post.rendered_body, post.content_html),
+        published_at: post.published_at,
+        updated_at: post.updated_at,
+    }
+}
+
+/// Whether the copy the client already has, going by `If-None-Match` or else
+/// `If-Modified-Since`, is still the current one.
+fn is_fresh(
+    req: &HttpRequest,
+    etag: &header::EntityTag,
+    last_modified: Option<SystemTime>,
+) -> bool {
+    if let Ok(if_none_match) = header::IfNoneMatch::parse(req) {
+        return match if_none_match {
+            header::IfNoneMatch::Any => true,
+            header::IfNoneMatch::Items(tags) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        };
+    }
+    match (header::IfModifiedSince::parse(req), last_modified) {
+        (Ok(header::IfModifiedSince(since)), Some(last_modified)) => {
+            last_modified <= SystemTime::from(since)
+        }
+        _ => false,
+    }
+}
+
+/// Renders the feed of the posts matching `filter`, or answers `304` when the client is up
+/// to date.
+async fn feed_response(
+    req: &HttpRequest,
+    app_state: &AppState,
+    format: FeedFormat,
+    filter: FeedFilter,
+    title: String,
+) -> Result<HttpResponse, MyError> {
+    let posts = app_state
+        .db_actor_addr
+        .send(selectors::feeds::GetFeedPosts {
+            filter: filter,
+            limit: FEED_SIZE,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let feed = Feed {
+        title: title,
+        home_url: app_state.site_url.clone(),
+        feed_url: format!("{}{}", app_state.site_url, req.path()),
+        entries: posts
+            .into_iter()
+            .map(|post| feed_entry(app_state, post))
+            .collect::<Vec<FeedEntry>>(),
+    };
+    let body = feed.render(format);
+    let etag = header::EntityTag::new_strong(format!("{:x}", Sha256::digest(body.as_bytes())));
+    // http dates have no fractions of a second
+    let last_modified = feed
+        .updated_at()
+        .map(|updated_at| UNIX_EPOCH + Duration::from_secs(updated_at.timestamp().max(0) as u64));
+
+    let fresh = is_fresh(req, &etag, last_modified);
+
+    let mut response = if fresh {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header(header::ETag(etag))
+        .insert_header((header::CACHE_CONTROL, "public, no-cache"));
+    if let Some(last_modified) = last_modified {
+        response.insert_header(header::LastModified(last_modified.into()));
+    }
+    if fresh {
+        Ok(response.finish())
+    } else {
+        Ok(response.content_type(format.content_type()).body(body))
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("feed_file" = String, path, description = "`rss.xml` for RSS 2.0, `atom.xml` for Atom or `feed.json` for JSON Feed"),
+        ("If-None-Match" = Option<String>, header, description = "ETag of the copy the client has"),
+        ("If-Modified-Since" = Option<String>, header, description = "Last-Modified of the copy the client has"),
+    ),
+    responses(
+        (status = 200, description = "Feed of the latest published public posts"),
+        (status = 304, description = "The copy the client has is up to date"),
+        (status = 404, description = "Feed Not Found")
+    )
+)]
+#[get("/feeds/{feed_file}")]
+async fn get_feed(
+    req: HttpRequest,
+    path: web::Path<String>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let format = feed_format(&path.into_inner())?;
+    let title = app_state.site_title.clone();
+    feed_response(&req, app_state.get_ref(), format, FeedFilter::All, title).await
+}
+
+#[utoipa::path(
+    params(
+        ("scope" = String, path, description = "One of `authors`, `tags` or `categories`"),
+        ("slug" = String, path, description = "Username of the author, or slug of the tag or category"),
+        ("feed_file" = String, path, description = "`rss.xml` for RSS 2.0, `atom.xml` for Atom or `feed.json` for JSON Feed"),
+        ("If-None-Match" = Option<String>, header, description = "ETag of the copy the client has"),
+        ("If-Modified-Since" = Option<String>, header, description = "Last-Modified of the copy the client has"),
+    ),
+    responses(
+        (status = 200, description = "Feed of the latest published public posts of an author, with a tag, or in a category or its sub categories"),
+        (status = 304, description = "The copy the client has is up to date"),
+        (status = 404, description = "Feed Not Found")
+    )
+)]
+#[get("/feeds/{scope}/{slug}/{feed_file}")]
+async fn get_scoped_feed(
+    req: HttpRequest,
+    path: web::Path<(String, String, String)>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let (scope, slug, feed_file) = path.into_inner();
+    let format = feed_format(&feed_file)?;
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let site_title = &app_state.site_title;
+    let (filter, title) = match scope.as_str() {
+        "authors" => {
+            let author = db_actor_addr
+                .send(selectors::users::GetUserByUsername { username: slug })
+                .await
+                .map_err(|_| MyError::InternalServerError)?
+                .map_err(|err| match err {
+                    NotFound => MyError::FeedNotFound,
+                    _ => MyError::DieselError(err),
+                })?;
+            (
+                FeedFilter::Author(author.id),
+                format!("{}: posts by {}", site_title, author.username),
+            )
+        }
+        "tags" => {
+            let tags = db_actor_addr
+                .send(selectors::tags::GetTags)
+                .await
+                .map_err(|_| MyError::InternalServerError)?
+                .map_err(|err| MyError::DieselError(err))?;
+            let tag = tags
+                .into_iter()
+                .find(|tag| tag.slug == slug)
+                .ok_or(MyError::FeedNotFound)?;
+            (
+                FeedFilter::Tag(tag.id),
+                format!("{}: posts tagged {}", site_title, tag.name),
+            )
+        }
+        "categories" => {
+            let categories = db_actor_addr
+                .send(selectors::categories::GetCategories)
+                .await
+                .map_err(|_| MyError::InternalServerError)?
+                .map_err(|err| MyError::DieselError(err))?;
+            let category = categories
+                .iter()
+                .find(|category| category.slug == slug)
+                .ok_or(MyError::FeedNotFound)?;
+            (
+                FeedFilter::Categories(descendant_ids(&categories, category.id)),
+                format!("{}: posts in {}", site_title, category.name),
+            )
+        }
+        _ => return Err(MyError::FeedNotFound),
+    };
+    feed_response(&req, app_state.get_ref(), format, filter, title).await
+}
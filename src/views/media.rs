@@ -0,0 +1,484 @@
+use crate::{
+    db::{
+        actor::DbActor,
+        models::media::{Media, MediaProcessingStatus, MediaVariant},
+        models::users::User,
+        selectors, services,
+    },
+    errors::MyError,
+    image_processing::{actor::ImageProcessor, job::process_media},
+    storage::actor::{DeleteObject, ObjectUrl, PutObject},
+    views, AppState,
+};
+use actix::Addr;
+use actix_multipart::Multipart;
+use actix_web::{get, post, web};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::vec::Vec;
+use utoipa::Component;
+
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(upload_media)
+        .service(get_media)
+        .service(get_media_by_id)
+        .service(get_media_variants)
+        .service(delete_media);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MediaData {
+    id: i32,
+    url: String,
+    file_name: String,
+    mime_type: String,
+    size_bytes: i64,
+    width: Option<i32>,
+    height: Option<i32>,
+    checksum: String,
+    created_at: chrono::NaiveDateTime,
+    processing_status: String,
+    blurhash: Option<String>,
+}
+
+impl MediaData {
+    pub fn from_media(media: &Media) -> Self {
+        MediaData {
+            id: media.id,
+            url: media.url.clone(),
+            file_name: media.file_name.clone(),
+            mime_type: media.mime_type.clone(),
+            size_bytes: media.size_bytes,
+            width: media.width,
+            height: media.height,
+            checksum: media.checksum.clone(),
+            created_at: media.created_at,
+            processing_status: media.processing_status.clone(),
+            blurhash: media.blurhash.clone(),
+        }
+    }
+}
+
+fn media_lookup_error(err: diesel::result::Error) -> MyError {
+    match err {
+        diesel::result::Error::NotFound => MyError::MediaNotFound,
+        _ => MyError::DieselError(err),
+    }
+}
+
+pub async fn ensure_user_owns_media_or_is_admin(
+    db_actor_addr: Addr<DbActor>,
+    user: &User,
+    media_id: i32,
+) -> Result<Media, MyError> {
+    let media = db_actor_addr
+        .send(selectors::media::GetMediaById { media_id: media_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(media_lookup_error)?;
+    if media.user_id == user.id || user.is_admin {
+        Ok(media)
+    } else {
+        Err(MyError::YouDontOwnThisMedia)
+    }
+}
+
+struct Upload {
+    file_name: String,
+    data: Vec<u8>,
+}
+
+/// Drops any directories the client sent along with the file name.
+fn clean_file_name(file_name: &str) -> String {
+    file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or("")
+        .trim()
+        .chars()
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect()
+}
+
+/// Reads the `file` field of the form, without ever buffering more than `max_size_bytes`.
+async fn read_upload(payload: &mut Multipart, max_size_bytes: usize) -> Result<Upload, MyError> {
+    let invalid_upload =
+        |err: actix_multipart::MultipartError| MyError::InvalidUpload(err.to_string());
+    while let Some(mut field) = payload.try_next().await.map_err(invalid_upload)? {
+        let content_disposition = field.content_disposition();
+        if content_disposition.get_name() != Some("file") {
+            continue;
+        }
+        let file_name = clean_file_name(content_disposition.get_filename().unwrap_or(""));
+        let mut data = Vec::new();
+        while let Some(chunk) = field.try_next().await.map_err(invalid_upload)? {
+            if data.len() + chunk.len() > max_size_bytes {
+                return Err(MyError::MediaTooLarge(max_size_bytes));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        if data.is_empty() {
+            return Err(MyError::InvalidUpload("file is empty".to_string()));
+        }
+        return Ok(Upload {
+            file_name: file_name,
+            data: data,
+        });
+    }
+    Err(MyError::InvalidUpload("missing `file` field".to_string()))
+}
+
+#[utoipa::path(
+    request_body(content = String, description = "Multipart form with a `file` field", content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Upload a file to the media library, images are served once their `processing_status` is `processed`", body = MediaData),
+        (status = 413, description = "File is too large"),
+        (status = 415, description = "File type is not allowed")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/media/upload")]
+async fn upload_media(
+    bearer_auth: BearerAuth,
+    mut payload: Multipart,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MediaData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let storage_addr = app_state.get_ref().storage_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let upload = read_upload(&mut payload, app_state.get_ref().media_max_size_bytes).await?;
+
+    // the declared content type is not trusted, the type is sniffed from the content
+    let file_type = infer::get(&upload.data).ok_or(MyError::UnsupportedMediaType)?;
+    let mime_type = file_type.mime_type().to_string();
+    if !app_state.get_ref().media_allowed_types.contains(&mime_type) {
+        return Err(MyError::UnsupportedMediaType);
+    }
+    let (width, height) = match imagesize::blob_size(&upload.data) {
+        Ok(size) if mime_type.starts_with("image/") => {
+            (Some(size.width as i32), Some(size.height as i32))
+        }
+        _ => (None, None),
+    };
+    let checksum = format!("{:x}", Sha256::digest(&upload.data));
+    let size_bytes = upload.data.len() as i64;
+    let storage_key = format!(
+        "{}/{}.{}",
+        authed_user.user.id,
+        uuid::Uuid::new_v4().to_simple(),
+        file_type.extension()
+    );
+    let file_name = if upload.file_name.is_empty() {
+        storage_key.rsplit('/').next().unwrap_or("").to_string()
+    } else {
+        upload.file_name
+    };
+    let processing_status = if ImageProcessor::can_process(&mime_type) {
+        MediaProcessingStatus::Pending
+    } else {
+        MediaProcessingStatus::Skipped
+    };
+    // images are only stored once the image processors stripped their metadata (EXIF/GPS),
+    // until then their url serves nothing
+    let (url, image_data) = match processing_status {
+        MediaProcessingStatus::Pending => {
+            let url = storage_addr
+                .send(ObjectUrl {
+                    key: storage_key.clone(),
+                })
+                .await
+                .map_err(|_| MyError::InternalServerError)?;
+            (url, Some(upload.data))
+        }
+        _ => {
+            let url = storage_addr
+                .send(PutObject {
+                    key: storage_key.clone(),
+                    data: upload.data,
+                    content_type: mime_type.clone(),
+                })
+                .await
+                .map_err(|_| MyError::InternalServerError)?
+                .map_err(|err| {
+                    log::error!("Failed storing `{}`: {}", storage_key, err);
+                    MyError::StorageError
+                })?;
+            (url, None)
+        }
+    };
+
+    let media = db_actor_addr
+        .send(services::media::AddMedia {
+            user_id: authed_user.user.id,
+            storage_key: storage_key.clone(),
+            url: url,
+            file_name: file_name,
+            mime_type: mime_type,
+            size_bytes: size_bytes,
+            width: width,
+            height: height,
+            checksum: checksum,
+            processing_status: processing_status,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)
+        .and_then(|res| res.map_err(|err| MyError::DieselError(err)));
+    match media {
+        Ok(media) => {
+            if let Some(image_data) = image_data {
+                actix_web::rt::spawn(process_media(
+                    db_actor_addr,
+                    storage_addr,
+                    app_state.get_ref().image_processor_addr.clone(),
+                    media.clone(),
+                    image_data,
+                ));
+            }
+            Ok(web::Json(MediaData::from_media(&media)))
+        }
+        Err(err) => {
+            // dont leave orphaned objects behind
+            let _ = storage_addr.send(DeleteObject { key: storage_key }).await;
+            Err(err)
+        }
+    }
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get the media library of the user", body = [MediaData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/media/get_media")]
+async fn get_media(
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<MediaData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let media = db_actor_addr
+        .send(selectors::media::GetUserMedia {
+            user_id: authed_user.user.id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        media
+            .iter()
+            .map(MediaData::from_media)
+            .collect::<Vec<MediaData>>(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("media_id" = i32, path, description = "Media database id"),
+    ),
+    responses(
+        (status = 200, description = "Get Media", body = MediaData),
+        (status = 404, description = "Media Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/media/{media_id:\\d+}")]
+async fn get_media_by_id(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MediaData>, MyError> {
+    let media_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let media =
+        ensure_user_owns_media_or_is_admin(db_actor_addr, &authed_user.user, media_id).await?;
+    Ok(web::Json(MediaData::from_media(&media)))
+}
+
+#[utoipa::path(
+    params(
+        ("media_id" = i32, path, description = "Media database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete Media, it is detached from all posts", body = MediaData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/media/delete/{media_id}")]
+async fn delete_media(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MediaData>, MyError> {
+    let media_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let storage_addr = app_state.get_ref().storage_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_owns_media_or_is_admin(db_actor_addr.clone(), &authed_user.user, media_id)
+        .await?;
+    let variants = db_actor_addr
+        .send(selectors::media::GetMediaVariants { media_id: media_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let media = db_actor_addr
+        .send(services::media::DeleteMedia { media_id: media_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(media_lookup_error)?;
+    let storage_keys = std::iter::once(media.storage_key.clone())
+        .chain(variants.into_iter().map(|variant| variant.storage_key));
+    for storage_key in storage_keys {
+        match storage_addr
+            .send(DeleteObject {
+                key: storage_key.clone(),
+            })
+            .await
+        {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => log::error!("Failed deleting `{}`: {}", storage_key, err),
+            Err(_) => log::error!("Failed deleting `{}`", storage_key),
+        }
+    }
+    Ok(web::Json(MediaData::from_media(&media)))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MediaVariantData {
+    url: String,
+    mime_type: String,
+    width: i32,
+    height: i32,
+    size_bytes: i64,
+}
+
+impl MediaVariantData {
+    pub fn from_media_variant(variant: &MediaVariant) -> Self {
+        MediaVariantData {
+            url: variant.url.clone(),
+            mime_type: variant.mime_type.clone(),
+            width: variant.width,
+            height: variant.height,
+            size_bytes: variant.size_bytes,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MediaVariantsData {
+    media_id: i32,
+    processing_status: String,
+    blurhash: Option<String>,
+    width: Option<i32>,
+    height: Option<i32>,
+    /// Ready to use `srcset` attribute per mime type, e.g. for `<source type="image/webp">`
+    srcset: BTreeMap<String, String>,
+    variants: Vec<MediaVariantData>,
+}
+
+impl MediaVariantsData {
+    pub fn from_media(media: &Media, variants: &[MediaVariant]) -> Self {
+        let mut variants = variants
+            .iter()
+            .map(MediaVariantData::from_media_variant)
+            .collect::<Vec<MediaVariantData>>();
+        // the (stripped) original is the largest candidate of its own format
+        if let (Some(width), Some(height)) = (media.width, media.height) {
+            variants.push(MediaVariantData {
+                url: media.url.clone(),
+                mime_type: media.mime_type.clone(),
+                width: width,
+                height: height,
+                size_bytes: media.size_bytes,
+            });
+        }
+        variants.sort_by(|a, b| (&a.mime_type, a.width).cmp(&(&b.mime_type, b.width)));
+
+        let mut srcset: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for variant in variants.iter() {
+            srcset
+                .entry(variant.mime_type.clone())
+                .or_default()
+                .push(format!("{} {}w", variant.url, variant.width));
+        }
+        MediaVariantsData {
+            media_id: media.id,
+            processing_status: media.processing_status.clone(),
+            blurhash: media.blurhash.clone(),
+            width: media.width,
+            height: media.height,
+            srcset: srcset
+                .into_iter()
+                .map(|(mime_type, candidates)| (mime_type, candidates.join(", ")))
+                .collect(),
+            variants: variants,
+        }
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("media_id" = i32, path, description = "Media database id"),
+    ),
+    responses(
+        (status = 200, description = "Get the resized variants of an image, along with `srcset` strings", body = MediaVariantsData),
+        (status = 404, description = "Media Not Found")
+    )
+)]
+#[get("/media/variants/{media_id}")]
+async fn get_media_variants(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MediaVariantsData>, MyError> {
+    let media_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let media = db_actor_addr
+        .send(selectors::media::GetMediaById { media_id: media_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(media_lookup_error)?;
+    let variants = db_actor_addr
+        .send(selectors::media::GetMediaVariants { media_id: media_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(MediaVariantsData::from_media(&media, &variants)))
+}
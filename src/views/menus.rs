@@ -0,0 +1,542 @@
+use crate::{
+    db::{
+        actor::DbActor,
+        models::menus::{Menu, MenuItem, MenuTarget},
+        selectors::{
+            self,
+            menus::{descendant_item_ids, MenuLinks},
+        },
+        services,
+    },
+    errors::MyError,
+    slugify::slugify,
+    views::{self, posts::optional_user},
+    AppState,
+};
+use actix::Addr;
+use actix_web::{get, post, web};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError, Error::NotFound};
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+use utoipa::Component;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_menus)
+        .service(get_menu)
+        .service(create_menu)
+        .service(delete_menu)
+        .service(create_menu_item)
+        .service(update_menu_item)
+        .service(move_menu_item)
+        .service(delete_menu_item);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MenuData {
+    id: i32,
+    name: String,
+    slug: String,
+}
+
+impl MenuData {
+    pub fn from_menu(menu: &Menu) -> Self {
+        MenuData {
+            id: menu.id,
+            name: menu.name.clone(),
+            slug: menu.slug.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MenuTree {
+    id: i32,
+    name: String,
+    slug: String,
+    /// Top level items, in order
+    items: Vec<MenuItemNode>,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MenuItemNode {
+    id: i32,
+    label: String,
+    /// One of `page`, `post`, `category` or `url`
+    link_type: String,
+    /// Id of the page, post or category linked to
+    target_id: Option<i32>,
+    /// Slug of the page, post or category linked to, `None` while it is not published
+    slug: Option<String>,
+    /// Set for external links
+    url: Option<String>,
+    /// Items below this one, in order
+    children: Vec<MenuItemNode>,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MenuItemData {
+    id: i32,
+    menu_id: i32,
+    parent_id: Option<i32>,
+    position: i32,
+    label: String,
+    page_id: Option<i32>,
+    post_id: Option<i32>,
+    category_id: Option<i32>,
+    url: Option<String>,
+}
+
+impl MenuItemData {
+    pub fn from_menu_item(item: &MenuItem) -> Self {
+        MenuItemData {
+            id: item.id,
+            menu_id: item.menu_id,
+            parent_id: item.parent_id,
+            position: item.position,
+            label: item.label.clone(),
+            page_id: item.page_id,
+            post_id: item.post_id,
+            category_id: item.category_id,
+            url: item.url.clone(),
+        }
+    }
+}
+
+/// Node of `item`, `None` if it links to something hidden and `keep_hidden` is not set.
+fn menu_item_node(item: &MenuItem, links: &MenuLinks, keep_hidden: bool) -> Option<MenuItemNode> {
+    let (link_type, target_id, slug) = match item.target()? {
+        MenuTarget::Page(page_id) => ("page", Some(page_id), links.pages.get(&page_id)),
+        MenuTarget::Post(post_id) => ("post", Some(post_id), links.posts.get(&post_id)),
+        MenuTarget::Category(category_id) => (
+            "category",
+            Some(category_id),
+            links.categories.get(&category_id),
+        ),
+        MenuTarget::Url(_) => ("url", None, None),
+    };
+    if target_id.is_some() && slug.is_none() && !keep_hidden {
+        return None;
+    }
+    Some(MenuItemNode {
+        id: item.id,
+        label: item.label.clone(),
+        link_type: link_type.to_string(),
+        target_id: target_id,
+        slug: slug.cloned(),
+        url: item.url.clone(),
+        children: vec![],
+    })
+}
+
+/// Tree of the items below `parent_id`, `items` must be in order among their siblings.
+/// Items linking to something hidden are left out along with the items below them.
+fn menu_tree(
+    items: &[MenuItem],
+    parent_id: Option<i32>,
+    links: &MenuLinks,
+    keep_hidden: bool,
+) -> Vec<MenuItemNode> {
+    items
+        .iter()
+        .filter(|item| item.parent_id == parent_id)
+        .filter_map(|item| {
+            let mut node = menu_item_node(item, links, keep_hidden)?;
+            node.children = menu_tree(items, Some(item.id), links, keep_hidden);
+            Some(node)
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MenuInput {
+    name: String,
+}
+
+/// Label and target of a menu item, exactly one of `page_id`, `post_id`, `category_id` and
+/// `url` must be given.
+#[derive(Serialize, Deserialize, Component)]
+pub struct MenuItemInput {
+    label: String,
+    page_id: Option<i32>,
+    post_id: Option<i32>,
+    category_id: Option<i32>,
+    /// `http(s)://` url or a path starting with `/`
+    url: Option<String>,
+}
+
+impl MenuItemInput {
+    fn checked_target(self) -> Result<(String, MenuTarget), MyError> {
+        let label = self.label.trim().to_string();
+        if label.is_empty() {
+            return Err(MyError::InvalidMenuItem("label is empty".to_string()));
+        }
+        let target =
+            MenuTarget::from_columns(self.page_id, self.post_id, self.category_id, self.url)
+                .ok_or_else(|| {
+                    MyError::InvalidMenuItem(
+                        "exactly one of `page_id`, `post_id`, `category_id` or `url` must be given"
+                            .to_string(),
+                    )
+                })?;
+        if let MenuTarget::Url(url) = &target {
+            let url = url.to_ascii_lowercase();
+            let safe = url.starts_with("https://")
+                || url.starts_with("http://")
+                || (url.starts_with('/') && !url.starts_with("//"));
+            if !safe {
+                return Err(MyError::InvalidMenuItem(
+                    "`url` must start with `http://`, `https://` or `/`".to_string(),
+                ));
+            }
+        }
+        Ok((label, target))
+    }
+}
+
+fn menu_error(err: diesel::result::Error) -> MyError {
+    match err {
+        NotFound => MyError::MenuNotFound,
+        DatabaseError(DatabaseErrorKind::UniqueViolation, _) => MyError::MenuAlreadyExists,
+        _ => MyError::DieselError(err),
+    }
+}
+
+fn menu_item_error(err: diesel::result::Error) -> MyError {
+    match err {
+        NotFound => MyError::MenuItemNotFound,
+        DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => MyError::InvalidMenuItem(
+            "the page, post or category linked to does not exist".to_string(),
+        ),
+        _ => MyError::DieselError(err),
+    }
+}
+
+async fn find_menu(
+    db_actor_addr: Addr<DbActor>,
+    lookup: selectors::menus::GetMenu,
+) -> Result<(Menu, Vec<MenuItem>), MyError> {
+    db_actor_addr
+        .send(lookup)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(menu_error)
+}
+
+async fn find_menu_item(db_actor_addr: Addr<DbActor>, item_id: i32) -> Result<MenuItem, MyError> {
+    db_actor_addr
+        .send(selectors::menus::GetMenuItem { item_id: item_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(menu_item_error)
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get Menus", body = [MenuData])
+    )
+)]
+#[get("/menus")]
+async fn get_menus(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<MenuData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let menus = db_actor_addr
+        .send(selectors::menus::GetMenus)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        menus
+            .iter()
+            .map(MenuData::from_menu)
+            .collect::<Vec<MenuData>>(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("menu_slug" = String, path, description = "Menu slug"),
+    ),
+    responses(
+        (status = 200, description = "Get the whole tree of a Menu. Items linking to drafts are left out, admins get them with a `null` slug", body = MenuTree)
+    )
+)]
+#[get("/menus/{menu_slug}")]
+async fn get_menu(
+    path: web::Path<String>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MenuTree>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let (menu, items) = find_menu(
+        db_actor_addr.clone(),
+        selectors::menus::GetMenu::BySlug(path.into_inner()),
+    )
+    .await?;
+    let is_admin = optional_user(db_actor_addr.clone(), auth_mgr_addr, bearer_auth)
+        .await
+        .is_some_and(|user| user.is_admin);
+    let links = db_actor_addr
+        .send(selectors::menus::GetMenuLinks {
+            page_ids: items.iter().filter_map(|item| item.page_id).collect(),
+            post_ids: items.iter().filter_map(|item| item.post_id).collect(),
+            category_ids: items.iter().filter_map(|item| item.category_id).collect(),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(MenuTree {
+        id: menu.id,
+        name: menu.name.clone(),
+        slug: menu.slug.clone(),
+        items: menu_tree(&items, None, &links, is_admin),
+    }))
+}
+
+#[utoipa::path(
+    request_body=MenuInput,
+    responses(
+        (status = 200, description = "Create Menu", body = MenuData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/menus/create")]
+async fn create_menu(
+    bearer_auth: BearerAuth,
+    menu_input: web::Json<MenuInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MenuData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let menu_input = menu_input.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    if slugify(&menu_input.name).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    let menu = db_actor_addr
+        .send(services::menus::AddMenu {
+            name: menu_input.name,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(menu_error)?;
+    Ok(web::Json(MenuData::from_menu(&menu)))
+}
+
+#[utoipa::path(
+    params(
+        ("menu_id" = i32, path, description = "Menu database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete Menu along with its items", body = MenuData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/menus/delete/{menu_id}")]
+async fn delete_menu(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MenuData>, MyError> {
+    let menu_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let menu = db_actor_addr
+        .send(services::menus::DeleteMenu { menu_id: menu_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(menu_error)?;
+    Ok(web::Json(MenuData::from_menu(&menu)))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CreateMenuItemData {
+    label: String,
+    page_id: Option<i32>,
+    post_id: Option<i32>,
+    category_id: Option<i32>,
+    /// `http(s)://` url or a path starting with `/`
+    url: Option<String>,
+    /// Item to nest the new item below, added at the top level when not given
+    parent_id: Option<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("menu_id" = i32, path, description = "Menu database id"),
+    ),
+    request_body=CreateMenuItemData,
+    responses(
+        (status = 200, description = "Add a Menu Item after the other items below its parent", body = MenuItemData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/menus/items/create/{menu_id}")]
+async fn create_menu_item(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    item_data: web::Json<CreateMenuItemData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MenuItemData>, MyError> {
+    let menu_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let item_data = item_data.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let parent_id = item_data.parent_id;
+    let (label, target) = MenuItemInput {
+        label: item_data.label,
+        page_id: item_data.page_id,
+        post_id: item_data.post_id,
+        category_id: item_data.category_id,
+        url: item_data.url,
+    }
+    .checked_target()?;
+    let (menu, items) = find_menu(
+        db_actor_addr.clone(),
+        selectors::menus::GetMenu::ById(menu_id),
+    )
+    .await?;
+    if let Some(parent_id) = parent_id {
+        if !items.iter().any(|item| item.id == parent_id) {
+            return Err(MyError::MenuItemNotFound);
+        }
+    }
+    let item = db_actor_addr
+        .send(services::menus::AddMenuItem {
+            menu_id: menu.id,
+            parent_id: parent_id,
+            label: label,
+            target: target,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(menu_item_error)?;
+    Ok(web::Json(MenuItemData::from_menu_item(&item)))
+}
+
+#[utoipa::path(
+    params(
+        ("item_id" = i32, path, description = "Menu Item database id"),
+    ),
+    request_body=MenuItemInput,
+    responses(
+        (status = 200, description = "Change the label or target of a Menu Item", body = MenuItemData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/menus/items/update/{item_id}")]
+async fn update_menu_item(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    item_input: web::Json<MenuItemInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MenuItemData>, MyError> {
+    let item_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let (label, target) = item_input.into_inner().checked_target()?;
+    let item = db_actor_addr
+        .send(services::menus::UpdateMenuItem {
+            item_id: item_id,
+            label: label,
+            target: target,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(menu_item_error)?;
+    Ok(web::Json(MenuItemData::from_menu_item(&item)))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MoveMenuItemData {
+    /// Item of the same menu to nest below, moves to the top level when not given
+    parent_id: Option<i32>,
+    /// Index among the new siblings, the item goes last when not given
+    position: Option<usize>,
+}
+
+#[utoipa::path(
+    params(
+        ("item_id" = i32, path, description = "Menu Item database id"),
+    ),
+    request_body=MoveMenuItemData,
+    responses(
+        (status = 200, description = "Move a Menu Item along with the items below it", body = MenuItemData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/menus/items/move/{item_id}")]
+async fn move_menu_item(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    move_data: web::Json<MoveMenuItemData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MenuItemData>, MyError> {
+    let item_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let move_data = move_data.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    if let Some(parent_id) = move_data.parent_id {
+        let item = find_menu_item(db_actor_addr.clone(), item_id).await?;
+        let (_, items) = find_menu(
+            db_actor_addr.clone(),
+            selectors::menus::GetMenu::ById(item.menu_id),
+        )
+        .await?;
+        if !items.iter().any(|item| item.id == parent_id) {
+            return Err(MyError::MenuItemNotFound);
+        }
+        if descendant_item_ids(&items, item_id).contains(&parent_id) {
+            return Err(MyError::MenuItemCycle);
+        }
+    }
+    let item = db_actor_addr
+        .send(services::menus::MoveMenuItem {
+            item_id: item_id,
+            parent_id: move_data.parent_id,
+            position: move_data.position.unwrap_or(usize::MAX),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(menu_item_error)?;
+    Ok(web::Json(MenuItemData::from_menu_item(&item)))
+}
+
+#[utoipa::path(
+    params(
+        ("item_id" = i32, path, description = "Menu Item database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete a Menu Item along with the items below it", body = MenuItemData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/menus/items/delete/{item_id}")]
+async fn delete_menu_item(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<MenuItemData>, MyError> {
+    let item_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let item = db_actor_addr
+        .send(services::menus::DeleteMenuItem { item_id: item_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(menu_item_error)?;
+    Ok(web::Json(MenuItemData::from_menu_item(&item)))
+}
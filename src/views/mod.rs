@@ -1,4 +1,20 @@
 pub mod users;
 pub mod admins;
 pub mod posts;
+pub mod categories;
+pub mod tags;
 pub mod swagger_ui;
+pub mod media;
+pub mod comments;
+pub mod reactions;
+pub mod analytics;
+pub mod collaborators;
+pub mod autosaves;
+pub mod previews;
+pub mod translations;
+pub mod content_types;
+pub mod pages;
+pub mod menus;
+pub mod collections;
+pub mod recommendations;
+pub mod feeds;
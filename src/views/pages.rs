@@ -0,0 +1,404 @@
+use crate::{
+    db::{
+        actor::DbActor,
+        models::{pages::Page, posts::BodyFormat},
+        selectors::{
+            self,
+            pages::{descendant_page_ids, published_pages},
+        },
+        services,
+    },
+    errors::MyError,
+    slugify::slugify,
+    views::{
+        self,
+        posts::{optional_user, parse_body_format},
+    },
+    AppState,
+};
+use actix::Addr;
+use actix_web::{get, post, web};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError, Error::NotFound};
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+use utoipa::Component;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_pages)
+        .service(get_all_pages)
+        .service(get_page_by_slug)
+        .service(create_page)
+        .service(update_page)
+        .service(move_page)
+        .service(delete_page);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PageData {
+    id: i32,
+    title: String,
+    body: String,
+    body_format: String,
+    /// `body` rendered to sanitized html
+    rendered_body: String,
+    slug: String,
+    /// Slugs from the top of the tree down to this page, e.g. `about/team`
+    path: String,
+    parent_id: Option<i32>,
+    position: i32,
+    published: bool,
+    updated_at: chrono::NaiveDateTime,
+}
+
+impl PageData {
+    pub fn from_page(page: &Page, path: String) -> Self {
+        PageData {
+            id: page.id,
+            title: page.title.clone(),
+            body: page.body.clone(),
+            body_format: page.body_format.clone(),
+            rendered_body: page.rendered_body.clone(),
+            slug: page.slug.clone(),
+            path: path,
+            parent_id: page.parent_id,
+            position: page.position,
+            published: page.published,
+            updated_at: page.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PageNode {
+    id: i32,
+    title: String,
+    slug: String,
+    /// Slugs from the top of the tree down to this page, e.g. `about/team`
+    path: String,
+    published: bool,
+    /// Pages below this one, in order
+    children: Vec<PageNode>,
+}
+
+/// Tree of the pages below `parent_id`, `pages` must be in order among their siblings.
+fn page_tree(pages: &[Page], parent_id: Option<i32>, parent_path: &str) -> Vec<PageNode> {
+    pages
+        .iter()
+        .filter(|page| page.parent_id == parent_id)
+        .map(|page| {
+            let path = match parent_path {
+                "" => page.slug.clone(),
+                _ => format!("{}/{}", parent_path, page.slug),
+            };
+            PageNode {
+                id: page.id,
+                title: page.title.clone(),
+                slug: page.slug.clone(),
+                children: page_tree(pages, Some(page.id), &path),
+                path: path,
+                published: page.published,
+            }
+        })
+        .collect()
+}
+
+/// Path of `page` within `pages`.
+fn page_path(pages: &[Page], page: &Page) -> String {
+    let mut slugs = vec![page.slug.clone()];
+    let mut parent_id = page.parent_id;
+    while let Some(parent) =
+        parent_id.and_then(|id| pages.iter().find(|candidate| candidate.id == id))
+    {
+        if slugs.len() > pages.len() {
+            break;
+        }
+        slugs.push(parent.slug.clone());
+        parent_id = parent.parent_id;
+    }
+    slugs.reverse();
+    slugs.join("/")
+}
+
+fn page_error(err: diesel::result::Error) -> MyError {
+    match err {
+        NotFound => MyError::PageNotFound,
+        DatabaseError(DatabaseErrorKind::UniqueViolation, _) => MyError::PageAlreadyExists,
+        // parent page does not exist
+        DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => MyError::PageNotFound,
+        _ => MyError::DieselError(err),
+    }
+}
+
+async fn load_pages(
+    db_actor_addr: Addr<DbActor>,
+    published_only: bool,
+) -> Result<Vec<Page>, MyError> {
+    db_actor_addr
+        .send(selectors::pages::GetPages {
+            published_only: published_only,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))
+}
+
+/// `PageData` of a page right after it changed.
+async fn changed_page_data(db_actor_addr: Addr<DbActor>, page: &Page) -> Result<PageData, MyError> {
+    let pages = load_pages(db_actor_addr, false).await?;
+    Ok(PageData::from_page(page, page_path(&pages, page)))
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get the tree of Published Pages", body = [PageNode])
+    )
+)]
+#[get("/pages")]
+async fn get_pages(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<PageNode>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let pages = load_pages(db_actor_addr, true).await?;
+    Ok(web::Json(page_tree(&pages, None, "")))
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get the tree of all Pages, drafts included", body = [PageNode])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/pages/all")]
+async fn get_all_pages(
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<PageNode>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let pages = load_pages(db_actor_addr, false).await?;
+    Ok(web::Json(page_tree(&pages, None, "")))
+}
+
+#[utoipa::path(
+    params(
+        ("slug" = String, path, description = "Page slug"),
+    ),
+    responses(
+        (status = 200, description = "Get Page, admins also get drafts", body = PageData)
+    )
+)]
+#[get("/pages/by_slug/{slug}")]
+async fn get_page_by_slug(
+    path: web::Path<String>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PageData>, MyError> {
+    let slug = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let all_pages = load_pages(db_actor_addr.clone(), false).await?;
+    let is_admin = optional_user(db_actor_addr, auth_mgr_addr, bearer_auth)
+        .await
+        .is_some_and(|user| user.is_admin);
+    let pages = if is_admin {
+        all_pages.clone()
+    } else {
+        published_pages(all_pages.clone())
+    };
+    let page = pages
+        .iter()
+        .find(|page| page.slug == slug)
+        .ok_or(MyError::PageNotFound)?;
+    Ok(web::Json(PageData::from_page(
+        page,
+        page_path(&all_pages, page),
+    )))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CreatePageData {
+    title: String,
+    body: String,
+    /// One of `plain` (default), `markdown` or `html`
+    body_format: Option<String>,
+    /// Made from the title when not given
+    slug: Option<String>,
+    /// Added at the top of the tree when not given
+    parent_id: Option<i32>,
+    /// Drafts are only shown to admins, defaults to `false`
+    published: Option<bool>,
+}
+
+#[utoipa::path(
+    request_body=CreatePageData,
+    responses(
+        (status = 200, description = "Create Page, it goes after the other pages below its parent", body = PageData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/pages/create")]
+async fn create_page(
+    bearer_auth: BearerAuth,
+    page_data: web::Json<CreatePageData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PageData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let page_data = page_data.into_inner();
+    let authed_user = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let body_format = parse_body_format(page_data.body_format)?.unwrap_or(BodyFormat::Plain);
+    if slugify(page_data.slug.as_ref().unwrap_or(&page_data.title)).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    let page = db_actor_addr
+        .send(services::pages::AddPage {
+            title: page_data.title,
+            body: page_data.body,
+            body_format: body_format,
+            slug: page_data.slug,
+            parent_id: page_data.parent_id,
+            published: page_data.published.unwrap_or(false),
+            user_id: authed_user.user.id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(page_error)?;
+    Ok(web::Json(changed_page_data(db_actor_addr, &page).await?))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdatePageData {
+    title: Option<String>,
+    body: Option<String>,
+    /// One of `plain`, `markdown` or `html`
+    body_format: Option<String>,
+    slug: Option<String>,
+    published: Option<bool>,
+}
+
+#[utoipa::path(
+    params(
+        ("page_id" = i32, path, description = "Page database id"),
+    ),
+    request_body=UpdatePageData,
+    responses(
+        (status = 200, description = "Update Page, fields left out stay as they are", body = PageData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/pages/update/{page_id}")]
+async fn update_page(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    page_data: web::Json<UpdatePageData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PageData>, MyError> {
+    let page_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let page_data = page_data.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let body_format = parse_body_format(page_data.body_format)?;
+    if let Some(slug) = &page_data.slug {
+        if slugify(slug).is_empty() {
+            return Err(MyError::InvalidName);
+        }
+    }
+    let page = db_actor_addr
+        .send(services::pages::UpdatePage {
+            page_id: page_id,
+            changes: services::pages::PageChanges {
+                title: page_data.title,
+                body: page_data.body,
+                body_format: body_format,
+                slug: page_data.slug,
+                published: page_data.published,
+            },
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(page_error)?;
+    Ok(web::Json(changed_page_data(db_actor_addr, &page).await?))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MovePageData {
+    /// Moves the page to the top of the tree when not given
+    parent_id: Option<i32>,
+    /// Index among the new siblings, the page goes last when not given
+    position: Option<usize>,
+}
+
+#[utoipa::path(
+    params(
+        ("page_id" = i32, path, description = "Page database id"),
+    ),
+    request_body=MovePageData,
+    responses(
+        (status = 200, description = "Move Page along with the pages below it", body = PageData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/pages/move/{page_id}")]
+async fn move_page(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    move_data: web::Json<MovePageData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PageData>, MyError> {
+    let page_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let move_data = move_data.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    if let Some(parent_id) = move_data.parent_id {
+        let pages = load_pages(db_actor_addr.clone(), false).await?;
+        if descendant_page_ids(&pages, page_id).contains(&parent_id) {
+            return Err(MyError::PageCycle);
+        }
+    }
+    let page = db_actor_addr
+        .send(services::pages::MovePage {
+            page_id: page_id,
+            parent_id: move_data.parent_id,
+            position: move_data.position.unwrap_or(usize::MAX),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(page_error)?;
+    Ok(web::Json(changed_page_data(db_actor_addr, &page).await?))
+}
+
+#[utoipa::path(
+    params(
+        ("page_id" = i32, path, description = "Page database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete Page, the pages below it take its place", body = PageData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/pages/delete/{page_id}")]
+async fn delete_page(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PageData>, MyError> {
+    let page_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let page = db_actor_addr
+        .send(services::pages::DeletePage { page_id: page_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(page_error)?;
+    Ok(web::Json(PageData::from_page(&page, page.slug.clone())))
+}
@@ -1,18 +1,38 @@
 use crate::{
+    argon2_password_hasher,
+    auth::actor::{AuthManager, CreateExpiringToken, ExtractClaim},
+    content_blocks::{Block, BlockEdit, BlockEditError},
     db::{
         actor::DbActor,
-        models::{posts::Post, users::User},
-        selectors, services,
+        models::{
+            collaborators::CollaboratorRole,
+            posts::{BodyFormat, Post, PublishStatus, Visibility},
+            translations::PostTranslation,
+            users::User,
+        },
+        selectors,
+        services::{self, posts::VersionedUpdate},
     },
     errors::MyError,
-    views, AppState,
+    slugify::slugify,
+    views::{
+        self,
+        categories::CategoryData,
+        media::MediaData,
+        tags::TagData,
+        translations::{negotiate_locales, LangQuery},
+    },
+    AppState,
 };
 use actix::Addr;
-use actix_web::{get, post, web};
+use actix_web::{get, http::header, patch, post, web, HttpRequest, HttpResponse};
 use actix_web_httpauth::extractors::bearer::BearerAuth;
+use diesel::OptionalExtension;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
 use std::vec::Vec;
-use utoipa::Component;
+use utoipa::{Component, IntoParams};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(create_post)
@@ -22,7 +42,31 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         .service(update_post_body_handler)
         .service(delete_post)
         .service(request_admin_to_publish)
-        .service(publish_post);
+        .service(publish_post)
+        .service(schedule_post)
+        .service(unpublish_post)
+        .service(archive_post)
+        .service(get_trash)
+        .service(restore_post)
+        .service(update_post_slug_handler)
+        .service(get_post_by_slug)
+        .service(get_post)
+        .service(patch_post)
+        .service(get_post_taxonomy)
+        .service(update_post_tags_handler)
+        .service(update_post_categories_handler)
+        .service(update_post_blocks_handler)
+        .service(insert_post_block)
+        .service(update_post_block)
+        .service(delete_post_block)
+        .service(move_post_block)
+        .service(get_post_media)
+        .service(attach_post_media)
+        .service(detach_post_media)
+        .service(update_post_comments_enabled_handler)
+        .service(update_post_visibility_handler)
+        .service(unlock_post)
+        .service(bulk_post_action);
 }
 
 #[derive(Serialize, Deserialize, Component)]
@@ -31,8 +75,68 @@ pub struct PostData {
     subject: String,
     body: String,
     owner: bool,
-    owner_name: String,
+    /// Usernames of the owner followed by the co-authors
+    authors: Vec<String>,
     status: String,
+    publish_at: Option<chrono::NaiveDateTime>,
+    unpublish_at: Option<chrono::NaiveDateTime>,
+    slug: String,
+    body_format: String,
+    /// `body` rendered to sanitized html
+    rendered_body: String,
+    content_blocks: serde_json::Value,
+    content_html: String,
+    content_text: String,
+    comments_enabled: bool,
+    /// Number of reactions per emoji
+    reaction_counts: serde_json::Value,
+    /// Reaction of the caller, when authenticated
+    my_reaction: Option<String>,
+    /// Bumped on every change, send it back as `If-Match` or `expected_version` when updating
+    version: i32,
+    /// One of `public`, `unlisted`, `members-only` or `password-protected`
+    visibility: String,
+    /// Locale `subject` and `body` are shown in, where it is negotiated. Content blocks are
+    /// not translated.
+    locale: Option<String>,
+    /// Content type of an entry, `None` for blog posts
+    content_type_id: Option<i32>,
+    /// Values of the fields of an entry, by field name
+    fields: serde_json::Value,
+    /// Series the post is part of, filled in on single posts
+    series: Option<SeriesNavigationData>,
+    /// Whether the post is in a pinned collection, shown first on the home listing
+    pinned: bool,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct SeriesLink {
+    id: i32,
+    subject: String,
+    slug: String,
+}
+
+impl SeriesLink {
+    fn from_series_post(post: &selectors::collections::SeriesPost) -> Self {
+        SeriesLink {
+            id: post.id,
+            subject: post.subject.clone(),
+            slug: post.slug.clone(),
+        }
+    }
+}
+
+/// Where a post stands in its series, drafts are not counted.
+#[derive(Serialize, Deserialize, Component)]
+pub struct SeriesNavigationData {
+    id: i32,
+    name: String,
+    slug: String,
+    /// Starting at 1
+    position: usize,
+    total: usize,
+    previous: Option<SeriesLink>,
+    next: Option<SeriesLink>,
 }
 
 impl PostData {
@@ -46,33 +150,285 @@ impl PostData {
             subject: post.subject.clone(),
             body: post.body.clone(),
             owner: owner,
-            owner_name: post.owner_name.clone(),
+            authors: vec![post.owner_name.clone()],
             status: post.status.clone(),
+            publish_at: post.publish_at,
+            unpublish_at: post.unpublish_at,
+            slug: post.slug.clone(),
+            body_format: post.body_format.clone(),
+            rendered_body: post.rendered_body.clone(),
+            content_blocks: post.content_blocks.clone(),
+            content_html: post.content_html.clone(),
+            content_text: post.content_text.clone(),
+            comments_enabled: post.comments_enabled,
+            reaction_counts: post.reaction_counts.clone(),
+            my_reaction: None,
+            version: post.version,
+            visibility: post.visibility.clone(),
+            locale: None,
+            content_type_id: post.content_type_id,
+            fields: post.fields.clone(),
+            series: None,
+            pinned: false,
         }
     }
 
+    /// `authors` is left to `with_authors`, as `user` may be a collaborator rather than the owner.
     pub fn from_post(post: &Post, user: User) -> Self {
         PostData {
             id: post.id,
             subject: post.post_subject.clone(),
             body: post.post_body.clone(),
-            owner: true,
-            owner_name: user.username,
+            owner: post.user_id == user.id,
+            authors: vec![],
             status: post.published_status.clone(),
+            publish_at: post.publish_at,
+            unpublish_at: post.unpublish_at,
+            slug: post.slug.clone(),
+            body_format: post.body_format.clone(),
+            rendered_body: post.rendered_body.clone(),
+            content_blocks: post.content_blocks.clone(),
+            content_html: post.content_html.clone(),
+            content_text: post.content_text.clone(),
+            comments_enabled: post.comments_enabled,
+            reaction_counts: post.reaction_counts.clone(),
+            my_reaction: None,
+            version: post.version,
+            visibility: post.visibility.clone(),
+            locale: None,
+            content_type_id: post.content_type_id,
+            fields: post.fields.clone(),
+            series: None,
+            pinned: false,
+        }
+    }
+}
+
+impl PostData {
+    /// Shows `translation` in place of what the post was written as.
+    fn translate(&mut self, translation: &PostTranslation) {
+        self.subject = translation.post_subject.clone();
+        self.body = translation.post_body.clone();
+        self.body_format = translation.body_format.clone();
+        self.rendered_body = translation.rendered_body.clone();
+        self.slug = translation.slug.clone();
+        self.locale = Some(translation.locale.clone());
+    }
+}
+
+/// Shows each of `posts` in the first of `locales` it has a published translation in. Posts
+/// are shown as written once the default locale comes up in `locales`, or if nothing matched.
+pub async fn with_translations(
+    app_state: &AppState,
+    locales: &[String],
+    mut posts: Vec<PostData>,
+) -> Result<Vec<PostData>, MyError> {
+    let default_locale = &app_state.default_locale;
+    let wanted = locales
+        .iter()
+        .take_while(|locale| *locale != default_locale)
+        .cloned()
+        .collect::<Vec<String>>();
+    let translations = app_state
+        .db_actor_addr
+        .send(selectors::translations::GetPublishedTranslations {
+            post_ids: posts.iter().map(|post| post.id).collect(),
+            locales: wanted.clone(),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let translations = translations
+        .into_iter()
+        .map(|translation| {
+            (
+                (translation.post_id, translation.locale.clone()),
+                translation,
+            )
+        })
+        .collect::<HashMap<(i32, String), PostTranslation>>();
+    for post in posts.iter_mut() {
+        let translation = wanted
+            .iter()
+            .find_map(|locale| translations.get(&(post.id, locale.clone())));
+        match translation {
+            Some(translation) => post.translate(translation),
+            None => post.locale = Some(default_locale.clone()),
+        }
+    }
+    Ok(posts)
+}
+
+/// Fills in `series` of `posts` that are part of one.
+pub async fn with_series(
+    db_actor_addr: Addr<DbActor>,
+    mut posts: Vec<PostData>,
+) -> Result<Vec<PostData>, MyError> {
+    let mut navigation = db_actor_addr
+        .send(selectors::collections::GetSeriesNavigation {
+            post_ids: posts.iter().map(|post| post.id).collect(),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    for post in posts.iter_mut() {
+        if let Some(series) = navigation.remove(&post.id) {
+            post.series = Some(SeriesNavigationData {
+                id: series.series.id,
+                name: series.series.name.clone(),
+                slug: series.series.slug.clone(),
+                position: series.position,
+                total: series.total,
+                previous: series.previous.as_ref().map(SeriesLink::from_series_post),
+                next: series.next.as_ref().map(SeriesLink::from_series_post),
+            });
+        }
+    }
+    Ok(posts)
+}
+
+/// Moves the posts of pinned collections to the front of `posts`, in collection order.
+async fn with_pinned_first(
+    db_actor_addr: Addr<DbActor>,
+    posts: Vec<PostData>,
+) -> Result<Vec<PostData>, MyError> {
+    let pinned_ids = db_actor_addr
+        .send(selectors::collections::GetPinnedPostIds)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let (mut pinned, rest): (Vec<PostData>, Vec<PostData>) = posts
+        .into_iter()
+        .partition(|post| pinned_ids.contains(&post.id));
+    pinned.sort_by_key(|post| pinned_ids.iter().position(|id| *id == post.id));
+    for post in pinned.iter_mut() {
+        post.pinned = true;
+    }
+    pinned.extend(rest);
+    Ok(pinned)
+}
+
+/// Fills in `my_reaction` of `posts`, nothing to do for anonymous callers.
+async fn with_my_reactions(
+    db_actor_addr: Addr<DbActor>,
+    user_maybe: &Option<User>,
+    mut posts: Vec<PostData>,
+) -> Result<Vec<PostData>, MyError> {
+    if let Some(user) = user_maybe {
+        let my_reactions = db_actor_addr
+            .send(selectors::reactions::GetUserReactions {
+                user_id: user.id,
+                post_ids: posts.iter().map(|post| post.id).collect(),
+            })
+            .await
+            .map_err(|_| MyError::InternalServerError)?
+            .map_err(|err| MyError::DieselError(err))?;
+        for post in posts.iter_mut() {
+            post.my_reaction = my_reactions.get(&post.id).cloned();
+        }
+    }
+    Ok(posts)
+}
+
+/// Fills in `authors` of `posts` with the owner and the co-authors of each post.
+pub async fn with_authors(
+    db_actor_addr: Addr<DbActor>,
+    mut posts: Vec<PostData>,
+) -> Result<Vec<PostData>, MyError> {
+    let mut authors = db_actor_addr
+        .send(selectors::collaborators::GetPostAuthors {
+            post_ids: posts.iter().map(|post| post.id).collect(),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    for post in posts.iter_mut() {
+        if let Some(post_authors) = authors.remove(&post.id) {
+            post.authors = post_authors;
+        }
+    }
+    Ok(posts)
+}
+
+/// `PostData` of a post right after `user` changed it.
+pub async fn changed_post_data(
+    db_actor_addr: Addr<DbActor>,
+    post: &Post,
+    user: User,
+) -> Result<PostData, MyError> {
+    let post = PostData::from_post(post, user);
+    Ok(with_authors(db_actor_addr, vec![post]).await?.remove(0))
+}
+
+/// Like `changed_post_data`, a conflicting update is turned into an error holding the
+/// current state of the post.
+pub async fn versioned_post_data(
+    db_actor_addr: Addr<DbActor>,
+    update: VersionedUpdate,
+    user: User,
+) -> Result<PostData, MyError> {
+    match update {
+        VersionedUpdate::Updated(post) => changed_post_data(db_actor_addr, &post, user).await,
+        VersionedUpdate::Conflict(post) => {
+            let current = changed_post_data(db_actor_addr, &post, user).await?;
+            let current =
+                serde_json::to_value(&current).map_err(|_| MyError::InternalServerError)?;
+            Err(MyError::VersionConflict(current))
         }
     }
 }
 
+fn etag(version: i32) -> header::EntityTag {
+    header::EntityTag::new_strong(version.to_string())
+}
+
+/// Responds with `post`, tagged with its version.
+pub fn post_response(post: PostData) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(header::ETag(etag(post.version)))
+        .json(post)
+}
+
+/// Version of the post the client is editing, taken from `If-Match` or else from the
+/// `expected_version` of the request body.
+pub fn expected_version(req: &HttpRequest, expected_version: Option<i32>) -> Result<i32, MyError> {
+    let if_match = match req.headers().get(header::IF_MATCH) {
+        None => return expected_version.ok_or(MyError::PreconditionRequired),
+        Some(if_match) => if_match,
+    };
+    if_match
+        .to_str()
+        .ok()
+        .and_then(|if_match| if_match.parse::<header::EntityTag>().ok())
+        .and_then(|tag| tag.tag().parse::<i32>().ok())
+        .ok_or(MyError::PreconditionRequired)
+}
+
 #[derive(Serialize, Deserialize, Component)]
 pub struct CreatePostData {
     subject: String,
     body: String,
+    slug: Option<String>,
+    /// One of `plain` (default), `markdown` or `html`
+    body_format: Option<String>,
+}
+
+pub fn parse_body_format(body_format: Option<String>) -> Result<Option<BodyFormat>, MyError> {
+    match body_format {
+        None => Ok(None),
+        Some(body_format) => body_format
+            .parse::<BodyFormat>()
+            .map(Some)
+            .map_err(|_| MyError::InvalidBodyFormat),
+    }
 }
 
 async fn add_post(
     db_actor_addr: Addr<DbActor>,
     subject: String,
     body: String,
+    slug: Option<String>,
+    body_format: BodyFormat,
     user_id: i32,
 ) -> actix_web::Result<Post, MyError> {
     db_actor_addr
@@ -80,6 +436,8 @@ async fn add_post(
             subject: subject,
             body: body,
             user_id: user_id,
+            slug: slug,
+            body_format: body_format,
         })
         .await
         .map_err(|_| MyError::InternalServerError)?
@@ -104,6 +462,7 @@ async fn create_post(
     let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
     let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
     let post_data = post_data.into_inner();
+    let body_format = parse_body_format(post_data.body_format)?.unwrap_or(BodyFormat::Plain);
     let authed_user = views::users::AuthedUser::from_bearer_token(
         db_actor_addr.clone(),
         auth_mgr_addr,
@@ -111,54 +470,196 @@ async fn create_post(
     )
     .await?;
     let post = add_post(
-        db_actor_addr,
+        db_actor_addr.clone(),
         post_data.subject,
         post_data.body,
+        post_data.slug,
+        body_format,
         authed_user.user.id,
     )
     .await?;
-    Ok(web::Json(PostData::from_post(&post, authed_user.user)))
+    Ok(web::Json(
+        changed_post_data(db_actor_addr, &post, authed_user.user).await?,
+    ))
+}
+
+/// Resolves the user for endpoints where authentication is optional, an invalid token is
+/// treated the same as no token.
+pub async fn optional_user(
+    db_actor_addr: Addr<DbActor>,
+    auth_mgr_addr: Addr<AuthManager>,
+    bearer_auth: Option<BearerAuth>,
+) -> Option<User> {
+    match bearer_auth {
+        None => None,
+        Some(bearer_auth) => {
+            views::users::AuthedUser::from_bearer_token(db_actor_addr, auth_mgr_addr, bearer_auth)
+                .await
+                .ok()
+                .map(|authed_user| authed_user.user)
+        }
+    }
+}
+
+/// Header holding the token handed out by `unlock_post`.
+const POST_ACCESS_TOKEN_HEADER: &str = "X-Post-Access-Token";
+const POST_ACCESS_TOKEN_SECS: u32 = 60 * 60;
+
+/// Data signed into a post access token, not a plain user id so it can never pass as a
+/// login token. Unknown fields are rejected so that other tokens carrying a `post_id`, such as
+/// preview tokens, never pass as one either.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+struct PostAccessClaim {
+    post_id: i32,
+}
+
+/// Whether the request holds a valid access token for `post_id`.
+pub async fn has_post_access(
+    req: &HttpRequest,
+    auth_mgr_addr: Addr<AuthManager>,
+    post_id: i32,
+) -> bool {
+    let token = match req
+        .headers()
+        .get(POST_ACCESS_TOKEN_HEADER)
+        .and_then(|token| token.to_str().ok())
+    {
+        Some(token) => token.to_string(),
+        None => return false,
+    };
+    match auth_mgr_addr
+        .send(ExtractClaim {
+            token: token,
+            phantom: PhantomData::<PostAccessClaim>,
+        })
+        .await
+    {
+        Ok(Ok(claim)) => claim.post_id == post_id,
+        _ => false,
+    }
+}
+
+/// Drafts are only visible to their owner and admins. Published posts are visible to everyone,
+/// except members-only ones which need a signed in user and password-protected ones which need
+/// an access token, see `has_post_access`.
+pub fn is_visible_to(
+    post: &selectors::posts::PostData,
+    user_maybe: &Option<User>,
+    has_access: bool,
+) -> bool {
+    let owner_or_admin = match user_maybe {
+        Some(user) => user.id == post.user_id || user.is_admin,
+        None => false,
+    };
+    if owner_or_admin {
+        return true;
+    }
+    if post.status != format!("{}", PublishStatus::Published) {
+        return false;
+    }
+    match post.visibility.parse::<Visibility>() {
+        Ok(Visibility::Public) | Ok(Visibility::Unlisted) => true,
+        Ok(Visibility::MembersOnly) => user_maybe.is_some(),
+        Ok(Visibility::PasswordProtected) => has_access,
+        Err(_) => false,
+    }
+}
+
+/// Error for a post that is not visible to the caller, drafts are reported as not found.
+pub fn not_visible_error(post: &selectors::posts::PostData) -> MyError {
+    if post.status != format!("{}", PublishStatus::Published) {
+        return MyError::PostNotFound;
+    }
+    match post.visibility.parse::<Visibility>() {
+        Ok(Visibility::MembersOnly) => MyError::MembersOnly,
+        Ok(Visibility::PasswordProtected) => MyError::PostLocked,
+        _ => MyError::PostNotFound,
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetPostsQuery {
+    /// Only posts with this tag slug
+    tag: Option<String>,
+    /// Only posts in this category slug or its sub categories
+    category: Option<String>,
+    /// Locale to show posts in, `Accept-Language` is used otherwise
+    lang: Option<String>,
 }
 
 #[utoipa::path(
     responses(
-        (status = 200, description = "Request Admin to publish this post")
+        (status = 200, description = "Get Published Posts, members-only ones included for signed in users. Without a tag or category the posts of pinned collections come first", body = [PostData])
     )
 )]
 #[get("/posts/get_posts")]
 async fn get_posts(
+    req: HttpRequest,
     bearer_auth: Option<BearerAuth>,
+    query: web::Query<GetPostsQuery>,
     app_state: web::Data<AppState>,
 ) -> actix_web::Result<web::Json<Vec<PostData>>, MyError> {
     let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
     let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
-    let user_maybe: Option<User> = match bearer_auth {
-        None => None,
-        Some(bearer_auth) => views::users::AuthedUser::from_bearer_token(
-            db_actor_addr.clone(),
-            auth_mgr_addr,
-            bearer_auth,
-        )
-        .await
-        .ok()
-        .map(|authed_user| authed_user.user),
-    };
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr, bearer_auth).await;
     let posts = db_actor_addr
-        .send(selectors::posts::GetPosts::GetPublishedPosts)
+        .send(selectors::posts::GetPosts::GetPublishedPosts(
+            selectors::posts::PostFilter {
+                tag: query.tag.clone(),
+                category: query.category.clone(),
+                content_type_id: None,
+                include_members_only: user_maybe.is_some(),
+            },
+        ))
         .await
         .map_err(|_| MyError::InternalServerError)?
         .map_err(|err| MyError::DieselError(err))?;
+    let posts = posts
+        .iter()
+        .map(|post| PostData::from_post_data(post, user_maybe.clone()))
+        .collect::<Vec<PostData>>();
+    let mut posts = with_authors(db_actor_addr.clone(), posts).await?;
+    // the home listing
+    if query.tag.is_none() && query.category.is_none() {
+        posts = with_pinned_first(db_actor_addr.clone(), posts).await?;
+    }
+    let posts = with_my_reactions(db_actor_addr, &user_maybe, posts).await?;
+    let locales = negotiate_locales(app_state.get_ref(), &req, &query.lang);
     Ok(web::Json(
-        posts
-            .iter()
-            .map(|post| PostData::from_post_data(post, user_maybe.clone()))
-            .collect::<Vec<PostData>>(),
+        with_translations(app_state.get_ref(), &locales, posts).await?,
     ))
 }
 
+#[derive(Serialize, Deserialize, Component)]
+#[serde(rename_all = "snake_case")]
+pub enum DraftStatus {
+    Unpublished,
+    RequestToAdminForPublish,
+    Scheduled,
+    Archived,
+}
+
+impl From<&DraftStatus> for PublishStatus {
+    fn from(status: &DraftStatus) -> Self {
+        match status {
+            DraftStatus::Unpublished => PublishStatus::Unpublished,
+            DraftStatus::RequestToAdminForPublish => PublishStatus::RequestToAdminForPublish,
+            DraftStatus::Scheduled => PublishStatus::Scheduled,
+            DraftStatus::Archived => PublishStatus::Archived,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetDraftsQuery {
+    /// `unpublished` (the default), `request_to_admin_for_publish`, `scheduled` or `archived`
+    status: Option<DraftStatus>,
+}
+
 #[utoipa::path(
     responses(
-        (status = 200, description = "Get Drafts", body = [PostData])
+        (status = 200, description = "Get the posts the user owns or collaborates on in a status, drafts by default", body = [PostData])
     ),
     security(
         ("bearer_auth" = [])
@@ -167,6 +668,7 @@ async fn get_posts(
 #[get("/posts/get_drafts")]
 async fn get_drafts(
     bearer_auth: BearerAuth,
+    query: web::Query<GetDraftsQuery>,
     app_state: web::Data<AppState>,
 ) -> actix_web::Result<web::Json<Vec<PostData>>, MyError> {
     let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
@@ -178,21 +680,69 @@ async fn get_drafts(
     )
     .await?;
     let unpublished_posts = db_actor_addr
-        .send(selectors::posts::GetPosts::GetUnpublishedPosts(
+        .send(selectors::posts::GetPosts::GetOwnedPosts(
             authed_user.user.id,
+            query
+                .status
+                .as_ref()
+                .map_or(PublishStatus::Unpublished, PublishStatus::from),
         ))
         .await
         .map_err(|_| MyError::InternalServerError)?
         .map_err(|err| MyError::DieselError(err))?;
+    let unpublished_posts = unpublished_posts
+        .iter()
+        .map(|post| PostData::from_post_data(post, Some(authed_user.user.clone())))
+        .collect::<Vec<PostData>>();
     Ok(web::Json(
-        unpublished_posts
-            .iter()
-            .map(|post| PostData::from_post_data(post, Some(authed_user.user.clone())))
-            .collect::<Vec<PostData>>(),
+        with_authors(db_actor_addr, unpublished_posts).await?,
     ))
 }
 
-async fn ensure_user_owns_post(
+pub fn post_lookup_error(err: diesel::result::Error) -> MyError {
+    match err {
+        diesel::result::Error::NotFound => MyError::PostNotFound,
+        _ => MyError::DieselError(err),
+    }
+}
+
+/// Role of a user on a post, `None` when they dont collaborate on it.
+pub async fn collaborator_role(
+    db_actor_addr: Addr<DbActor>,
+    post_id: i32,
+    user_id: i32,
+) -> Result<Option<CollaboratorRole>, MyError> {
+    let role = db_actor_addr
+        .send(selectors::collaborators::GetCollaboratorRole {
+            post_id: post_id,
+            user_id: user_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(role.and_then(|role| role.parse::<CollaboratorRole>().ok()))
+}
+
+/// Like `is_visible_to`, collaborators can also see the drafts they work on.
+pub async fn can_view_post(
+    db_actor_addr: Addr<DbActor>,
+    post: &selectors::posts::PostData,
+    user_maybe: &Option<User>,
+    has_access: bool,
+) -> Result<bool, MyError> {
+    if is_visible_to(post, user_maybe, has_access) {
+        return Ok(true);
+    }
+    match user_maybe {
+        Some(user) => Ok(collaborator_role(db_actor_addr, post.id, user.id)
+            .await?
+            .is_some()),
+        None => Ok(false),
+    }
+}
+
+/// Only the owner can delete a post and manage its collaborators.
+pub async fn ensure_user_owns_post(
     db_actor_addr: Addr<DbActor>,
     user_id: i32,
     post_id: i32,
@@ -201,7 +751,7 @@ async fn ensure_user_owns_post(
         .send(selectors::posts::GetPostById { post_id: post_id })
         .await
         .map_err(|_| MyError::InternalServerError)?
-        .map_err(|err| MyError::DieselError(err))?;
+        .map_err(post_lookup_error)?;
     if post.user_id == user_id {
         Ok(post)
     } else {
@@ -209,9 +759,46 @@ async fn ensure_user_owns_post(
     }
 }
 
+/// The owner, co-authors and editors can edit a post.
+pub async fn ensure_user_can_edit_post(
+    db_actor_addr: Addr<DbActor>,
+    user_id: i32,
+    post_id: i32,
+) -> Result<Post, MyError> {
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    if post.user_id == user_id {
+        return Ok(post);
+    }
+    match collaborator_role(db_actor_addr, post_id, user_id).await? {
+        Some(role) if role.can_edit() => Ok(post),
+        _ => Err(MyError::YouDontOwnThisPost),
+    }
+}
+
+pub async fn ensure_user_can_edit_post_or_is_admin(
+    db_actor_addr: Addr<DbActor>,
+    user: &User,
+    post_id: i32,
+) -> Result<Post, MyError> {
+    if user.is_admin {
+        return db_actor_addr
+            .send(selectors::posts::GetPostById { post_id: post_id })
+            .await
+            .map_err(|_| MyError::InternalServerError)?
+            .map_err(post_lookup_error);
+    }
+    ensure_user_can_edit_post(db_actor_addr, user.id, post_id).await
+}
+
 #[derive(Serialize, Deserialize, Component)]
 pub struct UpdatePostSubject {
     new_subject: String,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
 }
 
 async fn update_post_subject(
@@ -219,12 +806,14 @@ async fn update_post_subject(
     post_id: i32,
     new_subject: String,
     user_id: i32,
-) -> actix_web::Result<Post, MyError> {
+    expected_version: i32,
+) -> actix_web::Result<VersionedUpdate, MyError> {
     db_actor_addr
         .send(services::posts::UpdatePostSubject {
             post_id: post_id,
             new_subject: new_subject,
             user_id: user_id,
+            expected_version: expected_version,
         })
         .await
         .map_err(|_| MyError::InternalServerError)?
@@ -237,7 +826,9 @@ async fn update_post_subject(
     ),
     request_body=UpdatePostSubject,
     responses(
-        (status = 200, description = "Update Post Subject", body = PostData)
+        (status = 200, description = "Update Post Subject", body = PostData),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
     ),
     security(
         ("bearer_auth" = [])
@@ -245,48 +836,61 @@ async fn update_post_subject(
 )]
 #[post("/posts/update/subject/{post_id}")]
 async fn update_post_subject_handler(
+    req: HttpRequest,
     path: web::Path<i32>,
     bearer_auth: BearerAuth,
     new_post_subject: web::Json<UpdatePostSubject>,
     app_state: web::Data<AppState>,
-) -> actix_web::Result<web::Json<PostData>, MyError> {
+) -> actix_web::Result<HttpResponse, MyError> {
     let post_id = path.into_inner();
     let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
     let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
     let new_post_subject = new_post_subject.into_inner();
+    let expected_version = expected_version(&req, new_post_subject.expected_version)?;
     let authed_user = views::users::AuthedUser::from_bearer_token(
         db_actor_addr.clone(),
         auth_mgr_addr,
         bearer_auth,
     )
     .await?;
-    let _ = ensure_user_owns_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
-    let post = update_post_subject(
-        db_actor_addr,
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = update_post_subject(
+        db_actor_addr.clone(),
         post_id,
         new_post_subject.new_subject,
         authed_user.user.id,
+        expected_version,
     )
     .await?;
-    Ok(web::Json(PostData::from_post(&post, authed_user.user)))
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
 }
 
 #[derive(Serialize, Deserialize, Component)]
 pub struct UpdatePostBody {
     new_body: String,
+    /// One of `plain`, `markdown` or `html`, keeps the current format if not given
+    new_body_format: Option<String>,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
 }
 
 async fn update_post_body(
     db_actor_addr: Addr<DbActor>,
     post_id: i32,
     new_body: String,
+    new_body_format: Option<BodyFormat>,
     user_id: i32,
-) -> actix_web::Result<Post, MyError> {
+    expected_version: i32,
+) -> actix_web::Result<VersionedUpdate, MyError> {
     db_actor_addr
         .send(services::posts::UpdatePostBody {
             post_id: post_id,
             new_body: new_body,
             user_id: user_id,
+            new_body_format: new_body_format,
+            expected_version: expected_version,
         })
         .await
         .map_err(|_| MyError::InternalServerError)?
@@ -299,7 +903,9 @@ async fn update_post_body(
     ),
     request_body=UpdatePostBody,
     responses(
-        (status = 200, description = "Update Post Body", body = PostData)
+        (status = 200, description = "Update Post Body", body = PostData),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
     ),
     security(
         ("bearer_auth" = [])
@@ -307,30 +913,37 @@ async fn update_post_body(
 )]
 #[post("/posts/update/body/{post_id}")]
 async fn update_post_body_handler(
+    req: HttpRequest,
     path: web::Path<i32>,
     bearer_auth: BearerAuth,
     new_post_body: web::Json<UpdatePostBody>,
     app_state: web::Data<AppState>,
-) -> actix_web::Result<web::Json<PostData>, MyError> {
+) -> actix_web::Result<HttpResponse, MyError> {
     let post_id = path.into_inner();
     let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
     let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
     let new_post_body = new_post_body.into_inner();
+    let new_body_format = parse_body_format(new_post_body.new_body_format)?;
+    let expected_version = expected_version(&req, new_post_body.expected_version)?;
     let authed_user = views::users::AuthedUser::from_bearer_token(
         db_actor_addr.clone(),
         auth_mgr_addr,
         bearer_auth,
     )
     .await?;
-    let _ = ensure_user_owns_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
-    let post = update_post_body(
-        db_actor_addr,
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = update_post_body(
+        db_actor_addr.clone(),
         post_id,
         new_post_body.new_body,
+        new_body_format,
         authed_user.user.id,
+        expected_version,
     )
     .await?;
-    Ok(web::Json(PostData::from_post(&post, authed_user.user)))
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
 }
 
 #[utoipa::path(
@@ -338,7 +951,8 @@ async fn update_post_body_handler(
         ("post_id" = i32, path, description = "Post database id"),
     ),
     responses(
-        (status = 200, description = "Delete Post", body = String)
+        (status = 200, description = "Move Post to the trash", body = String),
+        (status = 404, description = "Post Not Found, or already in the trash")
     ),
     security(
         ("bearer_auth" = [])
@@ -367,7 +981,7 @@ async fn delete_post(
         })
         .await
         .map_err(|_| MyError::InternalServerError)?
-        .map_err(|err| MyError::DieselError(err));
+        .map_err(post_lookup_error)?;
     Ok("Success!".to_string())
 }
 
@@ -398,7 +1012,7 @@ async fn request_admin_to_publish(
     )
     .await?;
     let _: Post =
-        ensure_user_owns_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+        ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
     let _ = db_actor_addr
         .send(services::posts::RequestToPublishPost {
             post_id: post_id,
@@ -440,3 +1054,1379 @@ async fn publish_post(
 
     Ok("Success".into())
 }
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct SchedulePostData {
+    publish_at: Option<chrono::NaiveDateTime>,
+    unpublish_at: Option<chrono::NaiveDateTime>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=SchedulePostData,
+    responses(
+        (status = 200, description = "Schedule Post", body = PostData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/schedule/{post_id}")]
+async fn schedule_post(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    schedule: web::Json<SchedulePostData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let schedule = schedule.into_inner();
+    if let (Some(publish_at), Some(unpublish_at)) = (schedule.publish_at, schedule.unpublish_at) {
+        if unpublish_at <= publish_at {
+            return Err(MyError::InvalidSchedule);
+        }
+    }
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let post = db_actor_addr
+        .send(services::posts::SchedulePost {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            publish_at: schedule.publish_at,
+            unpublish_at: schedule.unpublish_at,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        changed_post_data(db_actor_addr, &post, authed_user.user).await?,
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Unpublish Post", body = PostData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/unpublish/{post_id}")]
+async fn unpublish_post(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ =
+        ensure_user_can_edit_post_or_is_admin(db_actor_addr.clone(), &authed_user.user, post_id)
+            .await?;
+    let post = db_actor_addr
+        .send(services::posts::UnpublishPost { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        changed_post_data(db_actor_addr, &post, authed_user.user).await?,
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Archive Post", body = PostData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/archive/{post_id}")]
+async fn archive_post(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let post = db_actor_addr
+        .send(services::posts::ArchivePost {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        changed_post_data(db_actor_addr, &post, authed_user.user).await?,
+    ))
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get posts in the trash", body = [PostData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/posts/trash")]
+async fn get_trash(
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<PostData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let trashed_posts = db_actor_addr
+        .send(selectors::posts::GetPosts::GetTrashedPosts(
+            authed_user.user.id,
+        ))
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let trashed_posts = trashed_posts
+        .iter()
+        .map(|post| PostData::from_post_data(post, Some(authed_user.user.clone())))
+        .collect::<Vec<PostData>>();
+    Ok(web::Json(with_authors(db_actor_addr, trashed_posts).await?))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Restore Post from the trash", body = PostData),
+        (status = 404, description = "Post Not Found in the trash")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/restore/{post_id}")]
+async fn restore_post(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let post = db_actor_addr
+        .send(services::posts::RestorePost {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    Ok(web::Json(
+        changed_post_data(db_actor_addr, &post, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdatePostSlug {
+    new_slug: String,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=UpdatePostSlug,
+    responses(
+        (status = 200, description = "Update Post Slug, the old slug keeps redirecting", body = PostData),
+        (status = 400, description = "Slug has nothing to keep once slugified"),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/update/slug/{post_id}")]
+async fn update_post_slug_handler(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    new_post_slug: web::Json<UpdatePostSlug>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let new_post_slug = new_post_slug.into_inner();
+    let expected_version = expected_version(&req, new_post_slug.expected_version)?;
+    if slugify(&new_post_slug.new_slug).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = db_actor_addr
+        .send(services::posts::UpdatePostSlug {
+            post_id: post_id,
+            new_slug: new_post_slug.new_slug,
+            user_id: authed_user.user.id,
+            expected_version: expected_version,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PatchPostData {
+    subject: Option<String>,
+    body: Option<String>,
+    /// One of `plain`, `markdown` or `html`
+    body_format: Option<String>,
+    /// The old slug keeps redirecting
+    slug: Option<String>,
+    comments_enabled: Option<bool>,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
+}
+
+impl PatchPostData {
+    /// Validates every given field, reporting all the invalid ones together.
+    fn into_changes(self) -> Result<services::posts::PostChanges, MyError> {
+        let mut invalid = BTreeMap::new();
+        if let Some(subject) = &self.subject {
+            if subject.trim().is_empty() {
+                invalid.insert("subject".to_string(), "must not be empty".to_string());
+            }
+        }
+        let body_format = match parse_body_format(self.body_format) {
+            Ok(body_format) => body_format,
+            Err(err) => {
+                invalid.insert("body_format".to_string(), err.to_string());
+                None
+            }
+        };
+        if let Some(slug) = &self.slug {
+            if slugify(slug).is_empty() {
+                invalid.insert("slug".to_string(), MyError::InvalidName.to_string());
+            }
+        }
+        if !invalid.is_empty() {
+            return Err(MyError::InvalidFields(invalid));
+        }
+        Ok(services::posts::PostChanges {
+            subject: self.subject,
+            body: self.body,
+            body_format: body_format,
+            slug: self.slug,
+            comments_enabled: self.comments_enabled,
+            fields: None,
+        })
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=PatchPostData,
+    responses(
+        (status = 200, description = "Update any of the given fields of a Post at once", body = PostData),
+        (status = 400, description = "Some fields are invalid, reported per field"),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[patch("/posts/{post_id:\\d+}")]
+async fn patch_post(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    patch_post_data: web::Json<PatchPostData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let patch_post_data = patch_post_data.into_inner();
+    let expected_version = expected_version(&req, patch_post_data.expected_version)?;
+    let changes = patch_post_data.into_changes()?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = db_actor_addr
+        .send(services::posts::UpdatePost {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            changes: changes,
+            expected_version: expected_version,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("slug" = String, path, description = "Post or translation slug"),
+    ),
+    responses(
+        (status = 200, description = "Get Published Post by slug, password-protected posts need the `X-Post-Access-Token` header. The slug of a translation shows the post in its locale", body = PostData),
+        (status = 301, description = "Slug was changed, redirects to the current slug"),
+        (status = 401, description = "Post is for members only"),
+        (status = 403, description = "Post is password-protected"),
+        (status = 404, description = "Post Not Found")
+    )
+)]
+#[get("/posts/by_slug/{slug}")]
+async fn get_post_by_slug(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<LangQuery>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let slug = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr.clone(), bearer_auth).await;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPublishedPostBySlug { slug: slug.clone() })
+        .await
+        .map_err(|_| MyError::InternalServerError)?;
+    let (post, locales) = match post {
+        Ok(post) => (
+            post,
+            negotiate_locales(app_state.get_ref(), &req, &query.lang),
+        ),
+        Err(diesel::result::Error::NotFound) => {
+            let translation = db_actor_addr
+                .send(selectors::translations::GetPublishedTranslationBySlug { slug: slug.clone() })
+                .await
+                .map_err(|_| MyError::InternalServerError)?
+                .optional()
+                .map_err(|err| MyError::DieselError(err))?;
+            match translation {
+                // the slug of a translation picks its locale, whatever was asked for
+                Some(translation) => {
+                    let post = db_actor_addr
+                        .send(selectors::posts::GetPostDataById {
+                            post_id: translation.post_id,
+                        })
+                        .await
+                        .map_err(|_| MyError::InternalServerError)?
+                        .map_err(post_lookup_error)?;
+                    (post, vec![translation.locale])
+                }
+                None => {
+                    let current_slug = db_actor_addr
+                        .send(selectors::posts::GetSlugRedirect { slug: slug })
+                        .await
+                        .map_err(|_| MyError::InternalServerError)?
+                        .map_err(post_lookup_error)?;
+                    return Ok(HttpResponse::MovedPermanently()
+                        .insert_header((
+                            header::LOCATION,
+                            format!("/posts/by_slug/{}", current_slug),
+                        ))
+                        .finish());
+                }
+            }
+        }
+        Err(err) => return Err(MyError::DieselError(err)),
+    };
+    let has_access = has_post_access(&req, auth_mgr_addr, post.id).await;
+    if !can_view_post(db_actor_addr.clone(), &post, &user_maybe, has_access).await? {
+        return Err(not_visible_error(&post));
+    }
+    views::analytics::record_view(app_state.get_ref(), &req, &post, &user_maybe);
+    let post = PostData::from_post_data(&post, user_maybe.clone());
+    let posts = with_authors(db_actor_addr.clone(), vec![post]).await?;
+    let posts = with_series(db_actor_addr.clone(), posts).await?;
+    let posts = with_my_reactions(db_actor_addr, &user_maybe, posts).await?;
+    let mut posts = with_translations(app_state.get_ref(), &locales, posts).await?;
+    Ok(post_response(posts.remove(0)))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Get Post, drafts are only visible to their owner, collaborators and admins", body = PostData),
+        (status = 401, description = "Post is for members only"),
+        (status = 403, description = "Post is password-protected, send the `X-Post-Access-Token` header"),
+        (status = 404, description = "Post Not Found")
+    )
+)]
+#[get("/posts/{post_id:\\d+}")]
+async fn get_post(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    query: web::Query<LangQuery>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr.clone(), bearer_auth).await;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    if can_view_post(db_actor_addr.clone(), &post, &user_maybe, has_access).await? {
+        views::analytics::record_view(app_state.get_ref(), &req, &post, &user_maybe);
+        let post = PostData::from_post_data(&post, user_maybe.clone());
+        let posts = with_authors(db_actor_addr.clone(), vec![post]).await?;
+        let posts = with_series(db_actor_addr.clone(), posts).await?;
+        let posts = with_my_reactions(db_actor_addr, &user_maybe, posts).await?;
+        let locales = negotiate_locales(app_state.get_ref(), &req, &query.lang);
+        let mut posts = with_translations(app_state.get_ref(), &locales, posts).await?;
+        Ok(post_response(posts.remove(0)))
+    } else {
+        // dont leak the existence of drafts
+        Err(not_visible_error(&post))
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PostTaxonomyData {
+    tags: Vec<TagData>,
+    categories: Vec<CategoryData>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Get the tags and categories of a Post", body = PostTaxonomyData),
+        (status = 404, description = "Post Not Found")
+    )
+)]
+#[get("/posts/taxonomy/{post_id}")]
+async fn get_post_taxonomy(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostTaxonomyData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr.clone(), bearer_auth).await;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    if !can_view_post(db_actor_addr.clone(), &post, &user_maybe, has_access).await? {
+        return Err(not_visible_error(&post));
+    }
+    let tags = db_actor_addr
+        .send(selectors::tags::GetPostTags { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let categories = db_actor_addr
+        .send(selectors::categories::GetPostCategories { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(PostTaxonomyData {
+        tags: tags.iter().map(TagData::from_tag).collect::<Vec<TagData>>(),
+        categories: categories
+            .iter()
+            .map(CategoryData::from_category)
+            .collect::<Vec<CategoryData>>(),
+    }))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdatePostTags {
+    tag_names: Vec<String>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=UpdatePostTags,
+    responses(
+        (status = 200, description = "Replace the tags of a Post, missing tags are created", body = [TagData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/update/tags/{post_id}")]
+async fn update_post_tags_handler(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    new_post_tags: web::Json<UpdatePostTags>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<TagData>>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let new_post_tags = new_post_tags.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    if new_post_tags
+        .tag_names
+        .iter()
+        .any(|tag_name| slugify(tag_name).is_empty())
+    {
+        return Err(MyError::InvalidName);
+    }
+    let tags = db_actor_addr
+        .send(services::tags::SetPostTags {
+            post_id: post_id,
+            tag_names: new_post_tags.tag_names,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        tags.iter().map(TagData::from_tag).collect::<Vec<TagData>>(),
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdatePostCategories {
+    category_ids: Vec<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=UpdatePostCategories,
+    responses(
+        (status = 200, description = "Replace the categories of a Post", body = [CategoryData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/update/categories/{post_id}")]
+async fn update_post_categories_handler(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    new_post_categories: web::Json<UpdatePostCategories>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<CategoryData>>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let new_post_categories = new_post_categories.into_inner();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let categories = db_actor_addr
+        .send(services::categories::SetPostCategories {
+            post_id: post_id,
+            category_ids: new_post_categories.category_ids,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| match err {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                _,
+            ) => MyError::CategoryNotFound,
+            _ => MyError::DieselError(err),
+        })?;
+    Ok(web::Json(
+        categories
+            .iter()
+            .map(CategoryData::from_category)
+            .collect::<Vec<CategoryData>>(),
+    ))
+}
+
+async fn edit_post_blocks(
+    db_actor_addr: Addr<DbActor>,
+    post_id: i32,
+    user_id: i32,
+    edit: BlockEdit,
+    expected_version: i32,
+) -> actix_web::Result<VersionedUpdate, MyError> {
+    db_actor_addr
+        .send(services::posts::EditPostBlocks {
+            post_id: post_id,
+            user_id: user_id,
+            edit: edit,
+            expected_version: expected_version,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| match err {
+            BlockEditError::DieselError(err) => post_lookup_error(err),
+            BlockEditError::BlockNotFound(block_id) => MyError::BlockNotFound(block_id),
+            BlockEditError::InvalidBlock(msg) => MyError::InvalidBlock(msg),
+        })
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdatePostBlocks {
+    blocks: Vec<Block>,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=UpdatePostBlocks,
+    responses(
+        (status = 200, description = "Replace all content blocks of a Post", body = PostData),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/update/blocks/{post_id}")]
+async fn update_post_blocks_handler(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    new_post_blocks: web::Json<UpdatePostBlocks>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let new_post_blocks = new_post_blocks.into_inner();
+    let expected_version = expected_version(&req, new_post_blocks.expected_version)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = edit_post_blocks(
+        db_actor_addr.clone(),
+        post_id,
+        authed_user.user.id,
+        BlockEdit::ReplaceAll(new_post_blocks.blocks),
+        expected_version,
+    )
+    .await?;
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct InsertPostBlock {
+    block: Block,
+    /// Inserts at the start when not given
+    after_block_id: Option<String>,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=InsertPostBlock,
+    responses(
+        (status = 200, description = "Insert a content block", body = PostData),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/blocks/insert/{post_id}")]
+async fn insert_post_block(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    new_block: web::Json<InsertPostBlock>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let new_block = new_block.into_inner();
+    let expected_version = expected_version(&req, new_block.expected_version)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = edit_post_blocks(
+        db_actor_addr.clone(),
+        post_id,
+        authed_user.user.id,
+        BlockEdit::Insert {
+            block: new_block.block,
+            after_block_id: new_block.after_block_id,
+        },
+        expected_version,
+    )
+    .await?;
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdatePostBlock {
+    block: Block,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+        ("block_id" = String, path, description = "Content block id"),
+    ),
+    request_body=UpdatePostBlock,
+    responses(
+        (status = 200, description = "Replace a single content block", body = PostData),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/blocks/update/{post_id}/{block_id}")]
+async fn update_post_block(
+    req: HttpRequest,
+    path: web::Path<(i32, String)>,
+    bearer_auth: BearerAuth,
+    new_block: web::Json<UpdatePostBlock>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let (post_id, block_id) = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let new_block = new_block.into_inner();
+    let expected_version = expected_version(&req, new_block.expected_version)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = edit_post_blocks(
+        db_actor_addr.clone(),
+        post_id,
+        authed_user.user.id,
+        BlockEdit::Update {
+            block_id: block_id,
+            block: new_block.block,
+        },
+        expected_version,
+    )
+    .await?;
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+        ("block_id" = String, path, description = "Content block id"),
+        ("If-Match" = String, header, description = "ETag of the version being edited"),
+    ),
+    responses(
+        (status = 200, description = "Delete a content block", body = PostData),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/blocks/delete/{post_id}/{block_id}")]
+async fn delete_post_block(
+    req: HttpRequest,
+    path: web::Path<(i32, String)>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let (post_id, block_id) = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let expected_version = expected_version(&req, None)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = edit_post_blocks(
+        db_actor_addr.clone(),
+        post_id,
+        authed_user.user.id,
+        BlockEdit::Delete { block_id: block_id },
+        expected_version,
+    )
+    .await?;
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct MovePostBlock {
+    /// Moves to the start when not given
+    after_block_id: Option<String>,
+    /// Version being edited, required unless sent as `If-Match`
+    expected_version: Option<i32>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+        ("block_id" = String, path, description = "Content block id"),
+    ),
+    request_body=MovePostBlock,
+    responses(
+        (status = 200, description = "Move a content block", body = PostData),
+        (status = 409, description = "Post was changed since the expected version, holds the current Post"),
+        (status = 428, description = "Neither `If-Match` nor `expected_version` was sent")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/blocks/move/{post_id}/{block_id}")]
+async fn move_post_block(
+    req: HttpRequest,
+    path: web::Path<(i32, String)>,
+    bearer_auth: BearerAuth,
+    position: web::Json<MovePostBlock>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let (post_id, block_id) = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let position = position.into_inner();
+    let expected_version = expected_version(&req, position.expected_version)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let update = edit_post_blocks(
+        db_actor_addr.clone(),
+        post_id,
+        authed_user.user.id,
+        BlockEdit::Move {
+            block_id: block_id,
+            after_block_id: position.after_block_id,
+        },
+        expected_version,
+    )
+    .await?;
+    Ok(post_response(
+        versioned_post_data(db_actor_addr, update, authed_user.user).await?,
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Get the media attached to a Post", body = [MediaData]),
+        (status = 404, description = "Post Not Found")
+    )
+)]
+#[get("/posts/media/{post_id}")]
+async fn get_post_media(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<MediaData>>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr.clone(), bearer_auth).await;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    if !can_view_post(db_actor_addr.clone(), &post, &user_maybe, has_access).await? {
+        return Err(not_visible_error(&post));
+    }
+    let media = db_actor_addr
+        .send(selectors::media::GetPostMedia { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        media
+            .iter()
+            .map(MediaData::from_media)
+            .collect::<Vec<MediaData>>(),
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct AttachPostMedia {
+    media_id: i32,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=AttachPostMedia,
+    responses(
+        (status = 200, description = "Attach Media from the library of the user to a Post", body = [MediaData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/media/attach/{post_id}")]
+async fn attach_post_media(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    attach_post_media: web::Json<AttachPostMedia>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<MediaData>>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let media_id = attach_post_media.into_inner().media_id;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let _ = views::media::ensure_user_owns_media_or_is_admin(
+        db_actor_addr.clone(),
+        &authed_user.user,
+        media_id,
+    )
+    .await?;
+    let _ = db_actor_addr
+        .send(services::media::AttachMediaToPost {
+            post_id: post_id,
+            media_id: media_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let media = db_actor_addr
+        .send(selectors::media::GetPostMedia { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        media
+            .iter()
+            .map(MediaData::from_media)
+            .collect::<Vec<MediaData>>(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+        ("media_id" = i32, path, description = "Media database id"),
+    ),
+    responses(
+        (status = 200, description = "Detach Media from a Post, the media stays in the library", body = [MediaData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/media/detach/{post_id}/{media_id}")]
+async fn detach_post_media(
+    path: web::Path<(i32, i32)>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<MediaData>>, MyError> {
+    let (post_id, media_id) = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let _ = db_actor_addr
+        .send(services::media::DetachMediaFromPost {
+            post_id: post_id,
+            media_id: media_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let media = db_actor_addr
+        .send(selectors::media::GetPostMedia { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        media
+            .iter()
+            .map(MediaData::from_media)
+            .collect::<Vec<MediaData>>(),
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdatePostCommentsEnabled {
+    enabled: bool,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=UpdatePostCommentsEnabled,
+    responses(
+        (status = 200, description = "Turn comments on or off for a Post, existing comments stay visible", body = PostData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/update/comments_enabled/{post_id}")]
+async fn update_post_comments_enabled_handler(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    comments_enabled: web::Json<UpdatePostCommentsEnabled>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let post = db_actor_addr
+        .send(services::posts::SetPostCommentsEnabled {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            enabled: comments_enabled.into_inner().enabled,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    Ok(web::Json(
+        changed_post_data(db_actor_addr, &post, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdatePostVisibility {
+    /// One of `public`, `unlisted`, `members-only` or `password-protected`
+    visibility: String,
+    /// Required for `password-protected`, ignored otherwise
+    password: Option<String>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=UpdatePostVisibility,
+    responses(
+        (status = 200, description = "Change who can read a Post once published, unlisted posts are left out of listings", body = PostData),
+        (status = 400, description = "Unknown visibility, or no password for a password-protected Post")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/update/visibility/{post_id}")]
+async fn update_post_visibility_handler(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    visibility_data: web::Json<UpdatePostVisibility>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let visibility_data = visibility_data.into_inner();
+    let visibility = visibility_data
+        .visibility
+        .parse::<Visibility>()
+        .map_err(|_| MyError::InvalidVisibility)?;
+    let password = visibility_data
+        .password
+        .filter(|password| !password.is_empty());
+    if visibility == Visibility::PasswordProtected && password.is_none() {
+        return Err(MyError::PasswordRequired);
+    }
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let post = db_actor_addr
+        .send(services::posts::SetPostVisibility {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            visibility: visibility,
+            password: password,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    Ok(web::Json(
+        changed_post_data(db_actor_addr, &post, authed_user.user).await?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UnlockPostInput {
+    password: String,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PostAccessToken {
+    /// Send it as the `X-Post-Access-Token` header when reading the Post
+    token: String,
+    expires_in_secs: u32,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=UnlockPostInput,
+    responses(
+        (status = 200, description = "Get a short-lived access token for a password-protected Post", body = PostAccessToken),
+        (status = 400, description = "Incorrect Password"),
+        (status = 404, description = "Post Not Found")
+    )
+)]
+#[post("/posts/unlock/{post_id}")]
+async fn unlock_post(
+    path: web::Path<i32>,
+    unlock_input: web::Json<UnlockPostInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PostAccessToken>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    if post.published_status != format!("{}", PublishStatus::Published) {
+        return Err(MyError::PostNotFound);
+    }
+    let password_hash = match post.access_password_hash {
+        Some(password_hash) => password_hash,
+        None => return Err(MyError::PostNotFound),
+    };
+    let password = unlock_input.into_inner().password;
+    if !argon2_password_hasher::validate_password(password.as_bytes(), &password_hash) {
+        return Err(MyError::IncorrectPassword);
+    }
+    let token = auth_mgr_addr
+        .send(CreateExpiringToken {
+            data: PostAccessClaim { post_id: post.id },
+            expiration_duration: POST_ACCESS_TOKEN_SECS,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .ok_or(MyError::TokenCreationError)?;
+    Ok(web::Json(PostAccessToken {
+        token: token,
+        expires_in_secs: POST_ACCESS_TOKEN_SECS,
+    }))
+}
+
+const MAX_BULK_POSTS: usize = 100;
+
+#[derive(Serialize, Deserialize, Component)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkPostActionKind {
+    Delete,
+    Archive,
+    RequestPublish,
+    Publish,
+    AddTag,
+    RemoveTag,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct BulkPostActionData {
+    post_ids: Vec<i32>,
+    /// `delete`, `archive`, `request_publish`, `publish`, `add_tag` or `remove_tag`
+    action: BulkPostActionKind,
+    /// Required for `add_tag` and `remove_tag`
+    tag_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct BulkPostResult {
+    post_id: i32,
+    /// `done`, `not_found` or `not_permitted`
+    outcome: String,
+}
+
+#[utoipa::path(
+    request_body=BulkPostActionData,
+    responses(
+        (status = 200, description = "Apply an action to many Posts at once, with the outcome for each Post", body = [BulkPostResult])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/bulk")]
+async fn bulk_post_action(
+    bearer_auth: BearerAuth,
+    bulk_post_action_data: web::Json<BulkPostActionData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<BulkPostResult>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let bulk_post_action_data = bulk_post_action_data.into_inner();
+    if bulk_post_action_data.post_ids.len() > MAX_BULK_POSTS {
+        return Err(MyError::TooManyPosts(MAX_BULK_POSTS));
+    }
+    let tag_name = match bulk_post_action_data.tag_name {
+        Some(tag_name) if !slugify(&tag_name).is_empty() => Ok(tag_name),
+        _ => Err(MyError::InvalidName),
+    };
+    let action = match bulk_post_action_data.action {
+        BulkPostActionKind::Delete => services::posts::BulkAction::Delete,
+        BulkPostActionKind::Archive => services::posts::BulkAction::Archive,
+        BulkPostActionKind::RequestPublish => services::posts::BulkAction::RequestPublish,
+        BulkPostActionKind::Publish => services::posts::BulkAction::Publish,
+        BulkPostActionKind::AddTag => services::posts::BulkAction::AddTag(tag_name?),
+        BulkPostActionKind::RemoveTag => services::posts::BulkAction::RemoveTag(tag_name?),
+    };
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let outcomes = db_actor_addr
+        .send(services::posts::BulkPostAction {
+            post_ids: bulk_post_action_data.post_ids,
+            user_id: authed_user.user.id,
+            is_admin: authed_user.user.is_admin,
+            action: action,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        outcomes
+            .iter()
+            .map(|(post_id, outcome)| BulkPostResult {
+                post_id: *post_id,
+                outcome: format!("{}", outcome),
+            })
+            .collect::<Vec<BulkPostResult>>(),
+    ))
+}
@@ -0,0 +1,288 @@
+use crate::{
+    auth::actor::{CreateExpiringToken, ExtractClaim},
+    db::{models::previews::PostPreview, selectors, services},
+    errors::MyError,
+    views::{
+        self,
+        posts::{ensure_user_can_edit_post, post_lookup_error, with_authors, PostData},
+    },
+    AppState,
+};
+use actix_web::{get, http::header, post, web, HttpResponse};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::vec::Vec;
+use utoipa::Component;
+
+const DEFAULT_PREVIEW_HOURS: u32 = 72;
+const MAX_PREVIEW_HOURS: u32 = 30 * 24;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_post_preview)
+        .service(get_post_previews)
+        .service(revoke_post_preview)
+        .service(get_preview);
+}
+
+/// Data signed into a preview token. Not a plain user id, so a preview token can never
+/// pass as a login token, nor as a post access token.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+struct PreviewClaim {
+    preview_id: i32,
+    post_id: i32,
+    post_version: i32,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PreviewData {
+    id: i32,
+    post_id: i32,
+    /// The link shows this version of the post only
+    post_version: i32,
+    created_by: i32,
+    expires_at: chrono::NaiveDateTime,
+    created_at: chrono::NaiveDateTime,
+}
+
+impl PreviewData {
+    fn from_preview(preview: &PostPreview) -> Self {
+        PreviewData {
+            id: preview.id,
+            post_id: preview.post_id,
+            post_version: preview.post_version,
+            created_by: preview.created_by,
+            expires_at: preview.expires_at,
+            created_at: preview.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct PreviewLinkData {
+    preview: PreviewData,
+    token: String,
+    /// Path of the public preview
+    url: String,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CreatePreviewData {
+    /// 72 by default, at most 720
+    expires_in_hours: Option<u32>,
+}
+
+fn preview_lookup_error(err: diesel::result::Error) -> MyError {
+    match err {
+        diesel::result::Error::NotFound => MyError::PreviewNotFound,
+        _ => MyError::DieselError(err),
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=CreatePreviewData,
+    responses(
+        (status = 200, description = "Create a link showing the current version of a Post to anyone holding it", body = PreviewLinkData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/previews/create/{post_id}")]
+async fn create_post_preview(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    create_preview_data: web::Json<CreatePreviewData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<PreviewLinkData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let expires_in_hours = create_preview_data
+        .expires_in_hours
+        .unwrap_or(DEFAULT_PREVIEW_HOURS)
+        .clamp(1, MAX_PREVIEW_HOURS);
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr.clone(),
+        bearer_auth,
+    )
+    .await?;
+    let post =
+        ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let preview = db_actor_addr
+        .send(services::previews::AddPostPreview {
+            post_id: post.id,
+            post_version: post.version,
+            created_by: authed_user.user.id,
+            expires_at: chrono::Utc::now().naive_utc()
+                + chrono::Duration::hours(expires_in_hours as i64),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let token = auth_mgr_addr
+        .send(CreateExpiringToken {
+            data: PreviewClaim {
+                preview_id: preview.id,
+                post_id: preview.post_id,
+                post_version: preview.post_version,
+            },
+            expiration_duration: expires_in_hours * 60 * 60,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .ok_or(MyError::TokenCreationError)?;
+    Ok(web::Json(PreviewLinkData {
+        preview: PreviewData::from_preview(&preview),
+        url: format!("/posts/preview/{}", token),
+        token: token,
+    }))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Get the preview links of a Post that are neither expired nor revoked", body = [PreviewData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/posts/previews/{post_id}")]
+async fn get_post_previews(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<PreviewData>>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let previews = db_actor_addr
+        .send(selectors::previews::GetActivePostPreviews { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        previews
+            .iter()
+            .map(PreviewData::from_preview)
+            .collect::<Vec<PreviewData>>(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("preview_id" = i32, path, description = "Preview database id"),
+    ),
+    responses(
+        (status = 200, description = "Revoke a preview link", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/previews/revoke/{preview_id}")]
+async fn revoke_post_preview(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<String, MyError> {
+    let preview_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let preview = db_actor_addr
+        .send(selectors::previews::GetPostPreview {
+            preview_id: preview_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(preview_lookup_error)?;
+    let _ = ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, preview.post_id)
+        .await?;
+    let _ = db_actor_addr
+        .send(services::previews::RevokePostPreview {
+            preview_id: preview_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(preview_lookup_error)?;
+    Ok("Success!".to_string())
+}
+
+#[utoipa::path(
+    params(
+        ("token" = String, path, description = "Preview token"),
+    ),
+    responses(
+        (status = 200, description = "Show the version of a Post a preview link was made for, no account needed", body = PostData),
+        (status = 404, description = "Preview link is invalid, expired or revoked"),
+        (status = 410, description = "Post was changed since the preview link was created")
+    )
+)]
+#[get("/posts/preview/{token}")]
+async fn get_preview(
+    path: web::Path<String>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse, MyError> {
+    let token = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let claim = auth_mgr_addr
+        .send(ExtractClaim {
+            token: token,
+            phantom: PhantomData::<PreviewClaim>,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|_| MyError::PreviewNotFound)?;
+    let preview = db_actor_addr
+        .send(selectors::previews::GetPostPreview {
+            preview_id: claim.preview_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(preview_lookup_error)?;
+    if preview.revoked_at.is_some()
+        || preview.post_id != claim.post_id
+        || preview.expires_at <= chrono::Utc::now().naive_utc()
+    {
+        return Err(MyError::PreviewNotFound);
+    }
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById {
+            post_id: claim.post_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    if post.version != claim.post_version {
+        return Err(MyError::PreviewOutdated);
+    }
+    // read only, whoever holds the link is treated as an anonymous reader
+    let post = PostData::from_post_data(&post, None);
+    let mut posts = with_authors(db_actor_addr, vec![post]).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("X-Robots-Tag", "noindex"))
+        .insert_header((header::CACHE_CONTROL, "private, no-store"))
+        .json(posts.remove(0)))
+}
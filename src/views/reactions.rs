@@ -0,0 +1,177 @@
+use crate::{
+    db::{actor::DbActor, models::posts::PublishStatus, models::users::User, selectors, services},
+    errors::MyError,
+    views::{
+        self,
+        posts::{has_post_access, is_visible_to, not_visible_error, post_lookup_error},
+    },
+    AppState,
+};
+use actix::Addr;
+use actix_web::{get, post, web, HttpRequest};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+use utoipa::Component;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_reaction_emojis)
+        .service(set_post_reaction)
+        .service(remove_post_reaction);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct ReactionInput {
+    emoji: String,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct ReactionSummary {
+    post_id: i32,
+    /// Number of reactions per emoji
+    reaction_counts: serde_json::Value,
+    my_reaction: Option<String>,
+}
+
+/// Only published posts can be reacted to.
+async fn ensure_user_can_react(
+    db_actor_addr: Addr<DbActor>,
+    user: &User,
+    post_id: i32,
+    has_access: bool,
+) -> Result<(), MyError> {
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    if !is_visible_to(&post, &Some(user.clone()), has_access) {
+        Err(not_visible_error(&post))
+    } else if post.status == format!("{}", PublishStatus::Published) {
+        Ok(())
+    } else {
+        Err(MyError::PostNotFound)
+    }
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get the emojis posts can be reacted with", body = [String])
+    )
+)]
+#[get("/posts/reactions/emojis")]
+async fn get_reaction_emojis(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<String>>, MyError> {
+    Ok(web::Json(app_state.get_ref().reaction_emojis.clone()))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=ReactionInput,
+    responses(
+        (status = 200, description = "React to a published Post, replaces the previous reaction of the user", body = ReactionSummary),
+        (status = 404, description = "Post Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/reactions/set/{post_id}")]
+async fn set_post_reaction(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    reaction: web::Json<ReactionInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<ReactionSummary>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let emoji = reaction.into_inner().emoji;
+    if !app_state.get_ref().reaction_emojis.contains(&emoji) {
+        return Err(MyError::InvalidReaction);
+    }
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr.clone(),
+        bearer_auth,
+    )
+    .await?;
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    ensure_user_can_react(
+        db_actor_addr.clone(),
+        &authed_user.user,
+        post_id,
+        has_access,
+    )
+    .await?;
+    let reaction_counts = db_actor_addr
+        .send(services::reactions::SetPostReaction {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            emoji: Some(emoji.clone()),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    Ok(web::Json(ReactionSummary {
+        post_id: post_id,
+        reaction_counts: reaction_counts,
+        my_reaction: Some(emoji),
+    }))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Remove the reaction of the user from a Post", body = ReactionSummary),
+        (status = 404, description = "Post Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/reactions/remove/{post_id}")]
+async fn remove_post_reaction(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<ReactionSummary>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr.clone(),
+        bearer_auth,
+    )
+    .await?;
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    ensure_user_can_react(
+        db_actor_addr.clone(),
+        &authed_user.user,
+        post_id,
+        has_access,
+    )
+    .await?;
+    let reaction_counts = db_actor_addr
+        .send(services::reactions::SetPostReaction {
+            post_id: post_id,
+            user_id: authed_user.user.id,
+            emoji: None,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    Ok(web::Json(ReactionSummary {
+        post_id: post_id,
+        reaction_counts: reaction_counts,
+        my_reaction: None,
+    }))
+}
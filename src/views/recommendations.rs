@@ -0,0 +1,145 @@
+use crate::{
+    db::{
+        models::posts::{PublishStatus, Visibility},
+        selectors,
+    },
+    errors::MyError,
+    recommendations::actor::{CacheRelatedPosts, GetCachedRelatedPosts},
+    views::{
+        posts::{
+            can_view_post, has_post_access, not_visible_error, optional_user, post_lookup_error,
+            with_authors, with_translations, PostData,
+        },
+        translations::negotiate_locales,
+    },
+    AppState,
+};
+use actix_web::{get, web, HttpRequest};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use serde::Deserialize;
+use std::vec::Vec;
+use utoipa::IntoParams;
+
+const DEFAULT_RELATED_POSTS_LIMIT: i64 = 5;
+/// Also the number of related posts computed and cached per post.
+const MAX_RELATED_POSTS_LIMIT: i64 = 20;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_related_posts);
+}
+
+/// Ids of the related posts of `post_id`, from the cache unless a post changed since they were
+/// computed. Failures of the cache are logged and the related posts computed again.
+async fn related_post_ids(app_state: &AppState, post_id: i32) -> Result<Vec<i32>, MyError> {
+    let db_actor_addr = app_state.db_actor_addr.clone();
+    let stamp = db_actor_addr
+        .send(selectors::recommendations::GetRelatedPostsStamp)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    let cached = app_state
+        .related_posts_cache_addr
+        .send(GetCachedRelatedPosts {
+            post_id: post_id,
+            stamp: stamp.clone(),
+        })
+        .await;
+    match cached {
+        Ok(Ok(Some(related_post_ids))) => return Ok(related_post_ids),
+        Ok(Ok(None)) => {}
+        Ok(Err(err)) => log::error!("Failed reading cached related posts: {}", err),
+        Err(err) => log::error!("Could not reach related posts cache: {}", err),
+    }
+    let related_post_ids = db_actor_addr
+        .send(selectors::recommendations::GetRelatedPostIds {
+            post_id: post_id,
+            limit: MAX_RELATED_POSTS_LIMIT,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    app_state
+        .related_posts_cache_addr
+        .do_send(CacheRelatedPosts {
+            post_id: post_id,
+            stamp: stamp,
+            related_post_ids: related_post_ids.clone(),
+        });
+    Ok(related_post_ids)
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct RelatedPostsQuery {
+    /// Number of posts, 5 by default, at most 20
+    limit: Option<i64>,
+    /// Locale to show posts in, `Accept-Language` is used otherwise
+    lang: Option<String>,
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Published public posts related to a Post, best first. Scored by shared tags, similar text and recency", body = [PostData]),
+        (status = 401, description = "Post is for members only"),
+        (status = 403, description = "Post is password-protected, send the `X-Post-Access-Token` header"),
+        (status = 404, description = "Post Not Found")
+    )
+)]
+#[get("/posts/{post_id}/related")]
+async fn get_related_posts(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    query: web::Query<RelatedPostsQuery>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<PostData>>, MyError> {
+    let post_id = path.into_inner();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_RELATED_POSTS_LIMIT)
+        .clamp(1, MAX_RELATED_POSTS_LIMIT);
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr.clone(), bearer_auth).await;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    if !can_view_post(db_actor_addr.clone(), &post, &user_maybe, has_access).await? {
+        // dont leak the existence of drafts
+        return Err(not_visible_error(&post));
+    }
+
+    let related_post_ids = related_post_ids(app_state.get_ref(), post_id).await?;
+    let related_posts = db_actor_addr
+        .send(selectors::posts::GetPostsDataByIds {
+            post_ids: related_post_ids.clone(),
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    // the cache may be a little behind, anything that stopped being public is left out
+    let posts = related_post_ids
+        .iter()
+        .filter_map(|related_post_id| {
+            related_posts
+                .iter()
+                .find(|related_post| related_post.id == *related_post_id)
+        })
+        .filter(|related_post| {
+            related_post.status == format!("{}", PublishStatus::Published)
+                && related_post.visibility == format!("{}", Visibility::Public)
+        })
+        .take(limit as usize)
+        .map(|related_post| PostData::from_post_data(related_post, user_maybe.clone()))
+        .collect::<Vec<PostData>>();
+    let posts = with_authors(db_actor_addr, posts).await?;
+    let locales = negotiate_locales(app_state.get_ref(), &req, &query.lang);
+    Ok(web::Json(
+        with_translations(app_state.get_ref(), &locales, posts).await?,
+    ))
+}
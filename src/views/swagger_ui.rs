@@ -1,6 +1,10 @@
 use crate::{
     openapi::addons::BearerSecurity,
-    views::{users::*, posts::*},
+    views::{
+        analytics::*, autosaves::*, categories::*, collaborators::*, collections::*, comments::*,
+        content_types::*, feeds::*, media::*, menus::*, pages::*, posts::*, previews::*,
+        reactions::*, recommendations::*, tags::*, translations::*, users::*,
+    },
 };
 use actix_web::{get, http::StatusCode, web, HttpResponse};
 use utoipa::OpenApi;
@@ -24,6 +28,125 @@ use utoipa_swagger_ui::SwaggerUi;
         delete_post,
         request_admin_to_publish,
         publish_post,
+        schedule_post,
+        unpublish_post,
+        archive_post,
+        get_trash,
+        restore_post,
+        update_post_slug_handler,
+        get_post_by_slug,
+        get_post,
+        patch_post,
+        get_post_taxonomy,
+        update_post_tags_handler,
+        update_post_categories_handler,
+        update_post_blocks_handler,
+        insert_post_block,
+        update_post_block,
+        delete_post_block,
+        move_post_block,
+        get_post_media,
+        attach_post_media,
+        detach_post_media,
+        update_post_comments_enabled_handler,
+        update_post_visibility_handler,
+        unlock_post,
+        bulk_post_action,
+        // categories
+        get_categories,
+        create_category,
+        update_category,
+        delete_category,
+        // tags
+        get_tags,
+        get_tag_cloud,
+        create_tag,
+        update_tag,
+        delete_tag,
+        // media
+        upload_media,
+        get_media,
+        get_media_by_id,
+        get_media_variants,
+        delete_media,
+        // comments
+        get_post_comments,
+        create_comment,
+        update_comment,
+        delete_comment,
+        moderate_comment,
+        get_moderation_queue,
+        // reactions
+        get_reaction_emojis,
+        set_post_reaction,
+        remove_post_reaction,
+        // analytics
+        get_post_stats,
+        get_top_posts,
+        // collaborators
+        get_post_collaborators,
+        invite_post_collaborator,
+        remove_post_collaborator,
+        // autosaves
+        get_autosave,
+        save_autosave,
+        discard_autosave,
+        promote_autosave,
+        // previews
+        create_post_preview,
+        get_post_previews,
+        revoke_post_preview,
+        get_preview,
+        // translations
+        get_post_translations,
+        get_translation_review_queue,
+        create_translation,
+        update_translation,
+        request_translation_publish,
+        publish_translation,
+        unpublish_translation,
+        delete_translation,
+        // content types
+        get_content_types,
+        get_content_type,
+        create_content_type,
+        update_content_type,
+        delete_content_type,
+        get_entries,
+        create_entry,
+        update_entry,
+        // pages
+        get_pages,
+        get_all_pages,
+        get_page_by_slug,
+        create_page,
+        update_page,
+        move_page,
+        delete_page,
+        // menus
+        get_menus,
+        get_menu,
+        create_menu,
+        delete_menu,
+        create_menu_item,
+        update_menu_item,
+        move_menu_item,
+        delete_menu_item,
+        // collections
+        get_collections,
+        get_collection,
+        create_collection,
+        update_collection,
+        pin_collection,
+        delete_collection,
+        add_collection_post,
+        remove_collection_post,
+        reorder_collection,
+        // recommendations
+        get_related_posts,
+        // feeds
+        get_feed,
+        get_scoped_feed,
     ),
     components(
         // users
@@ -37,6 +160,95 @@ use utoipa_swagger_ui::SwaggerUi;
         CreatePostData,
         UpdatePostSubject,
         UpdatePostBody,
+        SchedulePostData,
+        UpdatePostSlug,
+        PatchPostData,
+        PostTaxonomyData,
+        UpdatePostTags,
+        UpdatePostCategories,
+        UpdatePostBlocks,
+        InsertPostBlock,
+        UpdatePostBlock,
+        MovePostBlock,
+        AttachPostMedia,
+        UpdatePostCommentsEnabled,
+        UpdatePostVisibility,
+        UnlockPostInput,
+        PostAccessToken,
+        BulkPostActionKind,
+        BulkPostActionData,
+        BulkPostResult,
+        DraftStatus,
+        // categories
+        CategoryData,
+        CategoryInput,
+        // tags
+        TagData,
+        TagCloudEntry,
+        TagInput,
+        // media
+        MediaData,
+        MediaVariantData,
+        MediaVariantsData,
+        // comments
+        CommentData,
+        CommentPageData,
+        CreateCommentData,
+        UpdateCommentData,
+        ModerationAction,
+        ModerateCommentData,
+        // reactions
+        ReactionInput,
+        ReactionSummary,
+        // analytics
+        DailyViews,
+        PostStatsData,
+        TopPostData,
+        // collaborators
+        CollaboratorData,
+        InviteCollaborator,
+        // autosaves
+        AutosaveData,
+        SaveAutosaveData,
+        // previews
+        PreviewData,
+        PreviewLinkData,
+        CreatePreviewData,
+        // translations
+        TranslationData,
+        CreateTranslationData,
+        UpdateTranslationData,
+        // content types
+        ContentTypeData,
+        ContentTypeInput,
+        CreateEntryData,
+        UpdateEntryData,
+        // pages
+        PageData,
+        PageNode,
+        CreatePageData,
+        UpdatePageData,
+        MovePageData,
+        // menus
+        MenuData,
+        MenuTree,
+        MenuItemNode,
+        MenuItemData,
+        MenuInput,
+        MenuItemInput,
+        CreateMenuItemData,
+        MoveMenuItemData,
+        // collections
+        CollectionData,
+        CollectionPostsData,
+        CreateCollectionData,
+        UpdateCollectionData,
+        PinCollectionData,
+        AddCollectionPostData,
+        RemoveCollectionPostData,
+        ReorderCollectionData,
+        SeriesLink,
+        SeriesNavigationData,
     ),
     tags(
         (name = "Content Management System", description = "Content Management System Apis")
@@ -0,0 +1,218 @@
+use crate::{
+    db::{
+        models::tags::Tag,
+        selectors::{self, tags::TagCount},
+        services,
+    },
+    errors::MyError,
+    slugify::slugify,
+    views, AppState,
+};
+use actix_web::{get, post, web};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError, Error::NotFound};
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+use utoipa::Component;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_tags)
+        .service(get_tag_cloud)
+        .service(create_tag)
+        .service(update_tag)
+        .service(delete_tag);
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct TagData {
+    id: i32,
+    name: String,
+    slug: String,
+}
+
+impl TagData {
+    pub fn from_tag(tag: &Tag) -> Self {
+        TagData {
+            id: tag.id,
+            name: tag.name.clone(),
+            slug: tag.slug.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct TagCloudEntry {
+    id: i32,
+    name: String,
+    slug: String,
+    post_count: i64,
+}
+
+impl TagCloudEntry {
+    pub fn from_tag_count(tag_count: &TagCount) -> Self {
+        TagCloudEntry {
+            id: tag_count.id,
+            name: tag_count.name.clone(),
+            slug: tag_count.slug.clone(),
+            post_count: tag_count.post_count,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct TagInput {
+    name: String,
+}
+
+fn tag_error(err: diesel::result::Error) -> MyError {
+    match err {
+        NotFound => MyError::TagNotFound,
+        DatabaseError(DatabaseErrorKind::UniqueViolation, _) => MyError::TagAlreadyExists,
+        _ => MyError::DieselError(err),
+    }
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get Tags", body = [TagData])
+    )
+)]
+#[get("/tags/get_tags")]
+async fn get_tags(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<TagData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let tags = db_actor_addr
+        .send(selectors::tags::GetTags)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        tags.iter().map(TagData::from_tag).collect::<Vec<TagData>>(),
+    ))
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Number of published posts per tag", body = [TagCloudEntry])
+    )
+)]
+#[get("/tags/cloud")]
+async fn get_tag_cloud(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<TagCloudEntry>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let tag_counts = db_actor_addr
+        .send(selectors::tags::GetTagCloud)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        tag_counts
+            .iter()
+            .map(TagCloudEntry::from_tag_count)
+            .collect::<Vec<TagCloudEntry>>(),
+    ))
+}
+
+#[utoipa::path(
+    request_body=TagInput,
+    responses(
+        (status = 200, description = "Create Tag, returns the existing tag if there is one with the same slug", body = TagData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/tags/create")]
+async fn create_tag(
+    bearer_auth: BearerAuth,
+    tag_input: web::Json<TagInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<TagData>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let tag_input = tag_input.into_inner();
+    let _: views::users::AuthedUser = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    if slugify(&tag_input.name).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    let tag = db_actor_addr
+        .send(services::tags::AddTag {
+            name: tag_input.name,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(tag_error)?;
+    Ok(web::Json(TagData::from_tag(&tag)))
+}
+
+#[utoipa::path(
+    params(
+        ("tag_id" = i32, path, description = "Tag database id"),
+    ),
+    request_body=TagInput,
+    responses(
+        (status = 200, description = "Rename Tag", body = TagData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/tags/update/{tag_id}")]
+async fn update_tag(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    tag_input: web::Json<TagInput>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<TagData>, MyError> {
+    let tag_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let tag_input = tag_input.into_inner();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    if slugify(&tag_input.name).is_empty() {
+        return Err(MyError::InvalidName);
+    }
+    let tag = db_actor_addr
+        .send(services::tags::UpdateTag {
+            tag_id: tag_id,
+            name: tag_input.name,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(tag_error)?;
+    Ok(web::Json(TagData::from_tag(&tag)))
+}
+
+#[utoipa::path(
+    params(
+        ("tag_id" = i32, path, description = "Tag database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete Tag", body = TagData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/tags/delete/{tag_id}")]
+async fn delete_tag(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<TagData>, MyError> {
+    let tag_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _: views::users::AuthedUser = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let tag = db_actor_addr
+        .send(services::tags::DeleteTag { tag_id: tag_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(tag_error)?;
+    Ok(web::Json(TagData::from_tag(&tag)))
+}
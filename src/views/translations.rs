@@ -0,0 +1,501 @@
+use crate::{
+    db::{
+        models::{
+            posts::{BodyFormat, PublishStatus},
+            translations::PostTranslation,
+        },
+        selectors, services,
+    },
+    errors::MyError,
+    locale::{locale_chain, normalize_locale, parse_accept_language},
+    views::{
+        self,
+        posts::{
+            can_view_post, ensure_user_can_edit_post, ensure_user_can_edit_post_or_is_admin,
+            has_post_access, not_visible_error, optional_user, parse_body_format,
+            post_lookup_error,
+        },
+    },
+    AppState,
+};
+use actix_web::{get, http::header, post, web, HttpRequest};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError, Error::NotFound};
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+use utoipa::{Component, IntoParams};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_post_translations)
+        .service(get_translation_review_queue)
+        .service(create_translation)
+        .service(update_translation)
+        .service(request_translation_publish)
+        .service(publish_translation)
+        .service(unpublish_translation)
+        .service(delete_translation);
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct LangQuery {
+    /// Locale to show content in, `Accept-Language` is used otherwise
+    pub lang: Option<String>,
+}
+
+/// Locales to show posts in, from `lang` if given or else from `Accept-Language`, followed by
+/// the configured fallbacks.
+pub fn negotiate_locales(
+    app_state: &AppState,
+    req: &HttpRequest,
+    lang: &Option<String>,
+) -> Vec<String> {
+    let requested = match lang.as_ref().and_then(|lang| normalize_locale(lang)) {
+        Some(lang) => vec![lang],
+        None => req
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|accept_language| accept_language.to_str().ok())
+            .map(parse_accept_language)
+            .unwrap_or_default(),
+    };
+    locale_chain(
+        &requested,
+        &app_state.locale_fallbacks,
+        &app_state.supported_locales,
+    )
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct TranslationData {
+    id: i32,
+    post_id: i32,
+    locale: String,
+    subject: String,
+    body: String,
+    body_format: String,
+    /// `body` rendered to sanitized html
+    rendered_body: String,
+    slug: String,
+    status: String,
+    created_by: i32,
+    created_at: chrono::NaiveDateTime,
+    updated_at: chrono::NaiveDateTime,
+}
+
+impl TranslationData {
+    fn from_translation(translation: &PostTranslation) -> Self {
+        TranslationData {
+            id: translation.id,
+            post_id: translation.post_id,
+            locale: translation.locale.clone(),
+            subject: translation.post_subject.clone(),
+            body: translation.post_body.clone(),
+            body_format: translation.body_format.clone(),
+            rendered_body: translation.rendered_body.clone(),
+            slug: translation.slug.clone(),
+            status: translation.published_status.clone(),
+            created_by: translation.created_by,
+            created_at: translation.created_at,
+            updated_at: translation.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct CreateTranslationData {
+    /// One of the supported locales other than the default one, e.g. `de`
+    locale: String,
+    subject: String,
+    body: String,
+    /// One of `plain` (default), `markdown` or `html`
+    body_format: Option<String>,
+    /// Derived from `subject` by default
+    slug: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Component)]
+pub struct UpdateTranslationData {
+    subject: Option<String>,
+    body: Option<String>,
+    /// One of `plain`, `markdown` or `html`
+    body_format: Option<String>,
+    slug: Option<String>,
+}
+
+fn translation_error(err: diesel::result::Error) -> MyError {
+    match err {
+        NotFound => MyError::TranslationNotFound,
+        DatabaseError(DatabaseErrorKind::UniqueViolation, _) => MyError::TranslationAlreadyExists,
+        _ => MyError::DieselError(err),
+    }
+}
+
+async fn get_translation(
+    app_state: &AppState,
+    translation_id: i32,
+) -> Result<PostTranslation, MyError> {
+    app_state
+        .db_actor_addr
+        .send(selectors::translations::GetPostTranslation {
+            translation_id: translation_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(translation_error)
+}
+
+async fn set_translation_status(
+    app_state: &AppState,
+    translation_id: i32,
+    status: PublishStatus,
+) -> Result<PostTranslation, MyError> {
+    app_state
+        .db_actor_addr
+        .send(services::translations::SetPostTranslationStatus {
+            translation_id: translation_id,
+            status: status,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(translation_error)
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    responses(
+        (status = 200, description = "Get the translations of a Post, only the published ones unless the caller can edit it", body = [TranslationData]),
+        (status = 404, description = "Post Not Found")
+    )
+)]
+#[get("/posts/translations/{post_id:\\d+}")]
+async fn get_post_translations(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    bearer_auth: Option<BearerAuth>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<TranslationData>>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let user_maybe = optional_user(db_actor_addr.clone(), auth_mgr_addr.clone(), bearer_auth).await;
+    let post = db_actor_addr
+        .send(selectors::posts::GetPostDataById { post_id: post_id })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(post_lookup_error)?;
+    let has_access = has_post_access(&req, auth_mgr_addr, post_id).await;
+    if !can_view_post(db_actor_addr.clone(), &post, &user_maybe, has_access).await? {
+        return Err(not_visible_error(&post));
+    }
+    let can_edit = match &user_maybe {
+        Some(user) => ensure_user_can_edit_post_or_is_admin(db_actor_addr.clone(), user, post_id)
+            .await
+            .is_ok(),
+        None => false,
+    };
+    let translations = db_actor_addr
+        .send(selectors::translations::GetPostTranslations {
+            post_id: post_id,
+            published_only: !can_edit,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        translations
+            .iter()
+            .map(TranslationData::from_translation)
+            .collect::<Vec<TranslationData>>(),
+    ))
+}
+
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Get the translations waiting to be published, oldest request first", body = [TranslationData])
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[get("/posts/translations/review_queue")]
+async fn get_translation_review_queue(
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<Vec<TranslationData>>, MyError> {
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let _ = views::admins::ensure_admin(bearer_auth, app_state).await?;
+    let translations = db_actor_addr
+        .send(selectors::translations::GetTranslationReviewQueue)
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(|err| MyError::DieselError(err))?;
+    Ok(web::Json(
+        translations
+            .iter()
+            .map(TranslationData::from_translation)
+            .collect::<Vec<TranslationData>>(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+        ("post_id" = i32, path, description = "Post database id"),
+    ),
+    request_body=CreateTranslationData,
+    responses(
+        (status = 200, description = "Translate a Post, the translation is unpublished until an admin publishes it", body = TranslationData),
+        (status = 400, description = "Locale is not supported, or the Post already has a translation into it")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/translations/create/{post_id}")]
+async fn create_translation(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    translation_data: web::Json<CreateTranslationData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<TranslationData>, MyError> {
+    let post_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let translation_data = translation_data.into_inner();
+    let locale = normalize_locale(&translation_data.locale)
+        .filter(|locale| {
+            app_state.get_ref().supported_locales.contains(locale)
+                && locale != &app_state.get_ref().default_locale
+        })
+        .ok_or(MyError::UnsupportedLocale)?;
+    let body_format = parse_body_format(translation_data.body_format)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let post =
+        ensure_user_can_edit_post(db_actor_addr.clone(), authed_user.user.id, post_id).await?;
+    let translation = db_actor_addr
+        .send(services::translations::AddPostTranslation {
+            post_id: post_id,
+            locale: locale,
+            subject: translation_data.subject,
+            body: translation_data.body,
+            // same format as the post unless told otherwise
+            body_format: body_format
+                .or(post.body_format.parse().ok())
+                .unwrap_or(BodyFormat::Plain),
+            slug: translation_data.slug,
+            created_by: authed_user.user.id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(translation_error)?;
+    Ok(web::Json(TranslationData::from_translation(&translation)))
+}
+
+#[utoipa::path(
+    params(
+        ("translation_id" = i32, path, description = "Translation database id"),
+    ),
+    request_body=UpdateTranslationData,
+    responses(
+        (status = 200, description = "Update any of the given fields of a translation", body = TranslationData),
+        (status = 404, description = "Translation Not Found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/translations/update/{translation_id}")]
+async fn update_translation(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    translation_data: web::Json<UpdateTranslationData>,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<TranslationData>, MyError> {
+    let translation_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let translation_data = translation_data.into_inner();
+    let body_format = parse_body_format(translation_data.body_format)?;
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let translation = get_translation(app_state.get_ref(), translation_id).await?;
+    let _ = ensure_user_can_edit_post(
+        db_actor_addr.clone(),
+        authed_user.user.id,
+        translation.post_id,
+    )
+    .await?;
+    let translation = db_actor_addr
+        .send(services::translations::UpdatePostTranslation {
+            translation_id: translation_id,
+            subject: translation_data.subject,
+            body: translation_data.body,
+            body_format: body_format,
+            slug: translation_data.slug,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(translation_error)?;
+    Ok(web::Json(TranslationData::from_translation(&translation)))
+}
+
+#[utoipa::path(
+    params(
+        ("translation_id" = i32, path, description = "Translation database id"),
+    ),
+    responses(
+        (status = 200, description = "Ask an admin to publish a translation", body = TranslationData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/translations/request_to_publish/{translation_id}")]
+async fn request_translation_publish(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<TranslationData>, MyError> {
+    let translation_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let translation = get_translation(app_state.get_ref(), translation_id).await?;
+    let _ =
+        ensure_user_can_edit_post(db_actor_addr, authed_user.user.id, translation.post_id).await?;
+    let translation = set_translation_status(
+        app_state.get_ref(),
+        translation_id,
+        PublishStatus::RequestToAdminForPublish,
+    )
+    .await?;
+    Ok(web::Json(TranslationData::from_translation(&translation)))
+}
+
+#[utoipa::path(
+    params(
+        ("translation_id" = i32, path, description = "Translation database id"),
+    ),
+    responses(
+        (status = 200, description = "Publish a translation, it is shown once its Post is published too", body = TranslationData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/translations/publish/{translation_id}")]
+async fn publish_translation(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<TranslationData>, MyError> {
+    let translation_id = path.into_inner();
+    let _ = views::admins::ensure_admin(bearer_auth, app_state.clone()).await?;
+    let translation = set_translation_status(
+        app_state.get_ref(),
+        translation_id,
+        PublishStatus::Published,
+    )
+    .await?;
+    Ok(web::Json(TranslationData::from_translation(&translation)))
+}
+
+#[utoipa::path(
+    params(
+        ("translation_id" = i32, path, description = "Translation database id"),
+    ),
+    responses(
+        (status = 200, description = "Unpublish a translation, readers get the Post in another locale", body = TranslationData)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/translations/unpublish/{translation_id}")]
+async fn unpublish_translation(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<web::Json<TranslationData>, MyError> {
+    let translation_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let translation = get_translation(app_state.get_ref(), translation_id).await?;
+    let _ = ensure_user_can_edit_post_or_is_admin(
+        db_actor_addr,
+        &authed_user.user,
+        translation.post_id,
+    )
+    .await?;
+    let translation = set_translation_status(
+        app_state.get_ref(),
+        translation_id,
+        PublishStatus::Unpublished,
+    )
+    .await?;
+    Ok(web::Json(TranslationData::from_translation(&translation)))
+}
+
+#[utoipa::path(
+    params(
+        ("translation_id" = i32, path, description = "Translation database id"),
+    ),
+    responses(
+        (status = 200, description = "Delete a translation", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+#[post("/posts/translations/delete/{translation_id}")]
+async fn delete_translation(
+    path: web::Path<i32>,
+    bearer_auth: BearerAuth,
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<String, MyError> {
+    let translation_id = path.into_inner();
+    let db_actor_addr = app_state.get_ref().db_actor_addr.clone();
+    let auth_mgr_addr = app_state.get_ref().auth_mgr_addr.clone();
+    let authed_user = views::users::AuthedUser::from_bearer_token(
+        db_actor_addr.clone(),
+        auth_mgr_addr,
+        bearer_auth,
+    )
+    .await?;
+    let translation = get_translation(app_state.get_ref(), translation_id).await?;
+    let _ = ensure_user_can_edit_post(
+        db_actor_addr.clone(),
+        authed_user.user.id,
+        translation.post_id,
+    )
+    .await?;
+    let _ = db_actor_addr
+        .send(services::translations::DeletePostTranslation {
+            translation_id: translation_id,
+        })
+        .await
+        .map_err(|_| MyError::InternalServerError)?
+        .map_err(translation_error)?;
+    Ok("Success!".to_string())
+}
@@ -145,7 +145,7 @@ async fn login(
     let auth_mgr_addr = app_state.as_ref().auth_mgr_addr.clone();
     let password = input_user.password.as_bytes();
     let user = get_user_by_username(db_actor_addr, input_user.username.clone()).await?;
-    if argon2_password_hasher::validate_password(&password, &user.password_hash) {
+    if argon2_password_hasher::validate_password(password, &user.password_hash) {
         let token = auth_mgr_addr
             .send(CreateToken { data: user.id })
             .await